@@ -0,0 +1,32 @@
+//! Serves the mock backend used by the integration tests,
+//! so the app can be tried out without the real backend and judge:
+//!
+//! ```bash
+//! cargo run --example mock_backend
+//! cargo run -- --backend http://127.0.0.1:3000
+//! ```
+#![allow(dead_code)]
+
+#[cfg(not(target_arch = "wasm32"))]
+#[path = "../tests/mock_backend/mod.rs"]
+mod mock_backend;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    let address = std::env::args()
+        .nth(1)
+        .unwrap_or("127.0.0.1:3000".to_string());
+    let backend = mock_backend::MockBackend::start_on(&address);
+    println!(
+        "Mock backend on {}, log in as {} / {}",
+        backend.url(),
+        mock_backend::EMAIL,
+        mock_backend::PASSWORD
+    );
+    loop {
+        std::thread::park();
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
@@ -0,0 +1,475 @@
+use crate::helpers::{
+    admin::{AdminAction, AdminUser, AuditEntry, ADMIN_ROLE, AUDIT_CAPACITY},
+    api::{self, ApiRequest, StatusResponse},
+    AppState,
+};
+use egui_notify::Toasts;
+use scoreboard_db::{NiceTime, Score};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+enum Tab {
+    Users,
+    Scores,
+    AuditLog,
+}
+
+/// An action the backend hasn't answered yet.
+struct Running {
+    audit_id: u64,
+    action: AdminAction,
+    request: ApiRequest<StatusResponse>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct AdminApp {
+    tab: Tab,
+    #[serde(skip)]
+    filter: String,
+    #[serde(skip)]
+    users: Option<Vec<AdminUser>>,
+    #[serde(skip)]
+    users_error: Option<String>,
+    #[serde(skip)]
+    users_requestor: Option<ApiRequest<Vec<AdminUser>>>,
+    #[serde(skip)]
+    table: Option<String>,
+    #[serde(skip)]
+    scores: Vec<Score>,
+    #[serde(skip)]
+    scores_requestor: Option<ApiRequest<Vec<Score>>>,
+    /// Waiting to be confirmed
+    #[serde(skip)]
+    pending: Option<AdminAction>,
+    #[serde(skip)]
+    running: Vec<Running>,
+    #[serde(skip)]
+    toasts: Toasts,
+    #[serde(skip)]
+    app_state: Arc<Mutex<AppState>>,
+}
+
+impl Default for AdminApp {
+    fn default() -> Self {
+        Self {
+            tab: Tab::Users,
+            filter: String::new(),
+            users: None,
+            users_error: None,
+            users_requestor: None,
+            table: None,
+            scores: Vec::new(),
+            scores_requestor: None,
+            pending: None,
+            running: Vec::new(),
+            toasts: Toasts::default(),
+            app_state: Arc::new(Mutex::new(AppState::default())),
+        }
+    }
+}
+
+impl AdminApp {
+    fn fetch_users(&mut self) {
+        self.users_error = None;
+        self.users_requestor = Some(api::admin_users(&self.app_state).latest_wins("admin_users"));
+    }
+
+    fn fetch_scores(&mut self, table: &str) {
+        self.table = Some(table.to_string());
+        self.scores.clear();
+        self.scores_requestor =
+            Some(api::scores(&self.app_state, table).latest_wins("admin_scores"));
+    }
+
+    fn submit(&mut self, action: AdminAction) {
+        let audit_id = {
+            let mut app_state = self.app_state.lock().unwrap();
+            let admin = app_state
+                .identity
+                .as_ref()
+                .map(|identity| identity.email.clone())
+                .unwrap_or_default();
+            app_state.audit_dirty = true;
+            app_state.audit_log.record(&admin, &action)
+        };
+        let request = api::admin(&self.app_state, &action);
+        self.running.push(Running {
+            audit_id,
+            action,
+            request,
+        });
+    }
+
+    fn check_users_promise(&mut self) {
+        let result = match &mut self.users_requestor {
+            Some(requestor) => requestor.check(),
+            None => return,
+        };
+        match result {
+            None => {}
+            Some(Err(api::ApiError::Cancelled)) => self.users_requestor = None,
+            Some(Err(e)) => {
+                self.users_error = Some(e.to_string());
+                self.users_requestor = None;
+            }
+            Some(Ok(users)) => {
+                self.users = Some(users);
+                self.users_requestor = None;
+            }
+        }
+    }
+
+    fn check_scores_promise(&mut self) {
+        let result = match &mut self.scores_requestor {
+            Some(requestor) => requestor.check(),
+            None => return,
+        };
+        match result {
+            None => {}
+            Some(Err(api::ApiError::Cancelled)) => self.scores_requestor = None,
+            Some(Err(e)) => {
+                self.toasts
+                    .error(format!("Failed to fetch scores: {}", e))
+                    .set_duration(Some(Duration::from_secs(5)));
+                self.scores_requestor = None;
+            }
+            Some(Ok(scores)) => {
+                self.scores = scores;
+                self.scores_requestor = None;
+            }
+        }
+    }
+
+    fn check_running(&mut self) {
+        let mut finished = Vec::new();
+        self.running
+            .retain_mut(|running| match running.request.check() {
+                None => true,
+                Some(result) => {
+                    finished.push((running.audit_id, running.action.clone(), result));
+                    false
+                }
+            });
+
+        for (audit_id, action, result) in finished {
+            let outcome = result.map(|r| r.message).map_err(|e| e.to_string());
+            {
+                let mut app_state = self.app_state.lock().unwrap();
+                app_state.audit_log.complete(audit_id, outcome.clone());
+                app_state.audit_dirty = true;
+            }
+            match outcome {
+                Ok(_) => {
+                    self.apply(&action);
+                    self.toasts
+                        .info(format!("Done: {}", action))
+                        .set_duration(Some(Duration::from_secs(5)));
+                }
+                Err(e) => {
+                    self.toasts
+                        .error(format!("{} failed: {}", action, e))
+                        .set_duration(Some(Duration::from_secs(5)));
+                }
+            }
+        }
+    }
+
+    /// Shows what the backend has just done without fetching everything again.
+    fn apply(&mut self, action: &AdminAction) {
+        let set_locked = |users: &mut Option<Vec<AdminUser>>, email: &str, locked: bool| {
+            for user in users.iter_mut().flatten().filter(|u| u.email == email) {
+                user.locked = locked;
+            }
+        };
+        match action {
+            AdminAction::Lock { email } => set_locked(&mut self.users, email, true),
+            AdminAction::Unlock { email } => set_locked(&mut self.users, email, false),
+            AdminAction::ResetPassword { .. } => {}
+            AdminAction::RemoveScore { table, score }
+            | AdminAction::DisqualifyScore { table, score } => {
+                if self.table.as_ref() == Some(table) {
+                    self.scores.retain(|s| s != score);
+                }
+                if let Some(scores) = self.app_state.lock().unwrap().scoreboards.get_mut(table) {
+                    scores.retain(|s| s != score);
+                }
+            }
+        }
+    }
+
+    fn ui_confirm(&mut self, ctx: &egui::Context) {
+        let action = match &self.pending {
+            Some(action) => action.clone(),
+            None => return,
+        };
+        egui::Window::new("Confirm admin action")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("{}?", action));
+                ui.weak("This will be recorded in the audit log.");
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Confirm").clicked() {
+                        self.pending = None;
+                        self.submit(action.clone());
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.pending = None;
+                    }
+                });
+            });
+    }
+
+    fn ui_users(&mut self, ui: &mut egui::Ui) {
+        if self.users.is_none() && self.users_requestor.is_none() && self.users_error.is_none() {
+            self.fetch_users();
+        }
+        ui.horizontal(|ui| {
+            ui.label("Filter:");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.filter)
+                    .hint_text("Name or email")
+                    .desired_width(200.0),
+            );
+            if ui.button("Refresh").clicked() {
+                self.fetch_users();
+            }
+            if self.users_requestor.is_some() {
+                ui.spinner();
+            }
+        });
+        if let Some(e) = &self.users_error {
+            ui.colored_label(
+                ui.visuals().error_fg_color,
+                format!("Couldn't list users: {}", e),
+            );
+        }
+        ui.separator();
+
+        let filter = self.filter.to_lowercase();
+        let users: Vec<AdminUser> = self
+            .users
+            .iter()
+            .flatten()
+            .filter(|user| {
+                user.email.to_lowercase().contains(&filter)
+                    || user.name.to_lowercase().contains(&filter)
+            })
+            .cloned()
+            .collect();
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("admin_users_grid")
+                .num_columns(5)
+                .striped(true)
+                .show(ui, |ui| {
+                    for title in ["Name", "Email", "Roles", "Status", ""] {
+                        ui.strong(title);
+                    }
+                    ui.end_row();
+
+                    for user in users {
+                        ui.label(&user.name);
+                        ui.label(&user.email);
+                        ui.label(user.roles.join(", "));
+                        let status = match (user.locked, user.verified) {
+                            (true, _) => "Locked",
+                            (false, false) => "Unverified",
+                            (false, true) => "Active",
+                        };
+                        ui.label(status);
+                        ui.horizontal(|ui| {
+                            let email = user.email.clone();
+                            let toggle = match user.locked {
+                                true => ("Unlock", AdminAction::Unlock { email }),
+                                false => ("Lock", AdminAction::Lock { email }),
+                            };
+                            if ui.button(toggle.0).clicked() {
+                                self.pending = Some(toggle.1);
+                            }
+                            if ui.button("Reset Password").clicked() {
+                                self.pending = Some(AdminAction::ResetPassword {
+                                    email: user.email.clone(),
+                                });
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+
+    fn ui_scores(&mut self, ui: &mut egui::Ui) {
+        let challenges = self.app_state.lock().unwrap().challenges.items.clone();
+        let selected = self.table.clone();
+        let selected_text = challenges
+            .iter()
+            .find(|challenge| Some(&challenge.table) == selected.as_ref())
+            .map(|challenge| challenge.name.clone())
+            .unwrap_or("None".to_string());
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label("Challenge")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    for challenge in &challenges {
+                        let is_selected = Some(&challenge.table) == selected.as_ref();
+                        if ui.selectable_label(is_selected, &challenge.name).clicked() {
+                            self.fetch_scores(&challenge.table);
+                        }
+                    }
+                });
+            if self.scores_requestor.is_some() {
+                ui.spinner();
+            }
+        });
+        ui.separator();
+
+        let table = match &self.table {
+            Some(table) => table.clone(),
+            None => return,
+        };
+        let scores = self.scores.clone();
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("admin_scores_grid")
+                .num_columns(4)
+                .striped(true)
+                .show(ui, |ui| {
+                    for title in ["Name", "Language", "Time", ""] {
+                        ui.strong(title);
+                    }
+                    ui.end_row();
+
+                    for score in scores {
+                        ui.label(&score.name);
+                        ui.label(&score.language);
+                        ui.label(NiceTime::new(score.time_ns).to_string());
+                        ui.horizontal(|ui| {
+                            if ui.button("Disqualify").clicked() {
+                                self.pending = Some(AdminAction::DisqualifyScore {
+                                    table: table.clone(),
+                                    score: score.clone(),
+                                });
+                            }
+                            if ui.button("Remove").clicked() {
+                                self.pending = Some(AdminAction::RemoveScore {
+                                    table: table.clone(),
+                                    score: score.clone(),
+                                });
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+
+    fn ui_audit_log(&mut self, ui: &mut egui::Ui) {
+        // newest first
+        let entries: Vec<AuditEntry> = self
+            .app_state
+            .lock()
+            .unwrap()
+            .audit_log
+            .entries()
+            .rev()
+            .cloned()
+            .collect();
+        ui.label(format!(
+            "The last {} actions taken from this device, kept between runs",
+            AUDIT_CAPACITY
+        ));
+        if entries.is_empty() {
+            ui.label("Nothing has been done yet");
+            return;
+        }
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("admin_audit_grid")
+                .num_columns(4)
+                .striped(true)
+                .show(ui, |ui| {
+                    for title in ["Time", "Admin", "Action", "Outcome"] {
+                        ui.strong(title);
+                    }
+                    ui.end_row();
+
+                    for entry in entries {
+                        // kept between runs, so the day matters too
+                        ui.label(
+                            entry
+                                .time
+                                .with_timezone(&chrono::Local)
+                                .format("%d %b %H:%M:%S")
+                                .to_string(),
+                        );
+                        ui.label(&entry.admin);
+                        ui.label(&entry.action);
+                        match &entry.outcome {
+                            None => ui.spinner(),
+                            Some(Ok(_)) => ui.label("✔ Done"),
+                            Some(Err(e)) => {
+                                ui.colored_label(ui.visuals().error_fg_color, format!("✖ {}", e))
+                            }
+                        };
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+}
+
+impl super::App for AdminApp {
+    fn name(&self) -> &'static str {
+        "🛡 Admin"
+    }
+
+    fn set_app_state_ref(&mut self, app_state: Arc<Mutex<AppState>>) {
+        self.app_state = app_state;
+    }
+
+    fn required_role(&self) -> Option<&'static str> {
+        Some(ADMIN_ROLE)
+    }
+
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        self.check_users_promise();
+        self.check_scores_promise();
+        self.check_running();
+
+        use super::View as _;
+        egui::Window::new(self.name())
+            .open(open)
+            .default_height(500.0)
+            .show(ctx, |ui| self.ui(ui));
+        if *open {
+            self.ui_confirm(ctx);
+        } else {
+            self.pending = None;
+        }
+
+        self.toasts.show(ctx);
+    }
+}
+
+impl super::View for AdminApp {
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        if !self.app_state.lock().unwrap().has_role(ADMIN_ROLE) {
+            ui.label("Only admins can use the admin console");
+            self.users = None;
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.tab, Tab::Users, "Users");
+            ui.selectable_value(&mut self.tab, Tab::Scores, "Scores");
+            ui.selectable_value(&mut self.tab, Tab::AuditLog, "Audit Log");
+        });
+        ui.separator();
+        match self.tab {
+            Tab::Users => self.ui_users(ui),
+            Tab::Scores => self.ui_scores(ui),
+            Tab::AuditLog => self.ui_audit_log(ui),
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use super::App;
-use crate::helpers::AppState;
+use crate::helpers::{deep_link::DeepLink, AppState};
 use egui::{Context, ScrollArea, Ui};
 use std::collections::BTreeSet;
 use std::sync::{Arc, Mutex};
@@ -19,9 +19,18 @@ impl Default for Apps {
             Box::<super::login_app::LoginApp>::default(),
             Box::<super::scoreboard_app::ScoreBoardApp>::default(),
             Box::<super::challenge_info::ChallengeInfoApp>::default(),
+            Box::<super::WhatsNewApp>::default(),
             Box::<super::code_editor::CodeEditor>::default(),
             Box::<super::binary_upload::BinaryUpload>::default(),
+            Box::<super::SubmissionQueueApp>::default(),
+            Box::<super::SubmissionHistoryApp>::default(),
             Box::<super::PasswordResetApp>::default(),
+            Box::<super::ProfileApp>::default(),
+            Box::<super::AdminApp>::default(),
+            Box::<super::ChallengeAuthorApp>::default(),
+            Box::<super::SettingsApp>::default(),
+            Box::<super::NetworkApp>::default(),
+            Box::<super::ConsoleApp>::default(),
         ])
     }
 }
@@ -35,8 +44,12 @@ impl Apps {
     }
 
     #[allow(dead_code)] //inhibit warnings when target =/= WASM
-    pub fn checkboxes(&mut self, ui: &mut Ui) {
+    pub fn checkboxes(&mut self, ui: &mut Ui, app_state: &AppState) {
         let Self { apps, open } = self;
+        let apps: Vec<_> = apps
+            .iter()
+            .filter(|app| permitted(app.as_ref(), app_state))
+            .collect();
         ui.label(format!("{} apps", apps.len()));
         for app in apps {
             let mut is_open = open.contains(app.name());
@@ -45,17 +58,41 @@ impl Apps {
         }
     }
 
+    pub fn open(&mut self, name: &'static str) {
+        set_open(&mut self.open, name, true);
+    }
+
+    /// Opens the windows that handle `link`, they pick it up when next shown.
+    pub fn open_for(&mut self, link: &DeepLink) {
+        let Self { apps, open } = self;
+        for app in apps.iter().filter(|app| app.handles_link(link)) {
+            set_open(open, app.name(), true);
+        }
+    }
+
+    /* Windows the user isn't allowed are still shown closed,
+    so whatever they had in flight is seen through */
     #[allow(dead_code)] //inhibit warnings when target =/= WASM
-    pub fn windows(&mut self, ctx: &Context) {
+    pub fn windows(&mut self, ctx: &Context, app_state: &Arc<Mutex<AppState>>) {
         let Self { apps, open } = self;
         for app in apps {
-            let mut is_open = open.contains(app.name());
+            let allowed = permitted(app.as_ref(), &app_state.lock().unwrap());
+            let mut is_open = allowed && open.contains(app.name());
             app.show(ctx, &mut is_open);
-            set_open(open, app.name(), is_open);
+            if allowed {
+                set_open(open, app.name(), is_open);
+            }
         }
     }
 }
 
+fn permitted(app: &dyn App, app_state: &AppState) -> bool {
+    match app.required_role() {
+        Some(role) => app_state.has_role(role),
+        None => true,
+    }
+}
+
 // ----------------------------------------------------------------------------
 
 fn set_open(open: &mut BTreeSet<String>, key: &'static str, is_open: bool) {
@@ -145,9 +182,23 @@ impl AppWindows {
         self.show_windows(ctx);
     }
 
+    /* A backend asked for at start stays in front until it is added or ignored */
     #[allow(dead_code)] //inhibit warnings when target =/= WASM
     fn show_windows(&mut self, ctx: &Context) {
-        self.apps.windows(ctx);
+        let (link, pending_backend) = {
+            let app_state = self.app_state.lock().unwrap();
+            (
+                app_state.deep_link.clone(),
+                app_state.config.pending_backend().is_some(),
+            )
+        };
+        if let Some(link) = link {
+            self.apps.open_for(&link);
+        }
+        if pending_backend {
+            self.apps.open(super::SettingsApp::default().name());
+        }
+        self.apps.windows(ctx, &self.app_state);
     }
 
     #[allow(dead_code)] //inhibit warnings when target =/= WASM
@@ -156,7 +207,8 @@ impl AppWindows {
             ui.with_layout(egui::Layout::top_down_justified(egui::Align::LEFT), |ui| {
                 // ui.toggle_value(&mut self.about_is_open, self.about.name());
 
-                self.apps.checkboxes(ui);
+                let app_state = self.app_state.clone();
+                self.apps.checkboxes(ui, &app_state.lock().unwrap());
             });
         });
     }
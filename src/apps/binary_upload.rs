@@ -2,11 +2,10 @@ use std::borrow::BorrowMut;
 use std::sync::{Arc, Mutex};
 
 use crate::helpers::{
-    fetchers::Requestor,
-    submission::{Submission, SubmissionResult},
+    execute, retry,
+    submission::{PendingSubmission, Submission, SubmissionResult},
     AppState, Languages,
 };
-use std::future::Future;
 use std::sync::mpsc::{channel, Receiver, Sender};
 
 struct Binary {
@@ -19,13 +18,15 @@ struct Binary {
 pub struct BinaryUpload {
     #[serde(skip)]
     last_result: SubmissionResult,
-    url: String,
     #[serde(skip)]
     run: Submission,
     #[serde(skip)]
     binary_channel: (Sender<Binary>, Receiver<Binary>),
     #[serde(skip)]
-    submitter: Option<Requestor>,
+    submitter: Option<PendingSubmission>,
+    /// Picked here rather than taken from the profile
+    #[serde(skip)]
+    language_chosen: bool,
     #[serde(skip)]
     app_state: Arc<Mutex<AppState>>,
 }
@@ -33,9 +34,6 @@ pub struct BinaryUpload {
 impl Default for BinaryUpload {
     fn default() -> Self {
         Self {
-            url: option_env!("BACKEND_URL")
-                .unwrap_or("http://123.4.5.6:3000/")
-                .to_string(),
             run: Submission {
                 filename: "Select Binary".to_string(),
                 ..Default::default()
@@ -43,6 +41,7 @@ impl Default for BinaryUpload {
             binary_channel: channel(),
             submitter: None,
             last_result: SubmissionResult::NotStarted,
+            language_chosen: false,
             app_state: Arc::new(Mutex::new(AppState::default())),
         }
     }
@@ -50,10 +49,7 @@ impl Default for BinaryUpload {
 
 impl BinaryUpload {
     fn submit(&mut self) {
-        let submission = self.run.clone();
-        let url = format!("{}api/game/binary", self.url);
-        let app_state = Arc::clone(&self.app_state);
-        self.submitter = submission.sender(app_state, &url);
+        self.submitter = self.run.clone().sender(&self.app_state);
     }
 }
 
@@ -67,6 +63,11 @@ impl super::App for BinaryUpload {
     }
 
     fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        if !self.language_chosen {
+            if let Some(profile) = &self.app_state.lock().unwrap().user_profile {
+                self.run.language = profile.language;
+            }
+        }
         use super::View as _;
         egui::Window::new(self.name())
             .open(open)
@@ -79,7 +80,7 @@ impl super::App for BinaryUpload {
             ctx.request_repaint();
         }
 
-        let submission = Submission::check_sender(&mut self.submitter);
+        let submission = Submission::check_sender(&mut self.submitter, &self.app_state);
         match submission {
             SubmissionResult::NotStarted => {}
             _ => {
@@ -88,6 +89,7 @@ impl super::App for BinaryUpload {
             }
         }
         if let Some(fetcher) = self.submitter.borrow_mut() {
+            retry::repaint_while_waiting(ctx, fetcher.next_retry());
             if fetcher.refresh_context() {
                 ctx.request_repaint();
             }
@@ -104,7 +106,12 @@ impl super::View for BinaryUpload {
                 ui.set_min_width(60.0);
 
                 for language in Languages::iter() {
-                    ui.selectable_value(&mut self.run.language, language, format!("{}", language));
+                    if ui
+                        .selectable_value(&mut self.run.language, language, format!("{}", language))
+                        .clicked()
+                    {
+                        self.language_chosen = true;
+                    }
                 }
             });
 
@@ -133,6 +140,7 @@ impl super::View for BinaryUpload {
                 .unwrap()
                 .update_activity_timer();
             let sender = self.binary_channel.0.clone();
+            let ctx = ui.ctx().clone();
             let task = rfd::AsyncFileDialog::new().pick_file();
             execute(async move {
                 let file = task.await;
@@ -142,6 +150,7 @@ impl super::View for BinaryUpload {
                         filename: file.file_name(),
                         bytes,
                     });
+                    ctx.request_repaint();
                 }
             });
         }
@@ -171,13 +180,3 @@ impl super::View for BinaryUpload {
         }
     }
 }
-
-#[cfg(not(target_arch = "wasm32"))]
-fn execute<F: Future<Output = ()> + Send + 'static>(_f: F) {
-    todo!();
-}
-
-#[cfg(target_arch = "wasm32")]
-fn execute<F: Future<Output = ()> + 'static>(f: F) {
-    wasm_bindgen_futures::spawn_local(f);
-}
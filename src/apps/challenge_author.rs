@@ -0,0 +1,406 @@
+use crate::helpers::{
+    admin::ADMIN_ROLE,
+    api::{self, ApiError, ApiRequest, StatusResponse},
+    execute, AppState, Attachment, AuthoredChallenge, ChallengeDraft, Publication,
+};
+use egui_commonmark::*;
+use egui_notify::Toasts;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ChallengeAuthorApp {
+    /// Kept between runs, apart from the attachments
+    draft: ChallengeDraft,
+    #[serde(skip)]
+    attachment_channel: (Sender<Attachment>, Receiver<Attachment>),
+    #[serde(skip)]
+    save_requestor: Option<(ApiRequest<StatusResponse>, ChallengeDraft)>,
+    /// Drafts included, `None` until fetched
+    #[serde(skip)]
+    authored: Option<Vec<AuthoredChallenge>>,
+    #[serde(skip)]
+    authored_fetcher: Option<ApiRequest<Vec<AuthoredChallenge>>>,
+    /// Session the challenges were fetched for
+    #[serde(skip)]
+    active_session: String,
+    /// Waiting for "Save Draft" on a published challenge to be confirmed
+    #[serde(skip)]
+    confirm_unpublish: bool,
+    #[serde(skip)]
+    preview_cache: CommonMarkCache,
+    #[serde(skip)]
+    toasts: Toasts,
+    #[serde(skip)]
+    app_state: Arc<Mutex<AppState>>,
+}
+
+impl Default for ChallengeAuthorApp {
+    fn default() -> Self {
+        Self {
+            draft: ChallengeDraft::default(),
+            attachment_channel: channel(),
+            save_requestor: None,
+            authored: None,
+            authored_fetcher: None,
+            active_session: String::new(),
+            confirm_unpublish: false,
+            preview_cache: CommonMarkCache::default(),
+            toasts: Toasts::default(),
+            app_state: Arc::new(Mutex::new(AppState::default())),
+        }
+    }
+}
+
+impl ChallengeAuthorApp {
+    /* Fetched once the window is opened by an admin, and again for each session */
+    fn check_for_fetch(&mut self, open: bool) {
+        let (session, admin) = {
+            let app_state = self.app_state.lock().unwrap();
+            (
+                app_state.config.session_key(),
+                app_state.has_role(ADMIN_ROLE),
+            )
+        };
+        if open && admin && session != self.active_session {
+            self.active_session = session;
+            self.authored = None;
+            self.authored_fetcher = Some(api::admin_challenges(&self.app_state));
+        }
+    }
+
+    fn check_authored_fetcher(&mut self) {
+        let result = match self.authored_fetcher.as_mut().and_then(|f| f.check()) {
+            Some(result) => result,
+            None => return,
+        };
+        self.authored_fetcher = None;
+        match result {
+            Ok(authored) => self.authored = Some(authored),
+            Err(e) => {
+                log::error!("Failed to fetch the drafts: {}", e);
+                self.toasts
+                    .error(format!(
+                        "Couldn't fetch the drafts, only published challenges are listed: {}",
+                        e
+                    ))
+                    .set_duration(Some(Duration::from_secs(5)));
+            }
+        }
+    }
+
+    /// Everything that can be edited, only the published challenges until the drafts are fetched.
+    fn authored(&self) -> Vec<AuthoredChallenge> {
+        match &self.authored {
+            Some(authored) => authored.clone(),
+            None => self
+                .app_state
+                .lock()
+                .unwrap()
+                .challenges
+                .items
+                .iter()
+                .map(|challenge| AuthoredChallenge {
+                    challenge: challenge.clone(),
+                    published: true,
+                })
+                .collect(),
+        }
+    }
+
+    /* Saving a published challenge as a draft takes it off everyone's list,
+    so that has to be confirmed first */
+    fn save_draft(&mut self) {
+        let published = match &self.draft.editing {
+            Some(command) => self
+                .authored()
+                .iter()
+                .any(|a| &a.challenge.command == command && a.published),
+            None => false,
+        };
+        match published {
+            true => self.confirm_unpublish = true,
+            false => self.submit(Publication::Draft),
+        }
+    }
+
+    fn submit(&mut self, publication: Publication) {
+        self.confirm_unpublish = false;
+        self.draft.publication = publication;
+        let existing: Vec<_> = self.authored().into_iter().map(|a| a.challenge).collect();
+        let result = self.draft.validate(&existing);
+        if let Err(e) = result {
+            self.toasts
+                .error(e)
+                .set_duration(Some(Duration::from_secs(5)));
+            return;
+        }
+        let request = api::save_challenge(&self.app_state, &self.draft);
+        self.save_requestor = Some((request, self.draft.clone()));
+    }
+
+    fn check_save_promise(&mut self) {
+        let result = match &mut self.save_requestor {
+            Some((requestor, _)) => requestor.check(),
+            None => return,
+        };
+        match result {
+            None => {}
+            /* Another admin may have taken it since the list was fetched,
+            so the backend has the final say on what is taken */
+            Some(Err(ApiError::Http {
+                status: 409,
+                message,
+            })) => {
+                self.toasts
+                    .error(format!("Not saved, already taken: {}", message))
+                    .set_duration(Some(Duration::from_secs(5)));
+                self.save_requestor = None;
+            }
+            Some(Err(e)) => {
+                self.toasts
+                    .error(format!("Failed to save challenge: {}", e))
+                    .set_duration(Some(Duration::from_secs(5)));
+                self.save_requestor = None;
+            }
+            Some(Ok(_)) => {
+                let (_, saved) = self.save_requestor.take().unwrap();
+                let message = {
+                    let challenges = &mut self.app_state.lock().unwrap().challenges;
+                    match saved.publication {
+                        Publication::Draft => {
+                            if let Some(previous) = &saved.editing {
+                                challenges.remove(previous);
+                            }
+                            format!("Saved draft of {}", saved.name)
+                        }
+                        Publication::Published => {
+                            challenges.upsert(saved.editing.as_deref(), saved.to_challenge());
+                            format!("Published {}", saved.name)
+                        }
+                    }
+                };
+                if let Some(authored) = &mut self.authored {
+                    let previous = saved.editing.as_ref().unwrap_or(&saved.command);
+                    authored.retain(|a| &a.challenge.command != previous);
+                    authored.push(AuthoredChallenge {
+                        challenge: saved.to_challenge(),
+                        published: saved.publication == Publication::Published,
+                    });
+                }
+                // later saves update what was just saved
+                self.draft.editing = Some(saved.command.clone());
+                self.draft.attachments.clear();
+                self.toasts
+                    .info(message)
+                    .set_duration(Some(Duration::from_secs(5)));
+            }
+        }
+    }
+
+    fn pick_attachments(&mut self, ctx: &egui::Context) {
+        let sender = self.attachment_channel.0.clone();
+        let ctx = ctx.clone();
+        let task = rfd::AsyncFileDialog::new().pick_files();
+        execute(async move {
+            for file in task.await.unwrap_or_default() {
+                let bytes = file.read().await;
+                let _ = sender.send(Attachment {
+                    filename: file.file_name(),
+                    bytes,
+                });
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    fn ui_fields(&mut self, ui: &mut egui::Ui) {
+        let authored = self.authored();
+        ui.horizontal(|ui| {
+            if ui.button("New").clicked() {
+                self.draft = ChallengeDraft::default();
+                self.confirm_unpublish = false;
+            }
+            egui::ComboBox::from_label("Edit")
+                .selected_text(self.draft.editing.clone().unwrap_or("New".to_string()))
+                .show_ui(ui, |ui| {
+                    for authored in &authored {
+                        let challenge = &authored.challenge;
+                        let selected = self.draft.editing.as_ref() == Some(&challenge.command);
+                        let label = match authored.published {
+                            true => challenge.name.clone(),
+                            false => format!("{} (draft)", challenge.name),
+                        };
+                        if ui.selectable_label(selected, label).clicked() {
+                            self.draft = ChallengeDraft::edit(authored);
+                            self.confirm_unpublish = false;
+                        }
+                    }
+                });
+            if self.authored_fetcher.is_some() {
+                ui.spinner();
+            }
+        });
+
+        egui::Grid::new("challenge_author_grid")
+            .num_columns(2)
+            .spacing([20.0, 4.0])
+            .show(ui, |ui| {
+                ui.label("Name:");
+                ui.text_edit_singleline(&mut self.draft.name);
+                ui.end_row();
+
+                ui.label("Command:");
+                ui.text_edit_singleline(&mut self.draft.command)
+                    .on_hover_text("What players pass to the judge, e.g. 2331");
+                ui.end_row();
+
+                ui.label("Table:");
+                ui.text_edit_singleline(&mut self.draft.table)
+                    .on_hover_text("Where the scores are kept, e.g. 23_3_1");
+                ui.end_row();
+
+                ui.label("State:");
+                ui.label(match self.draft.publication {
+                    Publication::Draft => "Draft",
+                    Publication::Published => "Published",
+                });
+                ui.end_row();
+            });
+    }
+
+    fn ui_attachments(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Sample data:");
+            if ui.button("Attach Files").clicked() {
+                self.pick_attachments(ui.ctx());
+            }
+        });
+        let mut remove = None;
+        for (i, attachment) in self.draft.attachments.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "{} ({} KiB)",
+                    attachment.filename,
+                    (attachment.bytes.len() + 1023) / 1024
+                ));
+                if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove {
+            self.draft.attachments.remove(i);
+        }
+    }
+
+    fn ui_editor(&mut self, ui: &mut egui::Ui) {
+        ui.columns(2, |columns| {
+            columns[0].label("Instructions (markdown):");
+            egui::ScrollArea::vertical()
+                .id_source("challenge_author_doc")
+                .show(&mut columns[0], |ui| {
+                    ui.add(
+                        egui::TextEdit::multiline(&mut self.draft.doc)
+                            .font(egui::TextStyle::Monospace)
+                            .code_editor()
+                            .desired_rows(20)
+                            .desired_width(f32::INFINITY),
+                    );
+                });
+
+            columns[1].label("Preview:");
+            egui::ScrollArea::vertical()
+                .id_source("challenge_author_preview")
+                .show(&mut columns[1], |ui| {
+                    CommonMarkViewer::new("challenge_author_viewer").show(
+                        ui,
+                        &mut self.preview_cache,
+                        &self.draft.doc,
+                    );
+                });
+        });
+    }
+}
+
+impl super::App for ChallengeAuthorApp {
+    fn name(&self) -> &'static str {
+        "✏ Challenge Author"
+    }
+
+    fn set_app_state_ref(&mut self, app_state: Arc<Mutex<AppState>>) {
+        self.app_state = app_state;
+    }
+
+    fn required_role(&self) -> Option<&'static str> {
+        Some(ADMIN_ROLE)
+    }
+
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        self.check_for_fetch(*open);
+        self.check_authored_fetcher();
+        self.check_save_promise();
+        while let Ok(attachment) = self.attachment_channel.1.try_recv() {
+            self.draft.attachments.push(attachment);
+        }
+
+        use super::View as _;
+        egui::Window::new(self.name())
+            .open(open)
+            .default_width(900.0)
+            .default_height(600.0)
+            .show(ctx, |ui| self.ui(ui));
+
+        self.toasts.show(ctx);
+    }
+}
+
+impl super::View for ChallengeAuthorApp {
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        if !self.app_state.lock().unwrap().has_role(ADMIN_ROLE) {
+            ui.label("Only admins can author challenges");
+            return;
+        }
+        self.ui_fields(ui);
+        self.ui_attachments(ui);
+        ui.separator();
+
+        let idle = self.save_requestor.is_none() && !self.confirm_unpublish;
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(idle, egui::Button::new("Save Draft"))
+                .on_hover_text("Only admins will see it")
+                .clicked()
+            {
+                self.save_draft();
+            }
+            if ui.add_enabled(idle, egui::Button::new("Publish")).clicked() {
+                self.submit(Publication::Published);
+            }
+            if self.save_requestor.is_some() {
+                ui.spinner();
+            }
+        });
+        if self.confirm_unpublish {
+            ui.horizontal(|ui| {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!(
+                        "{} is published, as a draft players won't see it any more",
+                        self.draft.name
+                    ),
+                );
+                if ui.button("Unpublish").clicked() {
+                    self.submit(Publication::Draft);
+                }
+                if ui.button("Cancel").clicked() {
+                    self.confirm_unpublish = false;
+                }
+            });
+        }
+        ui.separator();
+        self.ui_editor(ui);
+    }
+}
@@ -1,20 +1,42 @@
-use crate::helpers::AppState;
+use crate::components::badge::challenge_badges;
+use crate::helpers::{
+    api::{self, ApiError, ApiRequest},
+    deep_link::DeepLink,
+    downloads::{self, ChallengeFile, SaveResult},
+    retry, AppState, Availability, ChallengeQuery, ChallengeSort, LoginState,
+};
+use egui::Key;
 use egui_commonmark::*;
+use egui_notify::Toasts;
+use scoreboard_db::Score;
+use std::collections::HashSet;
+use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::{Arc, Mutex};
-
-#[derive(PartialEq, Clone, Copy, serde::Deserialize, serde::Serialize)]
-enum FilterOption {
-    All,
-    UniquePlayers,
-    UniqueLanguage,
-}
+use std::time::Duration;
 
 #[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
 pub struct ChallengeInfoApp {
     selected_challenge: String,
     #[serde(skip)]
     active_challenge: Option<String>,
     instructions: String,
+    query: ChallengeQuery,
+    /// Fetching the scores of a table to tell whether it's solved
+    #[serde(skip)]
+    solved_fetcher: Option<(String, ApiRequest<Vec<Score>>)>,
+    /// Tables already fetched or given up on, for `active_session`
+    #[serde(skip)]
+    checked_tables: HashSet<String>,
+    #[serde(skip)]
+    active_session: String,
+    #[serde(skip)]
+    downloads: Vec<(ChallengeFile, ApiRequest<Vec<u8>>)>,
+    /// Where each download was saved
+    #[serde(skip)]
+    saved_channel: (Sender<SaveResult>, Receiver<SaveResult>),
+    #[serde(skip)]
+    toasts: Toasts,
     #[serde(skip)]
     app_state: Arc<Mutex<AppState>>,
 }
@@ -25,11 +47,297 @@ impl Default for ChallengeInfoApp {
             selected_challenge: "".to_string(),
             active_challenge: None,
             instructions: "None".to_string(),
+            query: ChallengeQuery::default(),
+            solved_fetcher: None,
+            checked_tables: HashSet::new(),
+            active_session: String::new(),
+            downloads: Vec::new(),
+            saved_channel: channel(),
+            toasts: Toasts::default(),
             app_state: Arc::new(Mutex::new(AppState::default())),
         }
     }
 }
 
+impl ChallengeInfoApp {
+    /* Solved is worked out from the scoreboards, so the ones
+    not seen yet are fetched one at a time while the browser is open */
+    fn fetch_next_scoreboard(&mut self) {
+        if self.solved_fetcher.is_some() {
+            return;
+        }
+        let table = {
+            let app_state = self.app_state.lock().unwrap();
+            let session = app_state.config.session_key();
+            if session != self.active_session {
+                self.active_session = session;
+                self.checked_tables.clear();
+            }
+            if !matches!(app_state.logged_in, LoginState::LoggedIn) {
+                return;
+            }
+            app_state
+                .challenges
+                .items
+                .iter()
+                .map(|c| c.table.clone())
+                .find(|t| {
+                    !self.checked_tables.contains(t) && !app_state.scoreboards.contains_key(t)
+                })
+        };
+        if let Some(table) = table {
+            self.checked_tables.insert(table.clone());
+            self.solved_fetcher = Some((table.clone(), api::scores(&self.app_state, &table)));
+        }
+    }
+
+    fn check_solved_promise(&mut self) {
+        let result = match &mut self.solved_fetcher {
+            Some((_, fetcher)) => fetcher.check(),
+            None => return,
+        };
+        let table = match result {
+            Some(_) => self.solved_fetcher.take().unwrap().0,
+            None => return,
+        };
+        match result {
+            Some(Ok(scores)) => {
+                self.app_state.lock().unwrap().store_scores(&table, scores);
+            }
+            Some(Err(ApiError::Cancelled)) | None => {}
+            Some(Err(e)) => log::warn!("Couldn't tell if {} is solved: {}", table, e),
+        }
+    }
+
+    /* Checked against the listing before the player is asked
+    where to save it, a broken sample wastes a lot of their time */
+    fn check_downloads(&mut self, ctx: &egui::Context) {
+        let mut finished = Vec::new();
+        self.downloads
+            .retain_mut(|(file, request)| match request.check() {
+                Some(result) => {
+                    finished.push((file.clone(), result));
+                    false
+                }
+                None => true,
+            });
+        for (file, result) in finished {
+            let result = result
+                .map_err(|e| format!("Failed to download {}: {}", file.name, e))
+                .and_then(|bytes| file.verify(&bytes).map(|_| bytes));
+            match result {
+                Ok(bytes) => downloads::save_file(
+                    &file.name,
+                    bytes,
+                    ctx.clone(),
+                    self.saved_channel.0.clone(),
+                ),
+                Err(e) => {
+                    log::error!("{}", e);
+                    self.toasts
+                        .error(e)
+                        .set_duration(Some(Duration::from_secs(5)));
+                }
+            }
+        }
+        while let Ok(saved) = self.saved_channel.1.try_recv() {
+            match saved {
+                Ok(path) => self.toasts.info(format!("Saved {}", path)),
+                Err(e) => self.toasts.error(e),
+            }
+            .set_duration(Some(Duration::from_secs(5)));
+        }
+    }
+
+    fn files_ui(&mut self, ui: &mut egui::Ui) {
+        let files = self
+            .app_state
+            .lock()
+            .unwrap()
+            .challenges
+            .items
+            .iter()
+            .find(|c| c.command == self.selected_challenge)
+            .map(|c| c.meta.files.clone())
+            .unwrap_or_default();
+        if files.is_empty() {
+            return;
+        }
+        ui.collapsing(format!("Downloads ({})", files.len()), |ui| {
+            for file in files {
+                ui.horizontal(|ui| {
+                    let downloading = self.downloads.iter().any(|(f, _)| *f == file);
+                    if ui
+                        .add_enabled(!downloading, egui::Button::new(format!("⬇ {}", file.name)))
+                        .clicked()
+                    {
+                        let request =
+                            api::download(&self.app_state, &self.selected_challenge, &file);
+                        self.downloads.push((file.clone(), request));
+                    }
+                    ui.label(format!(
+                        "{}, {}",
+                        file.kind,
+                        file.size
+                            .map_or("unknown size".to_string(), downloads::format_size)
+                    ));
+                    if file.sha1.is_some() {
+                        ui.label("🔒").on_hover_text("Checked once downloaded");
+                    }
+                    if downloading {
+                        ui.spinner();
+                    }
+                });
+            }
+        });
+        ui.separator();
+    }
+
+    fn open_in_editor(&mut self) {
+        if self.selected_challenge.is_empty() {
+            return;
+        }
+        log::info!("Opening {} in the code editor", self.selected_challenge);
+        self.app_state.lock().unwrap().deep_link = Some(DeepLink::OpenChallenge {
+            command: self.selected_challenge.clone(),
+        });
+    }
+
+    fn filters_ui(&mut self, ui: &mut egui::Ui) -> egui::Response {
+        let tags = self.app_state.lock().unwrap().challenges.tags();
+        let search = ui.add(
+            egui::TextEdit::singleline(&mut self.query.search)
+                .hint_text("🔍 Search")
+                .desired_width(f32::INFINITY),
+        );
+        ui.horizontal_wrapped(|ui| {
+            egui::ComboBox::from_id_source("challenge_tag")
+                .selected_text(self.query.tag.clone().unwrap_or("Any tag".to_string()))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.query.tag, None, "Any tag");
+                    for tag in tags {
+                        ui.selectable_value(&mut self.query.tag, Some(tag.clone()), tag);
+                    }
+                });
+            egui::ComboBox::from_id_source("challenge_availability")
+                .selected_text(
+                    self.query
+                        .availability
+                        .map_or("Any status".to_string(), |a| a.to_string()),
+                )
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.query.availability, None, "Any status");
+                    for availability in [
+                        Availability::Open,
+                        Availability::Upcoming,
+                        Availability::Closed,
+                    ] {
+                        ui.selectable_value(
+                            &mut self.query.availability,
+                            Some(availability),
+                            availability.to_string(),
+                        );
+                    }
+                });
+            egui::ComboBox::from_id_source("challenge_sort")
+                .selected_text(format!("Sort by {}", self.query.sort))
+                .show_ui(ui, |ui| {
+                    for sort in ChallengeSort::iter() {
+                        ui.selectable_value(&mut self.query.sort, sort, sort.to_string());
+                    }
+                });
+            ui.checkbox(&mut self.query.unsolved_only, "Unsolved");
+        });
+        search
+    }
+
+    fn browser_ui(&mut self, ui: &mut egui::Ui) {
+        let search = self.filters_ui(ui);
+        ui.separator();
+
+        let app_state = self.app_state.clone();
+        let app_state = app_state.lock().unwrap();
+        let now = chrono::Utc::now();
+        let found = self.query.apply(&app_state.challenges.items, now, |c| {
+            app_state.solved(&c.table)
+        });
+
+        /* Arrows move through what's listed, from the search box
+        or with the pointer over the browser. Enter opens the editor */
+        let keyboard = search.has_focus() || search.lost_focus() || ui.ui_contains_pointer();
+        let (up, down, enter) = ui.input(|i| {
+            (
+                i.key_pressed(Key::ArrowUp),
+                i.key_pressed(Key::ArrowDown),
+                i.key_pressed(Key::Enter),
+            )
+        });
+        let position = found
+            .iter()
+            .position(|c| c.command == self.selected_challenge);
+        let moved_to = match (keyboard, position) {
+            (false, _) => None,
+            (true, None) if down && !found.is_empty() => Some(0),
+            (true, Some(i)) if down => Some((i + 1).min(found.len() - 1)),
+            (true, Some(i)) if up => Some(i.saturating_sub(1)),
+            _ => None,
+        };
+        if let Some(i) = moved_to {
+            self.selected_challenge = found[i].command.clone();
+        }
+
+        let mut open_in_editor = false;
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, true])
+            .max_height(ui.available_height() - 30.0)
+            .show(ui, |ui| {
+                if found.is_empty() {
+                    ui.label("No challenges match");
+                }
+                for challenge in &found {
+                    let selected = self.selected_challenge == challenge.command;
+                    ui.horizontal(|ui| {
+                        let response = ui.selectable_label(selected, &challenge.name);
+                        if response.clicked() {
+                            self.selected_challenge = challenge.command.clone();
+                        }
+                        open_in_editor |= response.double_clicked();
+                        if selected && moved_to.is_some() {
+                            response.scroll_to_me(None);
+                        }
+                        if app_state.solved(&challenge.table) == Some(true) {
+                            ui.label("✔").on_hover_text("You have a score on this one");
+                        }
+                    });
+                    challenge_badges(ui, challenge, now);
+                    ui.add_space(4.0);
+                }
+            });
+        drop(app_state);
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            let selected = !self.selected_challenge.is_empty();
+            if ui
+                .add_enabled(selected, egui::Button::new("Open in Editor"))
+                .clicked()
+                || (keyboard && enter && selected)
+                || open_in_editor
+            {
+                self.open_in_editor();
+            }
+            if ui.button("Refresh").clicked() {
+                self.app_state
+                    .clone()
+                    .lock()
+                    .unwrap()
+                    .update_activity_timer();
+                self.active_challenge = None;
+            }
+        });
+    }
+}
+
 impl super::App for ChallengeInfoApp {
     fn name(&self) -> &'static str {
         "📖 Challenge Info"
@@ -40,6 +348,12 @@ impl super::App for ChallengeInfoApp {
     }
 
     fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        self.check_solved_promise();
+        self.check_downloads(ctx);
+        if *open {
+            self.fetch_next_scoreboard();
+        }
+
         let challenges_differ = match self.active_challenge.clone() {
             None => true,
             Some(active) => active != self.selected_challenge,
@@ -58,7 +372,7 @@ impl super::App for ChallengeInfoApp {
 
         egui::Window::new(self.name())
             .open(open)
-            .default_width(800.0)
+            .default_width(900.0)
             .default_height(600.0)
             .vscroll(false)
             .hscroll(false)
@@ -69,34 +383,26 @@ impl super::App for ChallengeInfoApp {
                 use super::View as _;
                 self.ui(ui);
             });
+        for (_, request) in &mut self.downloads {
+            retry::repaint_while_waiting(ctx, request.next_retry());
+            if request.refresh_context() {
+                ctx.request_repaint();
+            }
+        }
+        self.toasts.show(ctx);
     }
 }
 
 impl super::View for ChallengeInfoApp {
     fn ui(&mut self, ui: &mut egui::Ui) {
         egui::SidePanel::right("ChallengeInfoSelection")
-            .resizable(false)
+            .resizable(true)
+            .default_width(280.0)
             .show_inside(ui, |ui| {
-                ui.vertical(|ui| {
-                    for challenge in self.app_state.lock().unwrap().challenges.items.iter() {
-                        ui.radio_value(
-                            &mut self.selected_challenge,
-                            challenge.command.clone(),
-                            &challenge.command,
-                        );
-                    }
-                    ui.separator();
-                    if ui.button("Refresh").clicked() {
-                        self.app_state
-                            .clone()
-                            .lock()
-                            .unwrap()
-                            .update_activity_timer();
-                        self.active_challenge = None;
-                    }
-                });
+                self.browser_ui(ui);
             });
         egui::CentralPanel::default().show_inside(ui, |ui| {
+            self.files_ui(ui);
             egui::ScrollArea::both()
                 .auto_shrink([false, false])
                 .show(ui, |ui| {
@@ -1,6 +1,7 @@
 use crate::helpers::{
-    fetchers::Requestor,
-    submission::{Submission, SubmissionResult},
+    deep_link::DeepLink,
+    retry,
+    submission::{PendingSubmission, Submission, SubmissionResult},
     AppState, Languages,
 };
 use std::borrow::BorrowMut;
@@ -9,8 +10,6 @@ use std::sync::{Arc, Mutex};
 #[derive(serde::Deserialize, serde::Serialize)]
 #[serde(default)]
 pub struct CodeEditor {
-    #[serde(skip)]
-    url: String,
     #[serde(skip)]
     run: Submission,
     #[serde(skip)]
@@ -18,7 +17,10 @@ pub struct CodeEditor {
     #[serde(skip)]
     code: String,
     #[serde(skip)]
-    submitter: Option<Requestor>,
+    submitter: Option<PendingSubmission>,
+    /// Picked here rather than taken from the profile
+    #[serde(skip)]
+    language_chosen: bool,
     #[serde(skip)]
     app_state: Arc<Mutex<AppState>>,
 }
@@ -31,13 +33,11 @@ impl Default for CodeEditor {
         };
         run.language = Languages::Python;
         Self {
-            url: option_env!("BACKEND_URL")
-                .unwrap_or("http://123.4.5.6:3000/")
-                .to_string(),
             run,
             code: "#A very simple example\nprint(\"Hello world!\")".into(),
             last_result: SubmissionResult::NotStarted,
             submitter: None,
+            language_chosen: false,
             app_state: Arc::new(Mutex::new(AppState::default())),
         }
     }
@@ -45,10 +45,7 @@ impl Default for CodeEditor {
 
 impl CodeEditor {
     fn submit(&mut self) {
-        let submission = self.run.clone();
-        let url = format!("{}api/game/submit", self.url);
-        let app_state = Arc::clone(&self.app_state);
-        self.submitter = submission.sender(app_state, &url);
+        self.submitter = self.run.clone().sender(&self.app_state);
     }
 
     fn as_test_submission(&mut self) {
@@ -71,14 +68,42 @@ impl super::App for CodeEditor {
         self.app_state = app_state;
     }
 
+    fn handles_link(&self, link: &DeepLink) -> bool {
+        matches!(
+            link,
+            DeepLink::OpenChallenge { .. } | DeepLink::EditSubmission { .. }
+        )
+    }
+
     fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        let link = self
+            .app_state
+            .lock()
+            .unwrap()
+            .take_deep_link(|link| self.handles_link(link));
+        match link {
+            Some(DeepLink::OpenChallenge { command }) => self.run.challenge = Some(command),
+            Some(DeepLink::EditSubmission { submission }) => {
+                if let Some(code) = &submission.code {
+                    self.code = code.clone();
+                }
+                self.run = submission;
+                self.language_chosen = true;
+            }
+            _ => {}
+        }
+        if !self.language_chosen {
+            if let Some(profile) = &self.app_state.lock().unwrap().user_profile {
+                self.run.language = profile.language;
+            }
+        }
         use super::View as _;
         egui::Window::new(self.name())
             .open(open)
             .default_height(500.0)
             .show(ctx, |ui| self.ui(ui));
 
-        let submission = Submission::check_sender(&mut self.submitter);
+        let submission = Submission::check_sender(&mut self.submitter, &self.app_state);
         match submission {
             SubmissionResult::NotStarted => {}
             _ => {
@@ -86,6 +111,7 @@ impl super::App for CodeEditor {
             }
         }
         if let Some(fetcher) = self.submitter.borrow_mut() {
+            retry::repaint_while_waiting(ctx, fetcher.next_retry());
             if fetcher.refresh_context() {
                 ctx.request_repaint();
             }
@@ -109,7 +135,12 @@ impl super::View for CodeEditor {
             ui.label("Language:");
 
             for l in Languages::iter() {
-                ui.selectable_value(&mut self.run.language, l, format!("{}", l));
+                if ui
+                    .selectable_value(&mut self.run.language, l, format!("{}", l))
+                    .clicked()
+                {
+                    self.language_chosen = true;
+                }
             }
         });
         egui::ComboBox::from_label("Challenge")
@@ -0,0 +1,179 @@
+use crate::helpers::{
+    console_log::{self, LogLine},
+    execute, AppState,
+};
+use egui_notify::Toasts;
+use log::Level;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+type ExportResult = Result<(), String>;
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ConsoleApp {
+    level: Level,
+    #[serde(skip)]
+    search: String,
+    /// Lines as they were when paused
+    #[serde(skip)]
+    paused: Option<Vec<LogLine>>,
+    #[serde(skip)]
+    toasts: Toasts,
+    #[serde(skip)]
+    export_channel: (Sender<ExportResult>, Receiver<ExportResult>),
+    #[serde(skip)]
+    app_state: Arc<Mutex<AppState>>,
+}
+
+impl Default for ConsoleApp {
+    fn default() -> Self {
+        Self {
+            level: Level::Info,
+            search: String::new(),
+            paused: None,
+            toasts: Toasts::default(),
+            export_channel: channel(),
+            app_state: Arc::new(Mutex::new(AppState::default())),
+        }
+    }
+}
+
+impl ConsoleApp {
+    fn visible_lines(&self) -> Vec<LogLine> {
+        let search = self.search.to_lowercase();
+        let matches = |line: &&LogLine| {
+            line.level <= self.level
+                && (search.is_empty()
+                    || line.message.to_lowercase().contains(&search)
+                    || line.target.to_lowercase().contains(&search))
+        };
+        match &self.paused {
+            Some(lines) => lines.iter().filter(matches).cloned().collect(),
+            None => {
+                console_log::with_lines(|lines| lines.iter().filter(matches).cloned().collect())
+            }
+        }
+    }
+
+    fn export(&mut self, ctx: &egui::Context, lines: &[LogLine]) {
+        let text: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+        let sender = self.export_channel.0.clone();
+        let ctx = ctx.clone();
+        let task = rfd::AsyncFileDialog::new()
+            .set_file_name("console.log")
+            .save_file();
+        execute(async move {
+            if let Some(file) = task.await {
+                let result = file.write(text.as_bytes()).await;
+                let _ = sender.send(result.map_err(|e| e.to_string()));
+                ctx.request_repaint();
+            }
+        });
+    }
+
+    fn check_export(&mut self) {
+        if let Ok(result) = self.export_channel.1.try_recv() {
+            match result {
+                Ok(_) => self.toasts.info("Console exported"),
+                Err(e) => self
+                    .toasts
+                    .error(format!("Failed to export console: {}", e)),
+            }
+            .set_duration(Some(Duration::from_secs(5)));
+        }
+    }
+
+    fn ui_line(ui: &mut egui::Ui, line: &LogLine) {
+        let color = match line.level {
+            Level::Error => ui.visuals().error_fg_color,
+            Level::Warn => ui.visuals().warn_fg_color,
+            Level::Info => ui.visuals().text_color(),
+            Level::Debug | Level::Trace => ui.visuals().weak_text_color(),
+        };
+        ui.label(
+            egui::RichText::new(line.to_string())
+                .monospace()
+                .color(color),
+        );
+    }
+}
+
+impl super::App for ConsoleApp {
+    fn name(&self) -> &'static str {
+        "📜 Console"
+    }
+
+    fn set_app_state_ref(&mut self, app_state: Arc<Mutex<AppState>>) {
+        self.app_state = app_state;
+    }
+
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        self.check_export();
+
+        use super::View as _;
+        egui::Window::new(self.name())
+            .open(open)
+            .default_width(600.0)
+            .default_height(400.0)
+            .vscroll(false)
+            .resizable(true)
+            .show(ctx, |ui| self.ui(ui));
+
+        // nothing else repaints when only the log changes
+        if *open && self.paused.is_none() {
+            ctx.request_repaint_after(Duration::from_secs(1));
+        }
+        self.toasts.show(ctx);
+    }
+}
+
+impl super::View for ConsoleApp {
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        let lines = self.visible_lines();
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("console_level")
+                .selected_text(self.level.to_string())
+                .show_ui(ui, |ui| {
+                    for level in [Level::Error, Level::Warn, Level::Info, Level::Debug] {
+                        ui.selectable_value(&mut self.level, level, level.to_string());
+                    }
+                });
+            ui.add(
+                egui::TextEdit::singleline(&mut self.search)
+                    .hint_text("Search")
+                    .desired_width(150.0),
+            );
+
+            let mut paused = self.paused.is_some();
+            if ui.toggle_value(&mut paused, "⏸ Pause").changed() {
+                self.paused = match paused {
+                    true => Some(console_log::with_lines(|lines| {
+                        lines.iter().cloned().collect()
+                    })),
+                    false => None,
+                };
+            }
+            if ui.button("Clear").clicked() {
+                console_log::clear();
+                self.paused = None;
+            }
+            if ui.button("💾 Export").clicked() {
+                self.export(ui.ctx(), &lines);
+            }
+        });
+        ui.separator();
+
+        let row_height = egui::TextStyle::Monospace.resolve(ui.style()).size;
+        egui::ScrollArea::both()
+            .auto_shrink([false, false])
+            .stick_to_bottom(true)
+            .show_rows(ui, row_height, lines.len(), |ui, range| {
+                for line in &lines[range] {
+                    Self::ui_line(ui, line);
+                }
+            });
+    }
+}
@@ -1,5 +1,8 @@
+use crate::components::password::{self, PasswordPolicy};
 use crate::helpers::{
-    fetchers::{RequestStatus, Requestor},
+    api::{self, ApiError, ApiRequest, LoginResponse, StatusResponse},
+    deep_link::DeepLink,
+    session::{self, Identity},
     AppState, LoginState,
 };
 use egui_notify::Toasts;
@@ -7,6 +10,10 @@ use email_address::*;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+const AUTH_REQUEST: &str = "auth";
+/// How long to wait before checking a saved session again when the backend couldn't be reached
+const SESSION_RECHECK_SECS: f64 = 30.0;
+
 #[derive(serde::Deserialize, serde::Serialize)]
 enum AuthRequest {
     Login,
@@ -20,19 +27,6 @@ struct LoginSchema {
     password: String,
 }
 
-impl LoginSchema {
-    fn to_forgot_password(&self) -> ForgotPasswordSchema {
-        ForgotPasswordSchema {
-            email: self.email.clone(),
-        }
-    }
-}
-
-#[derive(Clone, PartialEq, serde::Serialize)]
-struct ForgotPasswordSchema {
-    email: String,
-}
-
 #[derive(Default, Clone, PartialEq, serde::Serialize)]
 struct RegisterSchema {
     name: String,
@@ -42,29 +36,30 @@ struct RegisterSchema {
     confirm_password: String,
 }
 
-#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
-struct RegisterResponse {
-    status: String,
-    message: String,
+#[derive(Default, Clone, PartialEq)]
+struct ChangePasswordSchema {
+    email: String,
+    old_password: String,
+    new_password: String,
+    confirm_password: String,
+    logout_other_sessions: bool,
 }
 
-impl RegisterResponse {
-    fn is_success(&self) -> bool {
-        self.status.to_lowercase() == "success"
+impl ChangePasswordSchema {
+    fn validate(&self, policy: &PasswordPolicy) -> Result<(), String> {
+        if self.old_password.is_empty() {
+            return Err("Enter your current password".to_string());
+        }
+        if self.new_password != self.confirm_password {
+            return Err("Passwords do not match".to_string());
+        }
+        if self.new_password == self.old_password {
+            return Err("The new password must be different".to_string());
+        }
+        policy.check(&self.new_password, &self.email)
     }
 }
 
-#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
-pub enum LoginResponse {
-    Success {
-        status: String,
-        access_token: String,
-    },
-    Failure {
-        status: String,
-        message: String,
-    },
-}
 #[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 enum LoginAppState {
     Login,
@@ -74,10 +69,6 @@ enum LoginAppState {
 #[derive(serde::Deserialize, serde::Serialize)]
 #[serde(default)]
 pub struct LoginApp {
-    username: String,
-    token: Option<String>,
-    #[serde(skip)]
-    url: String,
     #[serde(skip)]
     login: LoginSchema,
     #[serde(skip)]
@@ -85,27 +76,38 @@ pub struct LoginApp {
     #[serde(skip)]
     register: RegisterSchema,
     #[serde(skip)]
+    change_password: ChangePasswordSchema,
+    #[serde(skip)]
     toasts: Toasts,
     #[serde(skip)]
-    login_requestor: Option<Requestor>,
+    refresh_requestor: Option<ApiRequest<StatusResponse>>,
+    /// When to check the saved session again, set while the backend can't be reached
+    #[serde(skip)]
+    recheck_at: Option<f64>,
     #[serde(skip)]
-    logout_requestor: Option<Requestor>,
+    login_requestor: Option<ApiRequest<LoginResponse>>,
     #[serde(skip)]
-    register_requestor: Option<Requestor>,
+    logout_requestor: Option<ApiRequest<()>>,
     #[serde(skip)]
-    reset_pass_requestor: Option<Requestor>,
+    identity_requestor: Option<ApiRequest<Identity>>,
+    #[serde(skip)]
+    register_requestor: Option<ApiRequest<StatusResponse>>,
+    #[serde(skip)]
+    reset_pass_requestor: Option<ApiRequest<StatusResponse>>,
+    #[serde(skip)]
+    change_password_requestor: Option<ApiRequest<StatusResponse>>,
+    #[serde(skip)]
+    verify_requestor: Option<ApiRequest<StatusResponse>>,
+    /// How following the last verification link went
+    #[serde(skip)]
+    verification: Option<Result<(), String>>,
     #[serde(skip)]
     app_state: Arc<Mutex<AppState>>,
 }
 
 impl Default for LoginApp {
     fn default() -> Self {
-        let url = option_env!("BACKEND_URL")
-            .unwrap_or("http://12.34.56.78:3000/")
-            .to_string();
-
         Self {
-            url,
             login: LoginSchema {
                 #[cfg(debug_assertions)]
                 email: "admin@admin.com".to_string(),
@@ -116,54 +118,55 @@ impl Default for LoginApp {
                 #[cfg(not(debug_assertions))]
                 password: "".to_string(),
             },
-            token: None,
-            username: "".to_string(),
             state: LoginAppState::Login,
             register: RegisterSchema::default(),
+            change_password: ChangePasswordSchema::default(),
             toasts: Toasts::default(),
             app_state: Default::default(),
+            refresh_requestor: None,
+            recheck_at: None,
             login_requestor: None,
             logout_requestor: None,
+            identity_requestor: None,
             register_requestor: None,
             reset_pass_requestor: None,
+            change_password_requestor: None,
+            verify_requestor: None,
+            verification: None,
         }
     }
 }
 
 impl LoginApp {
+    /* Logging in, out and restoring the session all decide whether we
+    are logged in, only the one asked for last gets to */
     fn submit_login(&mut self) {
-        let submission = Some(serde_json::to_string(&self.login).unwrap());
-        let url = format!("{}api/auth/login", self.url);
-        let app_state = Arc::clone(&self.app_state);
-        let mut req = Requestor::new_post(app_state, &url, true, submission);
-        req.send();
-        self.login_requestor = Some(req);
+        self.recheck_at = None;
+        self.login_requestor = Some(
+            api::login(&self.app_state, &self.login.email, &self.login.password)
+                .latest_wins(AUTH_REQUEST),
+        );
     }
 
     fn submit_logout(&mut self) {
-        let url = format!("{}api/auth/logout", self.url);
-        let app_state = Arc::clone(&self.app_state);
-        let mut req = Requestor::new_post(app_state, &url, true, None);
-        req.send();
-        self.logout_requestor = Some(req);
+        self.logout_requestor = Some(api::logout(&self.app_state).latest_wins(AUTH_REQUEST));
+    }
+
+    fn fetch_identity(&mut self) {
+        self.identity_requestor = Some(api::me(&self.app_state).latest_wins("identity"));
     }
 
     fn submit_register(&mut self) {
-        let submission = Some(serde_json::to_string(&self.register).unwrap());
-        let url = format!("{}api/auth/register", self.url);
-        let app_state = Arc::clone(&self.app_state);
-        let mut req = Requestor::new_post(app_state, &url, false, submission);
-        req.send();
-        self.register_requestor = Some(req);
+        self.register_requestor = Some(api::register(
+            &self.app_state,
+            &self.register.name,
+            &self.register.email,
+            &self.register.password,
+        ));
     }
 
     fn submit_forgot_password(&mut self) {
-        let url = format!("{}api/auth/forgotpassword", self.url);
-        let submission = Some(serde_json::to_string(&self.login.to_forgot_password()).unwrap());
-        let app_state = Arc::clone(&self.app_state);
-        let mut req = Requestor::new_post(app_state, &url, false, submission);
-        req.send();
-        self.reset_pass_requestor = Some(req);
+        self.reset_pass_requestor = Some(api::forgot_password(&self.app_state, &self.login.email));
         self.toasts
             .info(format!(
                 "If {} is a registered address you will receive a password reset link shortly.",
@@ -172,140 +175,319 @@ impl LoginApp {
             .set_duration(Some(Duration::from_secs(5)));
     }
 
-    fn check_login_promise(&mut self) {
-        let getter = &mut self.login_requestor;
-
-        if let Some(getter) = getter {
-            let result = &getter.check_promise();
-            match result {
-                RequestStatus::Failed(err) => {
-                    self.toasts
-                        .error(format!("Failed: {}", err))
-                        .set_duration(Some(Duration::from_secs(5)));
-
-                    log::error!("Error sending: {}", err);
-                    self.login_requestor = None;
-                }
-                RequestStatus::Success(text) => {
-                    log::debug!("Success: {}", text);
-                    let result: LoginResponse = serde_json::from_str(text).unwrap();
-                    match result {
-                        LoginResponse::Success { .. } => {
-                            self.toasts
-                                .info(format!("Logged in: {}", &self.login.email))
-                                .set_duration(Some(Duration::from_secs(5)));
-
-                            AppState::set_logged_in(&self.app_state);
-                        }
-                        LoginResponse::Failure { status: _, message } => {
-                            log::error!("Failed to login: {}", message);
-                            self.toasts
-                                .error(format!("Failed to login: {}", message))
-                                .set_duration(Some(Duration::from_secs(5)));
-                        }
-                    };
-                    self.login_requestor = None;
-                }
-                _ => {}
+    fn submit_change_password(&mut self, email: &str) {
+        self.change_password.email = email.to_string();
+        let policy = self
+            .app_state
+            .lock()
+            .unwrap()
+            .config
+            .password_policy
+            .clone();
+        if let Err(e) = self.change_password.validate(&policy) {
+            self.toasts
+                .error(e)
+                .set_duration(Some(Duration::from_secs(5)));
+            return;
+        }
+        self.change_password_requestor = Some(api::change_password(
+            &self.app_state,
+            &self.change_password.email,
+            &self.change_password.old_password,
+            &self.change_password.new_password,
+            self.change_password.logout_other_sessions,
+        ));
+    }
+
+    fn follow_link(&mut self) {
+        use super::App as _;
+        let link = self
+            .app_state
+            .lock()
+            .unwrap()
+            .take_deep_link(|link| self.handles_link(link));
+        if let Some(DeepLink::VerifyEmail { token }) = link {
+            self.state = LoginAppState::Login;
+            self.verification = None;
+            self.verify_requestor = Some(api::verify_email(&self.app_state, &token));
+        }
+    }
+
+    fn check_verify_promise(&mut self) {
+        let result = match &mut self.verify_requestor {
+            Some(getter) => getter.check(),
+            None => return,
+        };
+        match result {
+            None => {}
+            Some(Ok(_)) => {
+                self.toasts
+                    .info("Email verified")
+                    .set_duration(Some(Duration::from_secs(5)));
+                self.verification = Some(Ok(()));
+                self.verify_requestor = None;
+            }
+            Some(Err(err)) => {
+                log::error!("Failed to verify email: {}", err);
+                self.verification = Some(Err(err.to_string()));
+                self.verify_requestor = None;
             }
         }
     }
 
-    fn check_logout_promise(&mut self) {
-        let getter = &mut self.logout_requestor;
-
-        if let Some(getter) = getter {
-            let result = &getter.check_promise();
-            match result {
-                RequestStatus::Failed(err) => {
-                    self.toasts
-                        .error(format!("Failed: {}", err))
-                        .set_duration(Some(Duration::from_secs(5)));
-
-                    log::error!("Error sending: {}", err);
-                    self.logout_requestor = None;
-                }
-                RequestStatus::Success(text) => {
-                    log::debug!("Success: {}", text);
-                    self.toasts
-                        .info(format!("Logged out: {}", &self.login.email))
-                        .set_duration(Some(Duration::from_secs(5)));
-                    AppState::set_logged_out(&self.app_state);
-                    self.logout_requestor = None;
-                }
-                _ => {}
+    fn ui_verification(&mut self, ui: &mut egui::Ui) {
+        if self.verify_requestor.is_some() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Verifying your email...");
+            });
+        }
+        match &self.verification {
+            None => return,
+            Some(Ok(())) => {
+                ui.colored_label(
+                    ui.visuals().hyperlink_color,
+                    "✔ Email verified, you can log in now",
+                );
+            }
+            Some(Err(e)) => {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    format!("✖ Couldn't verify your email: {}", e),
+                );
             }
         }
+        ui.separator();
     }
 
-    fn check_register_promise(&mut self) {
-        let getter = &mut self.register_requestor;
-
-        if let Some(getter) = getter {
-            let result = &getter.check_promise();
-            match result {
-                RequestStatus::Failed(err) => {
-                    self.register_requestor = None;
-                    self.toasts
-                        .error(format!("Failed: {}", err))
-                        .set_duration(Some(Duration::from_secs(5)));
-
-                    log::error!("Error sending: {}", err);
-                }
-                RequestStatus::Success(text) => {
-                    log::debug!("Success: {}", text);
-                    let result: RegisterResponse = serde_json::from_str(text).unwrap();
-                    if result.is_success() {
-                        self.toasts
-                            .info("Registered successfully! Please login.")
-                            .set_duration(Some(Duration::from_secs(5)));
-                        self.toasts
-                            .info("Please check your junk folder for the registration email.")
-                            .set_duration(Some(Duration::from_secs(5)));
-                    } else {
-                        self.toasts
-                            .error("Failed to register!")
-                            .set_duration(Some(Duration::from_secs(5)));
-                    }
-                    self.register_requestor = None;
-                }
-                _ => {}
+    fn check_session(&mut self) {
+        self.recheck_at = None;
+        self.refresh_requestor = Some(api::refresh(&self.app_state).latest_wins(AUTH_REQUEST));
+    }
+
+    /* A still valid refresh cookie logs us in without asking,
+    so failures here are expected and not worth a toast.
+    When the backend can't be reached the restored identity is kept,
+    still logged out, until a later check can tell */
+    fn check_refresh_promise(&mut self, now: f64) {
+        let result = match &mut self.refresh_requestor {
+            Some(getter) => getter.check(),
+            None => return,
+        };
+        match result {
+            None => {}
+            Some(Ok(_)) => {
+                AppState::set_logged_in(&self.app_state);
+                session::save(&self.app_state);
+                self.fetch_identity();
+                self.refresh_requestor = None;
+            }
+            Some(Err(ApiError::Cancelled)) => {
+                self.refresh_requestor = None;
+            }
+            Some(Err(ApiError::Network(e))) => {
+                log::warn!("Couldn't check the saved session: {}", e);
+                self.recheck_at = Some(now + SESSION_RECHECK_SECS);
+                self.refresh_requestor = None;
+            }
+            Some(Err(e)) => {
+                log::debug!("No session to restore: {}", e);
+                AppState::set_logged_out(&self.app_state);
+                self.refresh_requestor = None;
             }
         }
     }
 
-    fn check_reset_password_promise(&mut self) {
-        let getter = &mut self.reset_pass_requestor;
+    /* Older backends can't say who we are,
+    the email we logged in with will have to do */
+    fn check_identity_promise(&mut self) {
+        let result = match &mut self.identity_requestor {
+            Some(getter) => getter.check(),
+            None => return,
+        };
+        match result {
+            None => {}
+            Some(Ok(identity)) => {
+                session::set_identity(&self.app_state, Some(identity));
+                self.identity_requestor = None;
+            }
+            Some(Err(ApiError::Cancelled)) => {
+                self.identity_requestor = None;
+            }
+            Some(Err(e)) => {
+                log::warn!("Couldn't fetch who is logged in: {}", e);
+                self.identity_requestor = None;
+            }
+        }
+    }
 
-        if let Some(getter) = getter {
-            let result = &getter.check_promise();
-            match result {
-                RequestStatus::Failed(err) => {
-                    self.toasts
-                        .error(format!("Failed: {}", err))
-                        .set_duration(Some(Duration::from_secs(5)));
+    fn check_login_promise(&mut self) {
+        let result = match &mut self.login_requestor {
+            Some(getter) => getter.check(),
+            None => return,
+        };
+        match result {
+            None => {}
+            Some(Err(ApiError::Cancelled)) => {
+                self.login_requestor = None;
+            }
+            Some(Err(err)) => {
+                self.toasts
+                    .error(format!("Failed: {}", err))
+                    .set_duration(Some(Duration::from_secs(5)));
 
-                    log::error!("Error sending: {}", err);
-                }
-                RequestStatus::Success(text) => {
-                    log::debug!("Success: {}", text);
-                    let result: RegisterResponse = serde_json::from_str(text).unwrap();
-                    if result.is_success() {
+                log::error!("Error sending: {}", err);
+                self.login_requestor = None;
+            }
+            Some(Ok(response)) => {
+                match response {
+                    LoginResponse::Success { roles, .. } => {
                         self.toasts
-                            .info("Password reset token sent")
+                            .info(format!("Logged in: {}", &self.login.email))
                             .set_duration(Some(Duration::from_secs(5)));
+
+                        AppState::set_logged_in(&self.app_state);
+                        let identity = Identity {
+                            email: self.login.email.clone(),
+                            roles,
+                            ..Default::default()
+                        };
+                        session::set_identity(&self.app_state, Some(identity));
+                        self.fetch_identity();
+                    }
+                    LoginResponse::Failure { status: _, message } => {
+                        log::error!("Failed to login: {}", message);
                         self.toasts
-                            .info("Please check your junk folder for the registration email.")
+                            .error(format!("Failed to login: {}", message))
                             .set_duration(Some(Duration::from_secs(5)));
                     }
-                    self.reset_pass_requestor = None;
-                }
-                _ => {}
+                };
+                self.login_requestor = None;
             }
         }
     }
 
-    fn ui_logged_in(&mut self, ui: &mut egui::Ui) {
+    fn check_logout_promise(&mut self) {
+        let result = match &mut self.logout_requestor {
+            Some(getter) => getter.check(),
+            None => return,
+        };
+        match result {
+            None => {}
+            Some(Err(ApiError::Cancelled)) => {
+                self.logout_requestor = None;
+            }
+            Some(Err(err)) => {
+                self.toasts
+                    .error(format!("Failed: {}", err))
+                    .set_duration(Some(Duration::from_secs(5)));
+
+                log::error!("Error sending: {}", err);
+                self.logout_requestor = None;
+            }
+            Some(Ok(())) => {
+                self.toasts
+                    .info(format!("Logged out: {}", &self.login.email))
+                    .set_duration(Some(Duration::from_secs(5)));
+                AppState::set_logged_out(&self.app_state);
+                self.logout_requestor = None;
+            }
+        }
+    }
+
+    fn check_register_promise(&mut self) {
+        let result = match &mut self.register_requestor {
+            Some(getter) => getter.check(),
+            None => return,
+        };
+        match result {
+            None => {}
+            Some(Err(err)) => {
+                self.register_requestor = None;
+                self.toasts
+                    .error(format!("Failed to register: {}", err))
+                    .set_duration(Some(Duration::from_secs(5)));
+
+                log::error!("Error sending: {}", err);
+            }
+            Some(Ok(_)) => {
+                self.toasts
+                    .info("Registered successfully! Please login.")
+                    .set_duration(Some(Duration::from_secs(5)));
+                self.toasts
+                    .info("Please check your junk folder for the registration email.")
+                    .set_duration(Some(Duration::from_secs(5)));
+                self.register_requestor = None;
+            }
+        }
+    }
+
+    fn check_reset_password_promise(&mut self) {
+        let result = match &mut self.reset_pass_requestor {
+            Some(getter) => getter.check(),
+            None => return,
+        };
+        match result {
+            None => {}
+            Some(Err(err)) => {
+                self.toasts
+                    .error(format!("Failed: {}", err))
+                    .set_duration(Some(Duration::from_secs(5)));
+
+                log::error!("Error sending: {}", err);
+                self.reset_pass_requestor = None;
+            }
+            Some(Ok(_)) => {
+                self.toasts
+                    .info("Password reset token sent")
+                    .set_duration(Some(Duration::from_secs(5)));
+                self.toasts
+                    .info("Please check your junk folder for the registration email.")
+                    .set_duration(Some(Duration::from_secs(5)));
+                self.reset_pass_requestor = None;
+            }
+        }
+    }
+
+    /* The backend may hand out new cookies along with the new password,
+    the saved session has to follow */
+    fn check_change_password_promise(&mut self) {
+        let result = match &mut self.change_password_requestor {
+            Some(getter) => getter.check(),
+            None => return,
+        };
+        match result {
+            None => {}
+            Some(Err(ApiError::Cancelled)) => {
+                self.change_password_requestor = None;
+            }
+            Some(Err(err)) => {
+                self.toasts
+                    .error(format!("Failed to change password: {}", err))
+                    .set_duration(Some(Duration::from_secs(5)));
+                log::error!("Error changing password: {}", err);
+                self.change_password_requestor = None;
+            }
+            Some(Ok(response)) => {
+                let message = match response.message.is_empty() {
+                    true => "Password changed".to_string(),
+                    false => format!("Password changed: {}", response.message),
+                };
+                self.toasts
+                    .info(message)
+                    .set_duration(Some(Duration::from_secs(5)));
+                self.change_password = ChangePasswordSchema::default();
+                session::save(&self.app_state);
+                self.change_password_requestor = None;
+            }
+        }
+    }
+
+    fn ui_logged_in(&mut self, ui: &mut egui::Ui, identity: Option<Identity>) {
+        let identity = identity.unwrap_or_else(|| Identity {
+            email: self.login.email.clone(),
+            ..Default::default()
+        });
         ui.horizontal(|ui| {
             ui.vertical(|ui| {
                 if ui.button("Logout").clicked() {
@@ -314,12 +496,100 @@ impl LoginApp {
             });
             ui.separator();
             ui.vertical(|ui| {
-                ui.label(format!("Logged in as: {}", self.login.email));
+                ui.label(format!("Logged in as: {}", identity.display_name()));
+                if !identity.name.is_empty() {
+                    ui.weak(&identity.email);
+                }
+                if !identity.roles.is_empty() {
+                    ui.label(format!("Roles: {}", identity.roles.join(", ")));
+                }
             });
         });
+
+        ui.separator();
+        ui.collapsing("Account", |ui| self.ui_change_password(ui, &identity.email));
+    }
+
+    fn ui_change_password(&mut self, ui: &mut egui::Ui, email: &str) {
+        let policy = self
+            .app_state
+            .lock()
+            .unwrap()
+            .config
+            .password_policy
+            .clone();
+        egui::Grid::new("change_password_grid")
+            .num_columns(2)
+            .spacing([20.0, 4.0])
+            .striped(false)
+            .show(ui, |ui| {
+                ui.label("Current Password:");
+                ui.add(password::password(&mut self.change_password.old_password));
+                ui.end_row();
+
+                ui.label("New Password:");
+                ui.add(password::new_password(
+                    &mut self.change_password.new_password,
+                    &policy,
+                    email,
+                ));
+                ui.end_row();
+
+                ui.label("Confirm Password:");
+                ui.add(password::confirm_password(
+                    &mut self.change_password.confirm_password,
+                    &self.change_password.new_password,
+                ));
+                ui.end_row();
+            });
+        ui.checkbox(
+            &mut self.change_password.logout_other_sessions,
+            "Log out my other sessions",
+        )
+        .on_hover_text("If the backend supports it, everywhere else will have to log in again");
+
+        let idle = self.change_password_requestor.is_none();
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(idle, egui::Button::new("Change Password"))
+                .clicked()
+            {
+                self.submit_change_password(email);
+            }
+            if !idle {
+                ui.spinner();
+            }
+        });
+    }
+
+    /// Shown on start, until the saved session has been checked.
+    fn ui_restoring(&mut self, ui: &mut egui::Ui, identity: &Identity) {
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.label(format!(
+                "Welcome back {}, checking your session...",
+                identity.display_name()
+            ));
+        });
+    }
+
+    fn ui_unchecked(&mut self, ui: &mut egui::Ui, identity: &Identity) {
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            format!(
+                "⚠ Welcome back {}, the backend couldn't be reached to check your session",
+                identity.display_name()
+            ),
+        );
+        if ui.button("Check again").clicked() {
+            self.check_session();
+        }
+        ui.separator();
+        self.ui_logged_out(ui);
     }
 
     fn ui_logged_out(&mut self, ui: &mut egui::Ui) {
+        self.ui_verification(ui);
         egui::Grid::new("login_grid")
             .num_columns(2)
             .spacing([20.0, 4.0])
@@ -362,6 +632,13 @@ impl LoginApp {
     }
 
     fn ui_register(&mut self, ui: &mut egui::Ui) {
+        let policy = self
+            .app_state
+            .lock()
+            .unwrap()
+            .config
+            .password_policy
+            .clone();
         egui::Grid::new("login_grid")
             .num_columns(2)
             .spacing([20.0, 4.0])
@@ -380,19 +657,18 @@ impl LoginApp {
                 ui.end_row();
 
                 ui.label("Password:");
-                ui.add(
-                    egui::widgets::text_edit::TextEdit::singleline(&mut self.register.password)
-                        .password(true),
-                );
+                ui.add(password::new_password(
+                    &mut self.register.password,
+                    &policy,
+                    &self.register.email,
+                ));
                 ui.end_row();
 
                 ui.label("Confirm Password:");
-                ui.add(
-                    egui::widgets::text_edit::TextEdit::singleline(
-                        &mut self.register.confirm_password,
-                    )
-                    .password(true),
-                );
+                ui.add(password::confirm_password(
+                    &mut self.register.confirm_password,
+                    &self.register.password,
+                ));
                 ui.end_row();
             });
 
@@ -408,6 +684,12 @@ impl LoginApp {
                         self.toasts
                             .error("Invalid email address")
                             .set_duration(Some(Duration::from_secs(5)));
+                    } else if let Err(e) =
+                        policy.check(&self.register.password, &self.register.email)
+                    {
+                        self.toasts
+                            .error(e)
+                            .set_duration(Some(Duration::from_secs(5)));
                     } else if !self
                         .register
                         .email
@@ -443,15 +725,28 @@ impl super::App for LoginApp {
 
     fn set_app_state_ref(&mut self, app_state: Arc<Mutex<AppState>>) {
         self.app_state = app_state;
-        let app_state = Arc::clone(&self.app_state);
-        let mut req = Requestor::new_refresh(app_state);
-        req.send();
-        self.login_requestor = Some(req);
+        self.check_session();
+    }
+
+    fn handles_link(&self, link: &DeepLink) -> bool {
+        matches!(link, DeepLink::VerifyEmail { .. })
     }
 
     fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        let now = ctx.input(|i| i.time);
+        if self.recheck_at.map_or(false, |at| now >= at) {
+            self.check_session();
+        }
+        if let Some(at) = self.recheck_at {
+            ctx.request_repaint_after(Duration::from_secs_f64((at - now).max(0.0)));
+        }
+        self.follow_link();
+        self.check_verify_promise();
+        self.check_refresh_promise(now);
         self.check_login_promise();
         self.check_logout_promise();
+        self.check_identity_promise();
+        self.check_change_password_promise();
         self.check_register_promise();
         self.check_reset_password_promise();
 
@@ -467,14 +762,21 @@ impl super::App for LoginApp {
 
 impl super::View for LoginApp {
     fn ui(&mut self, ui: &mut egui::Ui) {
-        let app = Arc::clone(&self.app_state);
-        let app = app.lock().unwrap();
-        let logged_in = app.logged_in.clone();
+        let (logged_in, identity) = {
+            let app_state = self.app_state.lock().unwrap();
+            (app_state.logged_in.clone(), app_state.identity.clone())
+        };
 
         match self.state {
-            LoginAppState::Login => match logged_in {
-                LoginState::LoggedIn => self.ui_logged_in(ui),
-                LoginState::LoggedOut => self.ui_logged_out(ui),
+            LoginAppState::Login => match (logged_in, identity) {
+                (LoginState::LoggedIn, identity) => self.ui_logged_in(ui, identity),
+                (LoginState::LoggedOut, Some(identity)) if self.refresh_requestor.is_some() => {
+                    self.ui_restoring(ui, &identity)
+                }
+                (LoginState::LoggedOut, Some(identity)) if self.recheck_at.is_some() => {
+                    self.ui_unchecked(ui, &identity)
+                }
+                (LoginState::LoggedOut, _) => self.ui_logged_out(ui),
             },
             LoginAppState::RegisterNewUser => self.ui_register(ui),
         }
@@ -1,4 +1,6 @@
+mod admin_app;
 pub mod app_windows;
+mod challenge_author;
 mod scoreboard_app;
 pub use scoreboard_app::ScoreBoardApp;
 mod challenge_info;
@@ -7,10 +9,26 @@ pub mod login_app;
 pub use login_app::LoginApp;
 pub mod binary_upload;
 pub mod code_editor;
+mod console_app;
+mod network_app;
 mod password_reset_app;
-use crate::helpers::AppState;
+mod profile_app;
+mod settings_app;
+mod submission_history_app;
+mod submission_queue_app;
+mod whats_new_app;
+use crate::helpers::{deep_link::DeepLink, AppState};
+pub use admin_app::AdminApp;
+pub use challenge_author::ChallengeAuthorApp;
+pub use console_app::ConsoleApp;
+pub use network_app::NetworkApp;
 pub use password_reset_app::PasswordResetApp;
+pub use profile_app::ProfileApp;
+pub use settings_app::SettingsApp;
 use std::sync::{Arc, Mutex};
+pub use submission_history_app::SubmissionHistoryApp;
+pub use submission_queue_app::SubmissionQueueApp;
+pub use whats_new_app::WhatsNewApp;
 
 /// Something to view in the demo windows
 pub trait View {
@@ -26,4 +44,14 @@ pub trait App {
     fn show(&mut self, ctx: &egui::Context, open: &mut bool);
 
     fn set_app_state_ref(&mut self, app_state: Arc<Mutex<AppState>>);
+
+    /// Whether following `link` should open this window.
+    fn handles_link(&self, _link: &DeepLink) -> bool {
+        false
+    }
+
+    /// Hidden from anyone logged in without this role.
+    fn required_role(&self) -> Option<&'static str> {
+        None
+    }
 }
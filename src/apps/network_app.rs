@@ -0,0 +1,190 @@
+use crate::helpers::{network_log::NetworkEntry, AppState};
+use std::sync::{Arc, Mutex};
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct NetworkApp {
+    errors_only: bool,
+    #[serde(skip)]
+    filter: String,
+    #[serde(skip)]
+    selected: Option<u64>,
+    #[serde(skip)]
+    app_state: Arc<Mutex<AppState>>,
+}
+
+impl Default for NetworkApp {
+    fn default() -> Self {
+        Self {
+            errors_only: false,
+            filter: String::new(),
+            selected: None,
+            app_state: Arc::new(Mutex::new(AppState::default())),
+        }
+    }
+}
+
+impl NetworkApp {
+    fn matches(&self, entry: &NetworkEntry) -> bool {
+        if self.errors_only && !entry.is_error() {
+            return false;
+        }
+        let filter = self.filter.to_lowercase();
+        filter.is_empty()
+            || entry.url.to_lowercase().contains(&filter)
+            || entry.status().to_lowercase().contains(&filter)
+            || entry.method.to_string().to_lowercase() == filter
+    }
+
+    fn ui_details(&mut self, ui: &mut egui::Ui, entry: &NetworkEntry) {
+        ui.horizontal(|ui| {
+            ui.strong(format!("{} {}", entry.method, entry.url));
+            if ui.button("📋 Copy as curl").clicked() {
+                ui.output_mut(|o| o.copied_text = entry.to_curl());
+            }
+            if ui.button("✖").on_hover_text("Close").clicked() {
+                self.selected = None;
+            }
+        });
+        ui.label(format!(
+            "Status: {}, sent {} bytes, received {} bytes in {}ms",
+            entry.status(),
+            entry.request_size,
+            entry.response_size,
+            entry.duration.num_milliseconds()
+        ));
+        let mut response = entry.response.as_str();
+        egui::ScrollArea::vertical()
+            .id_source("network_response")
+            .max_height(150.0)
+            .show(ui, |ui| {
+                ui.add(
+                    egui::TextEdit::multiline(&mut response)
+                        .font(egui::TextStyle::Monospace)
+                        .desired_width(f32::INFINITY),
+                );
+            });
+        ui.separator();
+    }
+
+    fn table_ui(&mut self, ui: &mut egui::Ui, entries: &[NetworkEntry]) {
+        use egui_extras::{Column, TableBuilder};
+
+        let text_height = egui::TextStyle::Body.resolve(ui.style()).size;
+        TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(Column::auto())
+            .column(Column::auto())
+            .column(Column::initial(250.0).at_least(40.0).clip(true))
+            .column(Column::auto())
+            .column(Column::auto())
+            .column(Column::auto())
+            .column(Column::remainder())
+            .min_scrolled_height(0.0)
+            .header(20.0, |mut header| {
+                for title in [
+                    "Time", "Method", "URL", "Status", "Duration", "Retry", "Size",
+                ] {
+                    header.col(|ui| {
+                        ui.strong(title);
+                    });
+                }
+            })
+            .body(|mut body| {
+                for entry in entries.iter() {
+                    body.row(text_height, |mut row| {
+                        row.col(|ui| {
+                            let selected = self.selected == Some(entry.id);
+                            let time = entry.started.format("%H:%M:%S").to_string();
+                            if ui.selectable_label(selected, time).clicked() {
+                                self.selected = Some(entry.id);
+                            }
+                        });
+                        row.col(|ui| {
+                            ui.label(entry.method.to_string());
+                        });
+                        row.col(|ui| {
+                            ui.label(&entry.url);
+                        });
+                        row.col(|ui| {
+                            let status = entry.status();
+                            match entry.is_error() {
+                                true => ui.colored_label(ui.visuals().error_fg_color, status),
+                                false => ui.label(status),
+                            };
+                        });
+                        row.col(|ui| {
+                            ui.label(format!("{}ms", entry.duration.num_milliseconds()));
+                        });
+                        row.col(|ui| {
+                            ui.label(entry.retry.to_string());
+                        });
+                        row.col(|ui| {
+                            ui.label(format!("{} / {}", entry.request_size, entry.response_size));
+                        });
+                    });
+                }
+            });
+    }
+}
+
+impl super::App for NetworkApp {
+    fn name(&self) -> &'static str {
+        "📡 Network"
+    }
+
+    fn set_app_state_ref(&mut self, app_state: Arc<Mutex<AppState>>) {
+        self.app_state = app_state;
+    }
+
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        egui::Window::new(self.name())
+            .open(open)
+            .default_width(700.0)
+            .default_height(400.0)
+            .vscroll(false)
+            .resizable(true)
+            .show(ctx, |ui| {
+                use super::View as _;
+                self.ui(ui);
+            });
+    }
+}
+
+impl super::View for NetworkApp {
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Filter:");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.filter)
+                    .hint_text("URL, status or method")
+                    .desired_width(200.0),
+            );
+            ui.checkbox(&mut self.errors_only, "Errors only");
+            if ui.button("Clear").clicked() {
+                self.app_state.lock().unwrap().network_log.clear();
+                self.selected = None;
+            }
+        });
+        ui.separator();
+
+        // newest first
+        let entries: Vec<NetworkEntry> = self
+            .app_state
+            .lock()
+            .unwrap()
+            .network_log
+            .entries()
+            .rev()
+            .filter(|entry| self.matches(entry))
+            .cloned()
+            .collect();
+
+        if let Some(entry) = entries.iter().find(|e| Some(e.id) == self.selected) {
+            self.ui_details(ui, entry);
+        }
+        self.table_ui(ui, &entries);
+    }
+}
@@ -1,5 +1,7 @@
+use crate::components::password;
 use crate::helpers::{
-    fetchers::{RequestStatus, Requestor},
+    api::{self, ApiRequest},
+    deep_link::DeepLink,
     AppState,
 };
 use egui_notify::Toasts;
@@ -7,20 +9,6 @@ use email_address::*;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-#[derive(Default, Clone, PartialEq, serde::Serialize)]
-struct ChangePasswordSchema {
-    email: String,
-    old_password: String,
-    new_password: String,
-    #[serde(skip)]
-    confirm_password: String,
-}
-
-#[derive(Default, Clone, PartialEq, serde::Serialize)]
-struct ResetPasswordSchema {
-    password: String,
-}
-
 #[derive(serde::Deserialize, serde::Serialize)]
 #[serde(default)]
 pub struct PasswordResetApp {
@@ -29,27 +17,21 @@ pub struct PasswordResetApp {
     #[serde(skip)]
     token: String,
     #[serde(skip)]
-    url: String,
-    #[serde(skip)]
     toasts: Toasts,
     #[serde(skip)]
     new_password: String,
     #[serde(skip)]
     confirm_password: String,
     #[serde(skip)]
-    requestor: Option<Requestor>,
+    requestor: Option<ApiRequest<()>>,
     #[serde(skip)]
     app_state: Arc<Mutex<AppState>>,
 }
 
 impl Default for PasswordResetApp {
     fn default() -> Self {
-        let url = option_env!("BACKEND_URL")
-            .unwrap_or("http://12.34.56.78:9999/")
-            .to_string();
         Self {
             requestor: Default::default(),
-            url,
             token: "".to_string(),
             email: "".to_string(),
             toasts: Toasts::default(),
@@ -62,41 +44,64 @@ impl Default for PasswordResetApp {
 
 impl PasswordResetApp {
     fn submit_reset(&mut self) {
-        let submission = ResetPasswordSchema {
-            password: self.new_password.clone(),
-        };
+        self.requestor = Some(api::reset_password(
+            &self.app_state,
+            &self.token,
+            &self.new_password,
+        ));
+    }
 
-        let submission = Some(serde_json::to_string(&submission).unwrap());
-        let url = format!("{}api/auth/resetpassword/{}", self.url, self.token);
-        let app_state = Arc::clone(&self.app_state);
-        let mut req = Requestor::new_post(app_state, &url, true, submission);
-        req.send();
-        self.requestor = Some(req);
+    /// Fills in what the emailed link carries.
+    fn follow_link(&mut self) {
+        use super::App as _;
+        let link = self
+            .app_state
+            .lock()
+            .unwrap()
+            .take_deep_link(|link| self.handles_link(link));
+        if let Some(DeepLink::ResetPassword { token, email }) = link {
+            self.token = token;
+            if let Some(email) = email {
+                self.email = email;
+            }
+            self.new_password.clear();
+            self.confirm_password.clear();
+            self.toasts
+                .info("Choose a new password")
+                .set_duration(Some(Duration::from_secs(5)));
+        }
     }
 
     fn check_reset_promise(&mut self) {
-        let getter = &mut self.requestor;
-
-        if let Some(getter) = getter {
-            let result = &getter.check_promise();
-
-            match result {
-                RequestStatus::Failed(message) => {
-                    self.toasts
-                        .error(format!("Error Resetting password: {}", message))
-                        .set_duration(Some(Duration::from_secs(5)));
-                }
-                RequestStatus::Success(_) => {
-                    self.toasts
-                        .info("Password reset successfully! Please login.")
-                        .set_duration(Some(Duration::from_secs(5)));
-                }
-                _ => {}
+        let result = match &mut self.requestor {
+            Some(getter) => getter.check(),
+            None => return,
+        };
+        match result {
+            None => {}
+            Some(Err(message)) => {
+                self.toasts
+                    .error(format!("Error Resetting password: {}", message))
+                    .set_duration(Some(Duration::from_secs(5)));
+                self.requestor = None;
+            }
+            Some(Ok(())) => {
+                self.toasts
+                    .info("Password reset successfully! Please login.")
+                    .set_duration(Some(Duration::from_secs(5)));
+                self.requestor = None;
             }
         }
     }
 
     fn ui_reset(&mut self, ui: &mut egui::Ui) {
+        let policy = self
+            .app_state
+            .lock()
+            .unwrap()
+            .config
+            .password_policy
+            .clone();
         egui::Grid::new("login_grid")
             .num_columns(2)
             .spacing([20.0, 4.0])
@@ -115,17 +120,18 @@ impl PasswordResetApp {
                 ui.end_row();
 
                 ui.label("Password:");
-                ui.add(
-                    egui::widgets::text_edit::TextEdit::singleline(&mut self.new_password)
-                        .password(true),
-                );
+                ui.add(password::new_password(
+                    &mut self.new_password,
+                    &policy,
+                    &self.email,
+                ));
                 ui.end_row();
 
                 ui.label("Confirm Password:");
-                ui.add(
-                    egui::widgets::text_edit::TextEdit::singleline(&mut self.confirm_password)
-                        .password(true),
-                );
+                ui.add(password::confirm_password(
+                    &mut self.confirm_password,
+                    &self.new_password,
+                ));
                 ui.end_row();
             });
 
@@ -140,6 +146,10 @@ impl PasswordResetApp {
                     self.toasts
                         .error("Invalid email address")
                         .set_duration(Some(Duration::from_secs(5)));
+                } else if let Err(e) = policy.check(&self.new_password, &self.email) {
+                    self.toasts
+                        .error(e)
+                        .set_duration(Some(Duration::from_secs(5)));
                 } else {
                     self.submit_reset();
                 }
@@ -157,7 +167,12 @@ impl super::App for PasswordResetApp {
         self.app_state = app_state;
     }
 
+    fn handles_link(&self, link: &DeepLink) -> bool {
+        matches!(link, DeepLink::ResetPassword { .. })
+    }
+
     fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        self.follow_link();
         self.check_reset_promise();
 
         use super::View as _;
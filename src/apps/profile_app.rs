@@ -0,0 +1,394 @@
+use crate::helpers::{
+    api::{self, ApiRequest, StatusResponse},
+    execute, session,
+    user_profile::{self, UserProfile},
+    AppState, Languages, LoginState,
+};
+use egui_notify::Toasts;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+struct Avatar {
+    filename: String,
+    bytes: Vec<u8>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ProfileApp {
+    /// The copy being edited
+    #[serde(skip)]
+    profile: Option<UserProfile>,
+    #[serde(skip)]
+    load_error: Option<String>,
+    #[serde(skip)]
+    avatar: Option<egui::TextureHandle>,
+    #[serde(skip)]
+    avatar_channel: (Sender<Avatar>, Receiver<Avatar>),
+    #[serde(skip)]
+    profile_requestor: Option<ApiRequest<UserProfile>>,
+    #[serde(skip)]
+    save_requestor: Option<ApiRequest<StatusResponse>>,
+    #[serde(skip)]
+    avatar_requestor: Option<(ApiRequest<StatusResponse>, Vec<u8>)>,
+    #[serde(skip)]
+    toasts: Toasts,
+    /// The profile shown belongs to this session
+    #[serde(skip)]
+    active_session: String,
+    #[serde(skip)]
+    app_state: Arc<Mutex<AppState>>,
+}
+
+impl Default for ProfileApp {
+    fn default() -> Self {
+        Self {
+            profile: None,
+            load_error: None,
+            avatar: None,
+            avatar_channel: channel(),
+            profile_requestor: None,
+            save_requestor: None,
+            avatar_requestor: None,
+            toasts: Toasts::default(),
+            active_session: String::new(),
+            app_state: Arc::new(Mutex::new(AppState::default())),
+        }
+    }
+}
+
+impl ProfileApp {
+    fn fetch(&mut self) {
+        self.load_error = None;
+        self.profile_requestor = Some(api::profile(&self.app_state).latest_wins("profile"));
+    }
+
+    fn submit_save(&mut self) {
+        let profile = match &self.profile {
+            Some(profile) => profile,
+            None => return,
+        };
+        if let Err(e) = profile.validate() {
+            self.toasts
+                .error(e)
+                .set_duration(Some(Duration::from_secs(5)));
+            return;
+        }
+        self.save_requestor = Some(api::update_profile(&self.app_state, profile));
+    }
+
+    fn submit_avatar(&mut self, avatar: Avatar) {
+        if let Err(e) = user_profile::decode_avatar(&avatar.bytes) {
+            self.toasts
+                .error(e)
+                .set_duration(Some(Duration::from_secs(5)));
+            return;
+        }
+        let requestor = api::upload_avatar(&self.app_state, &avatar.filename, &avatar.bytes);
+        self.avatar_requestor = Some((requestor, avatar.bytes));
+    }
+
+    /* The profile is fetched once logged in, whether or not the window
+    is open, so the editors can start with the preferred language */
+    fn sync_with_session(&mut self) {
+        let (logged_in, stored, session) = {
+            let app_state = self.app_state.lock().unwrap();
+            let logged_in = matches!(app_state.logged_in, LoginState::LoggedIn);
+            (
+                logged_in,
+                app_state.user_profile.clone(),
+                app_state.config.session_key(),
+            )
+        };
+        // another backend's profile must not be saved to this one
+        if session != self.active_session {
+            self.active_session = session;
+            self.profile = None;
+            self.avatar = None;
+            self.load_error = None;
+            self.profile_requestor = None;
+            self.save_requestor = None;
+            self.avatar_requestor = None;
+        }
+        if !logged_in {
+            self.profile = None;
+            self.avatar = None;
+            self.load_error = None;
+            self.profile_requestor = None;
+            return;
+        }
+        match stored {
+            Some(stored) if self.profile.is_none() => self.profile = Some(stored),
+            Some(_) => {}
+            None if self.profile_requestor.is_none() && self.load_error.is_none() => {
+                self.profile = None;
+                self.avatar = None;
+                self.fetch();
+            }
+            None => {}
+        }
+    }
+
+    fn check_profile_promise(&mut self) {
+        let result = match &mut self.profile_requestor {
+            Some(requestor) => requestor.check(),
+            None => return,
+        };
+        match result {
+            None => {}
+            Some(Err(api::ApiError::Cancelled)) => self.profile_requestor = None,
+            Some(Err(e)) => {
+                log::warn!("Failed to fetch profile: {}", e);
+                self.load_error = Some(e.to_string());
+                self.profile_requestor = None;
+            }
+            Some(Ok(profile)) => {
+                self.app_state.lock().unwrap().user_profile = Some(profile.clone());
+                self.profile = Some(profile);
+                self.avatar = None;
+                self.profile_requestor = None;
+            }
+        }
+    }
+
+    fn check_save_promise(&mut self) {
+        let result = match &mut self.save_requestor {
+            Some(requestor) => requestor.check(),
+            None => return,
+        };
+        match result {
+            None => {}
+            Some(Err(e)) => {
+                self.toasts
+                    .error(format!("Failed to save profile: {}", e))
+                    .set_duration(Some(Duration::from_secs(5)));
+                self.save_requestor = None;
+            }
+            Some(Ok(_)) => {
+                self.save_requestor = None;
+                let profile = match &self.profile {
+                    Some(profile) => profile.clone(),
+                    None => return,
+                };
+                let identity = {
+                    let mut app_state = self.app_state.lock().unwrap();
+                    app_state.user_profile = Some(profile.clone());
+                    app_state.identity.clone()
+                };
+                if let Some(mut identity) = identity {
+                    identity.name = profile.name.trim().to_string();
+                    session::set_identity(&self.app_state, Some(identity));
+                }
+                self.toasts
+                    .info("Profile saved")
+                    .set_duration(Some(Duration::from_secs(5)));
+            }
+        }
+    }
+
+    fn check_avatar_promise(&mut self) {
+        let result = match &mut self.avatar_requestor {
+            Some((requestor, _)) => requestor.check(),
+            None => return,
+        };
+        match result {
+            None => {}
+            Some(Err(e)) => {
+                self.toasts
+                    .error(format!("Failed to upload avatar: {}", e))
+                    .set_duration(Some(Duration::from_secs(5)));
+                self.avatar_requestor = None;
+            }
+            Some(Ok(_)) => {
+                let (_, bytes) = self.avatar_requestor.take().unwrap();
+                if let Some(profile) = &mut self.profile {
+                    profile.set_avatar(&bytes);
+                }
+                if let Some(stored) = &mut self.app_state.lock().unwrap().user_profile {
+                    stored.set_avatar(&bytes);
+                }
+                self.avatar = None;
+                self.toasts
+                    .info("Avatar updated")
+                    .set_duration(Some(Duration::from_secs(5)));
+            }
+        }
+    }
+
+    fn pick_avatar(&mut self, ctx: &egui::Context) {
+        let sender = self.avatar_channel.0.clone();
+        let ctx = ctx.clone();
+        let task = rfd::AsyncFileDialog::new()
+            .add_filter("Image", &["png", "jpg", "jpeg"])
+            .pick_file();
+        execute(async move {
+            if let Some(file) = task.await {
+                let bytes = file.read().await;
+                let _ = sender.send(Avatar {
+                    filename: file.file_name(),
+                    bytes,
+                });
+                ctx.request_repaint();
+            }
+        });
+    }
+
+    fn ui_avatar(&mut self, ui: &mut egui::Ui) {
+        let profile = match &self.profile {
+            Some(profile) => profile,
+            None => return,
+        };
+        if self.avatar.is_none() {
+            match profile.avatar_image() {
+                Some(Ok(image)) => {
+                    self.avatar = Some(ui.ctx().load_texture("avatar", image, Default::default()))
+                }
+                Some(Err(e)) => log::warn!("Can't show avatar: {}", e),
+                None => {}
+            }
+        }
+
+        ui.vertical(|ui| {
+            match &self.avatar {
+                Some(texture) => {
+                    ui.image((texture.id(), texture.size_vec2()));
+                }
+                None => {
+                    ui.label(egui::RichText::new("👤").size(64.0));
+                }
+            }
+            let idle = self.avatar_requestor.is_none();
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(idle, egui::Button::new("Upload Avatar"))
+                    .on_hover_text(format!(
+                        "PNG or JPEG, at most {} KiB",
+                        user_profile::MAX_AVATAR_BYTES / 1024
+                    ))
+                    .clicked()
+                {
+                    self.pick_avatar(ui.ctx());
+                }
+                if !idle {
+                    ui.spinner();
+                }
+            });
+        });
+    }
+
+    fn ui_profile(&mut self, ui: &mut egui::Ui) {
+        let profile = match &mut self.profile {
+            Some(profile) => profile,
+            None => return,
+        };
+        egui::Grid::new("profile_grid")
+            .num_columns(2)
+            .spacing([20.0, 4.0])
+            .striped(false)
+            .show(ui, |ui| {
+                ui.label("Display Name:");
+                ui.add(egui::widgets::text_edit::TextEdit::singleline(
+                    &mut profile.name,
+                ))
+                .on_hover_text("Shown on the scoreboards");
+                ui.end_row();
+
+                ui.label("Language:");
+                egui::ComboBox::from_id_source("profile_language")
+                    .selected_text(profile.language.to_string())
+                    .show_ui(ui, |ui| {
+                        for language in Languages::iter() {
+                            ui.selectable_value(
+                                &mut profile.language,
+                                language,
+                                language.to_string(),
+                            );
+                        }
+                    });
+                ui.end_row();
+            });
+
+        ui.separator();
+        ui.label("Email me when:");
+        let notifications = &mut profile.notifications;
+        ui.checkbox(
+            &mut notifications.new_challenges,
+            "A new challenge is published",
+        );
+        ui.checkbox(&mut notifications.beaten_scores, "Someone beats my score");
+        ui.checkbox(
+            &mut notifications.submission_results,
+            "My submissions have been judged",
+        );
+
+        ui.separator();
+        let idle = self.save_requestor.is_none();
+        ui.horizontal(|ui| {
+            if ui.add_enabled(idle, egui::Button::new("Save")).clicked() {
+                self.submit_save();
+            }
+            if !idle {
+                ui.spinner();
+            }
+        });
+    }
+}
+
+impl super::App for ProfileApp {
+    fn name(&self) -> &'static str {
+        "👤 Profile"
+    }
+
+    fn set_app_state_ref(&mut self, app_state: Arc<Mutex<AppState>>) {
+        self.app_state = app_state;
+    }
+
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        self.sync_with_session();
+        self.check_profile_promise();
+        self.check_save_promise();
+        self.check_avatar_promise();
+        if let Ok(avatar) = self.avatar_channel.1.try_recv() {
+            self.submit_avatar(avatar);
+        }
+
+        use super::View as _;
+        egui::Window::new(self.name())
+            .open(open)
+            .default_height(500.0)
+            .show(ctx, |ui| self.ui(ui));
+
+        self.toasts.show(ctx);
+    }
+}
+
+impl super::View for ProfileApp {
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        let logged_in = matches!(
+            self.app_state.lock().unwrap().logged_in,
+            LoginState::LoggedIn
+        );
+        if !logged_in {
+            ui.label("Log in to see your profile");
+            return;
+        }
+        if let Some(e) = self.load_error.clone() {
+            ui.label(format!("Couldn't load your profile: {}", e));
+            if ui.button("Retry").clicked() {
+                self.fetch();
+            }
+            return;
+        }
+        if self.profile.is_none() {
+            ui.spinner();
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            self.ui_avatar(ui);
+            ui.separator();
+            ui.vertical(|ui| self.ui_profile(ui));
+        });
+    }
+}
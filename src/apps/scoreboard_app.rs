@@ -1,6 +1,6 @@
 use crate::helpers::{
-    fetchers::{RequestStatus, Requestor},
-    AppState,
+    api::{self, ApiError, ApiRequest},
+    offline_cache, retry, AppState,
 };
 use scoreboard_db::Builder as FilterBuilder;
 use scoreboard_db::Filter as ScoreBoardFilter;
@@ -15,13 +15,6 @@ enum FilterOption {
     UniqueLanguage,
 }
 
-#[derive(Clone, serde::Deserialize, serde::Serialize)]
-enum FetchResponse {
-    Success(Vec<Score>),
-    Failure(String),
-    FailAuth,
-}
-
 #[derive(serde::Deserialize, serde::Serialize)]
 pub struct ScoreBoardApp {
     selected_challenge: String,
@@ -34,10 +27,14 @@ pub struct ScoreBoardApp {
 
     scores: Option<Vec<Score>>,
     #[serde(skip)]
-    url: String,
+    active_session: String,
+    #[serde(skip)]
+    active_table: String,
+    #[serde(skip)]
+    was_offline: bool,
 
     #[serde(skip)]
-    score_fetcher: Option<Requestor>,
+    score_fetcher: Option<ApiRequest<Vec<Score>>>,
     #[serde(skip)]
     app_state: Arc<Mutex<AppState>>,
 }
@@ -48,14 +45,14 @@ impl Default for ScoreBoardApp {
             selected_challenge: "".to_string(),
             filter: FilterOption::All,
             sort_column: "time".to_string(),
-            url: option_env!("BACKEND_URL")
-                .unwrap_or("http://123.4.5.6:3000/")
-                .to_string(),
 
             active_challenge: None,
             active_filter: FilterOption::All,
             active_sort_column: "time".to_string(),
             scores: None,
+            active_session: String::new(),
+            active_table: String::new(),
+            was_offline: false,
             score_fetcher: None,
             app_state: Arc::new(Mutex::new(AppState::default())),
         }
@@ -64,23 +61,19 @@ impl Default for ScoreBoardApp {
 
 impl ScoreBoardApp {
     fn fetch(&mut self) {
-        self.scores = None;
-
-        let url = format!(
-            "{}api/game/scores/{}",
-            self.url,
-            self.app_state
-                .lock()
-                .unwrap()
+        {
+            let app_state = self.app_state.lock().unwrap();
+            let table = app_state
                 .challenges
-                .get_table(self.selected_challenge.clone())
-        );
+                .get_table(self.selected_challenge.clone());
+            // show what we had for this table until the fresh scores arrive
+            self.scores = app_state.scoreboards.get(&table).cloned();
+            self.active_table = table;
+        }
 
         log::debug!("Fetching scoreboard info");
-        let app_state = Arc::clone(&self.app_state);
-        let mut getter = Requestor::new_get(app_state, &url, true);
-        getter.send();
-        self.score_fetcher = Some(getter);
+        self.score_fetcher =
+            Some(api::scores(&self.app_state, &self.active_table).latest_wins("scoreboard"));
     }
 
     fn check_for_reload(&mut self) -> bool {
@@ -88,6 +81,20 @@ impl ScoreBoardApp {
             None => true,
             Some(active) => active != self.selected_challenge,
         };
+        let (session, offline) = {
+            let app_state = self.app_state.lock().unwrap();
+            (
+                app_state.config.session_key(),
+                app_state.offline_since.is_some(),
+            )
+        };
+        // what is shown may be from the cache, so fetch it again once back online
+        let reconnected = std::mem::replace(&mut self.was_offline, offline) && !offline;
+        if session != self.active_session || reconnected {
+            self.active_session = session;
+            self.active_challenge = Some(self.selected_challenge.clone());
+            return true;
+        }
         if challenges_differ
             || self.active_filter != self.filter
             || self.active_sort_column != self.sort_column
@@ -100,24 +107,13 @@ impl ScoreBoardApp {
         false
     }
 
-    fn check_fetch_promise(&mut self) -> RequestStatus {
-        let getter = &mut self.score_fetcher;
-
-        if let Some(getter) = getter {
-            let result = &getter.check_promise();
-
-            match result {
-                RequestStatus::Success(_) => {
-                    self.score_fetcher = None;
-                }
-                RequestStatus::Failed(_) => {
-                    self.score_fetcher = None;
-                }
-                _ => {}
-            }
-            return result.clone();
+    /// `None` while there is nothing new to show.
+    fn check_fetch_promise(&mut self) -> Option<Result<Vec<Score>, ApiError>> {
+        let result = self.score_fetcher.as_mut()?.check();
+        if result.is_some() {
+            self.score_fetcher = None;
         }
-        RequestStatus::NotStarted
+        result
     }
 }
 
@@ -136,6 +132,7 @@ impl super::App for ScoreBoardApp {
         }
 
         if let Some(fetcher) = self.score_fetcher.borrow_mut() {
+            retry::repaint_while_waiting(ctx, fetcher.next_retry());
             if fetcher.refresh_context() {
                 log::debug!("Refreshing context");
                 ctx.request_repaint();
@@ -215,25 +212,53 @@ impl super::View for ScoreBoardApp {
 }
 
 impl ScoreBoardApp {
+    fn stale_ui(&self, ui: &mut egui::Ui) {
+        let fetched_at = {
+            let app_state = self.app_state.lock().unwrap();
+            match app_state.offline_since {
+                Some(_) => app_state.scoreboard_times.get(&self.active_table).copied(),
+                None => None,
+            }
+        };
+        if let (Some(at), Some(_)) = (fetched_at, &self.scores) {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!(
+                    "⚠ Offline, these scores are {}",
+                    offline_cache::stale_since(at)
+                ),
+            );
+        }
+    }
+
     fn table_ui(&mut self, ui: &mut egui::Ui) {
         use egui_extras::{Column, TableBuilder};
 
         match self.check_fetch_promise() {
-            RequestStatus::Success(text) => {
-                self.score_fetcher = None;
-                self.scores = Some(serde_json::from_str(&text).unwrap());
+            Some(Ok(scores)) => {
+                self.app_state
+                    .lock()
+                    .unwrap()
+                    .store_scores(&self.active_table, scores.clone());
+                self.scores = Some(scores);
             }
-            RequestStatus::Failed(e) => {
-                self.score_fetcher = None;
+            Some(Err(ApiError::Cancelled)) => {}
+            Some(Err(e)) => {
                 let message = format!("Failed to fetch scores: {}", e);
                 log::error!("{}", message);
                 ui.label(message);
             }
-            RequestStatus::InProgress => {
-                ui.label("Fetching scores...");
-            }
-            RequestStatus::NotStarted => {}
+            None => match self.score_fetcher.as_ref().map(|f| f.next_retry()) {
+                Some(Some(at)) => {
+                    ui.label(format!("Couldn't fetch scores, {}", retry::countdown(at)));
+                }
+                Some(None) => {
+                    ui.label("Fetching scores...");
+                }
+                None => {}
+            },
         }
+        self.stale_ui(ui);
 
         let text_height = egui::TextStyle::Body.resolve(ui.style()).size;
         let table = TableBuilder::new(ui)
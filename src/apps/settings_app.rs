@@ -0,0 +1,307 @@
+use crate::helpers::{config::Profile, retry::RetryPolicy, AppState};
+use egui_notify::Toasts;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SettingsApp {
+    #[serde(skip)]
+    backend_url: Option<String>,
+    #[serde(skip)]
+    editing_profile: String,
+    #[serde(skip)]
+    new_profile_name: String,
+    #[serde(skip)]
+    new_profile_url: String,
+    #[serde(skip)]
+    toasts: Toasts,
+    #[serde(skip)]
+    app_state: Arc<Mutex<AppState>>,
+}
+
+impl Default for SettingsApp {
+    fn default() -> Self {
+        Self {
+            backend_url: None,
+            editing_profile: String::new(),
+            new_profile_name: String::new(),
+            new_profile_url: String::new(),
+            toasts: Toasts::default(),
+            app_state: Arc::new(Mutex::new(AppState::default())),
+        }
+    }
+}
+
+impl SettingsApp {
+    fn apply(&mut self, url: &str) {
+        let result = self.app_state.lock().unwrap().set_backend_url(url);
+        match result {
+            Ok(_) => {
+                self.backend_url = None;
+                self.toasts
+                    .info(format!("Using backend: {}", url))
+                    .set_duration(Some(Duration::from_secs(5)));
+            }
+            Err(e) => {
+                self.toasts
+                    .error(format!("Invalid backend URL: {}", e))
+                    .set_duration(Some(Duration::from_secs(5)));
+            }
+        }
+    }
+
+    fn accept_pending_backend(&mut self) {
+        let result = {
+            let mut app_state = self.app_state.lock().unwrap();
+            app_state
+                .config
+                .accept_pending_backend()
+                .and_then(|name| app_state.switch_profile(&name).map(|_| name))
+        };
+        match result {
+            Ok(name) => {
+                self.toasts
+                    .info(format!("Added and switched to {}", name))
+                    .set_duration(Some(Duration::from_secs(5)));
+            }
+            Err(e) => {
+                self.toasts
+                    .error(format!("Failed to add profile: {}", e))
+                    .set_duration(Some(Duration::from_secs(5)));
+            }
+        }
+    }
+
+    fn ui_pending_backend(&mut self, ui: &mut egui::Ui, url: &str) {
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            format!("⚠ A link asked to use the backend {}", url),
+        );
+        ui.label("Logging in sends it your password, only add it if you trust it.");
+        ui.horizontal(|ui| {
+            if ui.button("Add and Use").clicked() {
+                self.accept_pending_backend();
+            }
+            if ui.button("Ignore").clicked() {
+                self.app_state
+                    .lock()
+                    .unwrap()
+                    .config
+                    .dismiss_pending_backend();
+            }
+        });
+        ui.separator();
+    }
+
+    fn add_profile(&mut self) {
+        let result = Profile::new(&self.new_profile_name, &self.new_profile_url)
+            .and_then(|profile| self.app_state.lock().unwrap().config.add_profile(profile));
+        match result {
+            Ok(_) => {
+                self.new_profile_name.clear();
+                self.new_profile_url.clear();
+            }
+            Err(e) => {
+                self.toasts
+                    .error(format!("Failed to add profile: {}", e))
+                    .set_duration(Some(Duration::from_secs(5)));
+            }
+        }
+    }
+
+    fn ui_profiles(&mut self, ui: &mut egui::Ui) {
+        let (profiles, active) = {
+            let config = &self.app_state.lock().unwrap().config;
+            (
+                config.profiles().to_vec(),
+                config.active_profile().to_string(),
+            )
+        };
+
+        let mut selected = active.clone();
+        let mut remove = None;
+        egui::Grid::new("profiles_grid")
+            .num_columns(3)
+            .spacing([20.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                for profile in profiles.iter() {
+                    ui.radio_value(&mut selected, profile.name.clone(), &profile.name);
+                    ui.label(profile.backend_url());
+                    if ui
+                        .add_enabled(profile.name != active, egui::Button::new("🗑"))
+                        .on_hover_text("Remove profile")
+                        .clicked()
+                    {
+                        remove = Some(profile.name.clone());
+                    }
+                    ui.end_row();
+                }
+
+                ui.add(
+                    egui::widgets::text_edit::TextEdit::singleline(&mut self.new_profile_name)
+                        .hint_text("Name"),
+                );
+                ui.add(
+                    egui::widgets::text_edit::TextEdit::singleline(&mut self.new_profile_url)
+                        .hint_text("http://"),
+                );
+                if ui.button("➕").on_hover_text("Add profile").clicked() {
+                    self.add_profile();
+                }
+                ui.end_row();
+            });
+
+        let mut app_state = self.app_state.lock().unwrap();
+        if selected != active {
+            if let Err(e) = app_state.switch_profile(&selected) {
+                log::error!("Failed to switch profile: {}", e);
+            }
+        }
+        if let Some(name) = remove {
+            if let Err(e) = app_state.remove_profile(&name) {
+                log::error!("Failed to remove profile: {}", e);
+            }
+        }
+    }
+
+    fn ui_network(&mut self, ui: &mut egui::Ui) {
+        let (mut retry, mut refresh_secs) = {
+            let config = &self.app_state.lock().unwrap().config;
+            (config.retry.clone(), config.challenge_refresh_secs)
+        };
+        egui::Grid::new("network_grid")
+            .num_columns(2)
+            .spacing([20.0, 4.0])
+            .show(ui, |ui| {
+                ui.label("Timeout:");
+                ui.add(
+                    egui::DragValue::new(&mut retry.timeout_ms)
+                        .clamp_range(1000..=120_000)
+                        .speed(100)
+                        .suffix(" ms"),
+                );
+                ui.end_row();
+
+                ui.label("Retries:");
+                ui.add(egui::DragValue::new(&mut retry.max_retries).clamp_range(0..=10))
+                    .on_hover_text("For network errors, 5xx and 429 responses");
+                ui.end_row();
+
+                ui.label("First retry after:");
+                ui.add(
+                    egui::DragValue::new(&mut retry.base_delay_ms)
+                        .clamp_range(100..=10_000)
+                        .speed(100)
+                        .suffix(" ms"),
+                )
+                .on_hover_text("Doubles with every attempt");
+                ui.end_row();
+
+                ui.label("Longest wait:");
+                ui.add(
+                    egui::DragValue::new(&mut retry.max_delay_ms)
+                        .clamp_range(1000..=300_000)
+                        .speed(1000)
+                        .suffix(" ms"),
+                );
+                ui.end_row();
+
+                ui.label("Check for new challenges:");
+                ui.add(
+                    egui::DragValue::new(&mut refresh_secs)
+                        .clamp_range(0..=3600)
+                        .speed(10)
+                        .custom_formatter(|secs, _| match secs as u64 {
+                            0 => "Never".to_string(),
+                            secs => format!("every {} s", secs),
+                        }),
+                );
+                ui.end_row();
+            });
+
+        if ui.button("Restore defaults").clicked() {
+            retry = RetryPolicy::default();
+        }
+        let config = &mut self.app_state.lock().unwrap().config;
+        config.retry = retry;
+        config.challenge_refresh_secs = refresh_secs;
+    }
+}
+
+impl super::App for SettingsApp {
+    fn name(&self) -> &'static str {
+        "⚙ Settings"
+    }
+
+    fn set_app_state_ref(&mut self, app_state: Arc<Mutex<AppState>>) {
+        self.app_state = app_state;
+    }
+
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        use super::View as _;
+        egui::Window::new(self.name())
+            .open(open)
+            .default_width(400.0)
+            .show(ctx, |ui| self.ui(ui));
+
+        self.toasts.show(ctx);
+    }
+}
+
+impl super::View for SettingsApp {
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        let (profile, current, default, pending) = {
+            let config = &self.app_state.lock().unwrap().config;
+            (
+                config.active_profile().to_string(),
+                config.backend_url().to_string(),
+                config.default_backend_url().to_string(),
+                config.pending_backend().map(str::to_string),
+            )
+        };
+        if let Some(url) = pending {
+            self.ui_pending_backend(ui, &url);
+        }
+        if profile != self.editing_profile {
+            self.editing_profile = profile;
+            self.backend_url = None;
+        }
+        let backend_url = self.backend_url.get_or_insert_with(|| current.clone());
+
+        egui::Grid::new("settings_grid")
+            .num_columns(2)
+            .spacing([20.0, 4.0])
+            .striped(false)
+            .show(ui, |ui| {
+                ui.label("Backend:");
+                ui.add(egui::widgets::text_edit::TextEdit::singleline(backend_url))
+                    .on_hover_text("Where the challenge server can be reached");
+                ui.end_row();
+            });
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            let changed = *self.backend_url.as_ref().unwrap() != current;
+            if ui
+                .add_enabled(changed, egui::Button::new("Apply"))
+                .clicked()
+            {
+                let url = self.backend_url.clone().unwrap();
+                self.apply(&url);
+            }
+            if ui
+                .add_enabled(default != current, egui::Button::new("Reset to default"))
+                .on_hover_text(&default)
+                .clicked()
+            {
+                self.apply(&default);
+            }
+        });
+
+        ui.separator();
+        ui.collapsing("Profiles", |ui| self.ui_profiles(ui));
+        ui.collapsing("Network", |ui| self.ui_network(ui));
+    }
+}
@@ -0,0 +1,326 @@
+use crate::helpers::{
+    api::{self, ApiRequest},
+    deep_link::DeepLink,
+    retry,
+    submission::{PendingSubmission, Submission, SubmissionResult},
+    submission_history::SubmissionRecord,
+    AppState, LoginState,
+};
+use chrono::{Local, Utc};
+use egui_notify::Toasts;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub struct SubmissionHistoryApp {
+    fetcher: Option<ApiRequest<Vec<SubmissionRecord>>>,
+    /// Session key and email it was last fetched for
+    fetched_for: (String, Option<String>),
+    selected: Option<SubmissionRecord>,
+    resubmitter: Option<PendingSubmission>,
+    error: Option<String>,
+    toasts: Toasts,
+    app_state: Arc<Mutex<AppState>>,
+}
+
+impl Default for SubmissionHistoryApp {
+    fn default() -> Self {
+        Self {
+            fetcher: None,
+            fetched_for: (String::new(), None),
+            selected: None,
+            resubmitter: None,
+            error: None,
+            toasts: Toasts::default(),
+            app_state: Arc::new(Mutex::new(AppState::default())),
+        }
+    }
+}
+
+impl SubmissionHistoryApp {
+    fn fetch(&mut self) {
+        log::debug!("Fetching submission history");
+        self.fetcher = Some(api::submissions(&self.app_state).latest_wins("submission_history"));
+    }
+
+    /* Only fetched once the window is opened, and then again for
+    each session and whoever logs in, the results of new ones are kept as they come */
+    fn check_for_fetch(&mut self, open: bool) {
+        let (fetch_for, logged_in) = {
+            let app_state = self.app_state.lock().unwrap();
+            let email = app_state.identity.as_ref().map(|i| i.email.clone());
+            (
+                (app_state.config.session_key(), email),
+                matches!(app_state.logged_in, LoginState::LoggedIn),
+            )
+        };
+        if fetch_for != self.fetched_for {
+            self.selected = None;
+        }
+        if open && logged_in && fetch_for != self.fetched_for {
+            self.fetched_for = fetch_for;
+            self.fetch();
+        }
+    }
+
+    fn check_fetcher(&mut self) {
+        let result = match self.fetcher.as_mut().and_then(|fetcher| fetcher.check()) {
+            Some(result) => result,
+            None => return,
+        };
+        self.fetcher = None;
+        match result {
+            Ok(records) => {
+                let mut app_state = self.app_state.lock().unwrap();
+                app_state.submission_history.replace(records, Utc::now());
+                app_state.cache_dirty = true;
+                self.error = None;
+            }
+            Err(e) => {
+                log::error!("Failed to fetch submission history: {}", e);
+                self.error = Some(format!(
+                    "Couldn't fetch submissions: {}, showing what was kept",
+                    e
+                ));
+            }
+        }
+    }
+
+    fn check_resubmitter(&mut self) {
+        match Submission::check_sender(&mut self.resubmitter, &self.app_state) {
+            SubmissionResult::NotStarted => {}
+            SubmissionResult::Success { message, .. } => {
+                self.toasts
+                    .info(format!("Resubmitted: {}", message))
+                    .set_duration(Some(Duration::from_secs(5)));
+            }
+            result => {
+                self.toasts
+                    .warning(format!("Resubmitted: {}", result))
+                    .set_duration(Some(Duration::from_secs(5)));
+            }
+        }
+    }
+
+    fn resubmit(&mut self, record: &SubmissionRecord) {
+        let submission = record.to_submission();
+        if let Err(e) = submission.validate() {
+            self.error = Some(e);
+            return;
+        }
+        log::info!("Submitting {} again", submission.filename);
+        self.resubmitter = submission.sender(&self.app_state);
+    }
+
+    fn ui_details(&mut self, ui: &mut egui::Ui, record: &SubmissionRecord) {
+        egui::Grid::new("submission_history_details")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Challenge:");
+                ui.label(&record.challenge);
+                ui.end_row();
+
+                ui.label("File:");
+                ui.label(&record.filename);
+                ui.end_row();
+
+                ui.label("Language:");
+                ui.label(record.language.to_string());
+                ui.end_row();
+
+                ui.label("Kind:");
+                ui.label(kind(record));
+                ui.end_row();
+
+                ui.label("Submitted:");
+                ui.label(
+                    record
+                        .submitted_at
+                        .with_timezone(&Local)
+                        .format("%d %b %Y %H:%M:%S")
+                        .to_string(),
+                );
+                ui.end_row();
+
+                ui.label("Score:");
+                ui.label(score(record));
+                ui.end_row();
+            });
+        ui.label(&record.message);
+
+        let mut open_in_editor = false;
+        let mut resubmit = false;
+        ui.horizontal(|ui| {
+            let has_code = record.code.is_some();
+            open_in_editor = ui
+                .add_enabled(has_code, egui::Button::new("Open in Editor"))
+                .on_disabled_hover_text("Binaries aren't kept, upload it again")
+                .clicked();
+            resubmit = ui
+                .add_enabled(
+                    has_code && self.resubmitter.is_none(),
+                    egui::Button::new("Resubmit"),
+                )
+                .on_disabled_hover_text("Binaries aren't kept, upload it again")
+                .clicked();
+            if self.resubmitter.is_some() {
+                ui.spinner();
+            }
+        });
+        if let Some(code) = &record.code {
+            let mut code = code.as_str();
+            ui.add(
+                egui::TextEdit::multiline(&mut code)
+                    .code_editor()
+                    .desired_rows(8)
+                    .desired_width(f32::INFINITY),
+            );
+        }
+
+        if open_in_editor {
+            log::info!("Opening {} in the code editor", record.filename);
+            self.app_state.lock().unwrap().deep_link = Some(DeepLink::EditSubmission {
+                submission: record.to_submission(),
+            });
+        }
+        if resubmit {
+            self.resubmit(record);
+        }
+    }
+}
+
+fn kind(record: &SubmissionRecord) -> &'static str {
+    match record.test {
+        true => "Test",
+        false => "Submission",
+    }
+}
+
+fn score(record: &SubmissionRecord) -> String {
+    match record.score {
+        Some(score) => score.to_string(),
+        None => "-".to_string(),
+    }
+}
+
+impl super::App for SubmissionHistoryApp {
+    fn name(&self) -> &'static str {
+        "🗂 My Submissions"
+    }
+
+    fn set_app_state_ref(&mut self, app_state: Arc<Mutex<AppState>>) {
+        self.app_state = app_state;
+    }
+
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        self.check_for_fetch(*open);
+        self.check_fetcher();
+        self.check_resubmitter();
+
+        use super::View as _;
+        egui::Window::new(self.name())
+            .open(open)
+            .default_width(700.0)
+            .vscroll(true)
+            .resizable(true)
+            .show(ctx, |ui| self.ui(ui));
+
+        if let Some(fetcher) = &mut self.fetcher {
+            retry::repaint_while_waiting(ctx, fetcher.next_retry());
+            if fetcher.refresh_context() {
+                ctx.request_repaint();
+            }
+        }
+        if let Some(sender) = &mut self.resubmitter {
+            retry::repaint_while_waiting(ctx, sender.next_retry());
+            if sender.refresh_context() {
+                ctx.request_repaint();
+            }
+        }
+        self.toasts.show(ctx);
+    }
+}
+
+impl super::View for SubmissionHistoryApp {
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        let (records, fetched_at) = {
+            let app_state = self.app_state.lock().unwrap();
+            let history = &app_state.submission_history;
+            (history.records().to_vec(), history.fetched_at)
+        };
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(self.fetcher.is_none(), egui::Button::new("Refresh"))
+                .clicked()
+            {
+                self.fetch();
+            }
+            if self.fetcher.is_some() {
+                ui.spinner();
+            } else if let Some(at) = fetched_at {
+                ui.weak(format!(
+                    "Fetched {}",
+                    at.with_timezone(&Local).format("%d %b %H:%M")
+                ));
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        ui.separator();
+
+        if records.is_empty() {
+            ui.label("Nothing submitted yet");
+            return;
+        }
+
+        let mut selected = None;
+        egui::Grid::new("submission_history_grid")
+            .num_columns(7)
+            .striped(true)
+            .show(ui, |ui| {
+                for title in [
+                    "When",
+                    "Challenge",
+                    "Language",
+                    "File",
+                    "Kind",
+                    "Score",
+                    "Result",
+                ] {
+                    ui.strong(title);
+                }
+                ui.end_row();
+
+                for record in records.iter().rev() {
+                    let is_selected = self.selected.as_ref() == Some(record);
+                    let when = record
+                        .submitted_at
+                        .with_timezone(&Local)
+                        .format("%d %b %H:%M")
+                        .to_string();
+                    if ui.selectable_label(is_selected, when).clicked() {
+                        selected = Some(record.clone());
+                    }
+                    ui.label(&record.challenge);
+                    ui.label(record.language.to_string());
+                    ui.label(&record.filename);
+                    ui.label(kind(record));
+                    ui.label(score(record));
+                    match record.score {
+                        Some(_) => ui.label(&record.message),
+                        None => ui.colored_label(ui.visuals().error_fg_color, &record.message),
+                    };
+                    ui.end_row();
+                }
+            });
+        if selected.is_some() {
+            self.selected = selected;
+        }
+
+        if let Some(record) = self.selected.clone() {
+            ui.separator();
+            self.ui_details(ui, &record);
+        }
+    }
+}
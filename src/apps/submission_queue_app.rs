@@ -0,0 +1,234 @@
+use crate::helpers::{
+    retry, submission::Submission, submission_queue::QueuedSubmission, AppState, Languages,
+};
+use std::sync::{Arc, Mutex};
+
+pub struct SubmissionQueueApp {
+    /// The queued submission being edited, and the edits so far
+    editing: Option<(u64, Submission)>,
+    error: Option<String>,
+    app_state: Arc<Mutex<AppState>>,
+}
+
+impl Default for SubmissionQueueApp {
+    fn default() -> Self {
+        Self {
+            editing: None,
+            error: None,
+            app_state: Arc::new(Mutex::new(AppState::default())),
+        }
+    }
+}
+
+impl SubmissionQueueApp {
+    fn discard(&mut self, id: u64) {
+        let mut app_state = self.app_state.lock().unwrap();
+        if let Some(item) = app_state.submission_queue.remove(id) {
+            log::info!("Discarded queued {}", item.describe());
+        }
+        app_state.queue_dirty = true;
+        if matches!(self.editing, Some((editing, _)) if editing == id) {
+            self.editing = None;
+        }
+    }
+
+    fn save_edit(&mut self) {
+        let (id, submission) = match &self.editing {
+            Some(editing) => editing.clone(),
+            None => return,
+        };
+        if let Err(e) = submission.validate() {
+            self.error = Some(e);
+            return;
+        }
+        let mut app_state = self.app_state.lock().unwrap();
+        app_state.submission_queue.replace(id, submission);
+        app_state.queue_dirty = true;
+        self.editing = None;
+        self.error = None;
+    }
+
+    fn ui_status(ui: &mut egui::Ui, item: &QueuedSubmission, sending: bool, mine: bool) {
+        if !mine {
+            let owner = match item.owner.is_empty() {
+                true => "whoever queued it",
+                false => item.owner.as_str(),
+            };
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!("Held until {} logs in", owner),
+            );
+            return;
+        }
+        if sending {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Sending");
+            });
+            return;
+        }
+        let error = item.error.as_deref().unwrap_or("Waiting");
+        match item.held {
+            true => ui.colored_label(ui.visuals().error_fg_color, format!("✖ {}", error)),
+            false if item.attempts > 0 => {
+                ui.label(format!("{}, tried {} times", error, item.attempts))
+            }
+            false => ui.label(error),
+        };
+    }
+
+    fn ui_editor(&mut self, ui: &mut egui::Ui) {
+        let mut save = false;
+        let mut cancel = false;
+        if let Some((_, submission)) = &mut self.editing {
+            ui.strong("Edit");
+            egui::Grid::new("submission_queue_edit")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Filename:");
+                    ui.text_edit_singleline(&mut submission.filename);
+                    ui.end_row();
+
+                    ui.label("Language:");
+                    egui::ComboBox::from_id_source("submission_queue_language")
+                        .selected_text(submission.language.to_string())
+                        .show_ui(ui, |ui| {
+                            for language in Languages::iter() {
+                                ui.selectable_value(
+                                    &mut submission.language,
+                                    language,
+                                    language.to_string(),
+                                );
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Test:");
+                    ui.checkbox(&mut submission.test, "Only test it");
+                    ui.end_row();
+                });
+            if let Some(code) = &mut submission.code {
+                ui.add(
+                    egui::TextEdit::multiline(code)
+                        .code_editor()
+                        .desired_rows(8)
+                        .desired_width(f32::INFINITY),
+                );
+            }
+            ui.horizontal(|ui| {
+                save = ui.button("Save").clicked();
+                cancel = ui.button("Cancel").clicked();
+            });
+        }
+        if save {
+            self.save_edit();
+        }
+        if cancel {
+            self.editing = None;
+            self.error = None;
+        }
+    }
+}
+
+impl super::App for SubmissionQueueApp {
+    fn name(&self) -> &'static str {
+        "📤 Submission Queue"
+    }
+
+    fn set_app_state_ref(&mut self, app_state: Arc<Mutex<AppState>>) {
+        self.app_state = app_state;
+    }
+
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        use super::View as _;
+        egui::Window::new(self.name())
+            .open(open)
+            .default_width(600.0)
+            .vscroll(true)
+            .resizable(true)
+            .show(ctx, |ui| self.ui(ui));
+    }
+}
+
+impl super::View for SubmissionQueueApp {
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        let (items, sending, next_attempt, email) = {
+            let app_state = self.app_state.lock().unwrap();
+            let queue = &app_state.submission_queue;
+            (
+                queue.items().to_vec(),
+                queue.sending,
+                queue.next_attempt,
+                app_state.identity.as_ref().map(|i| i.email.clone()),
+            )
+        };
+        if items.is_empty() {
+            ui.label("Nothing is waiting to be sent");
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            match next_attempt {
+                Some(at) => ui.label(format!(
+                    "Couldn't reach the backend, {}",
+                    retry::countdown(at)
+                )),
+                None => ui.label("Sent in order once the backend can be reached"),
+            };
+            if ui.button("Send now").clicked() {
+                self.app_state.lock().unwrap().submission_queue.send_now();
+            }
+        });
+        ui.separator();
+
+        let mut discard = None;
+        egui::Grid::new("submission_queue_grid")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                for title in ["Queued", "Submission", "Status", ""] {
+                    ui.strong(title);
+                }
+                ui.end_row();
+
+                for item in &items {
+                    let is_sending = sending == Some(item.id);
+                    let mine = item.belongs_to(email.as_deref());
+                    ui.label(item.queued_at.format("%H:%M:%S").to_string());
+                    ui.label(item.describe());
+                    Self::ui_status(ui, item, is_sending, mine);
+                    ui.add_enabled_ui(!is_sending, |ui| {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add_enabled(mine, egui::Button::new("Edit"))
+                                .on_disabled_hover_text("Queued by someone else")
+                                .clicked()
+                            {
+                                self.editing = Some((item.id, item.submission()));
+                                self.error = None;
+                            }
+                            if ui
+                                .add_enabled(mine, egui::Button::new("Discard"))
+                                .on_disabled_hover_text("Queued by someone else")
+                                .clicked()
+                            {
+                                discard = Some(item.id);
+                            }
+                        });
+                    });
+                    ui.end_row();
+                }
+            });
+        if let Some(id) = discard {
+            self.discard(id);
+        }
+
+        if self.editing.is_some() {
+            ui.separator();
+            self.ui_editor(ui);
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use crate::helpers::{
+    changes::{diff_lines, ChangeEntry, DiffLine},
+    AppState, ChallengeChange,
+};
+use egui::{Color32, RichText};
+use egui_notify::Toasts;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub struct WhatsNewApp {
+    /// Newest change already toasted
+    last_notified: u64,
+    toasts: Toasts,
+    app_state: Arc<Mutex<AppState>>,
+}
+
+impl Default for WhatsNewApp {
+    fn default() -> Self {
+        Self {
+            last_notified: 0,
+            toasts: Toasts::default(),
+            app_state: Arc::new(Mutex::new(AppState::default())),
+        }
+    }
+}
+
+impl WhatsNewApp {
+    /* Runs while the window is closed too,
+    a new challenge is worth hearing about either way */
+    fn notify(&mut self) {
+        let app_state = self.app_state.lock().unwrap();
+        for entry in app_state.challenge_changes.entries() {
+            if entry.id <= self.last_notified {
+                continue;
+            }
+            self.last_notified = entry.id;
+            if let ChallengeChange::Added(_) = entry.change {
+                self.toasts
+                    .info(entry.change.to_string())
+                    .set_duration(Some(Duration::from_secs(5)));
+            }
+        }
+    }
+
+    fn ui_entry(ui: &mut egui::Ui, entry: &ChangeEntry) {
+        ui.horizontal(|ui| {
+            ui.label(entry.time.format("%H:%M:%S").to_string());
+            ui.strong(entry.change.to_string());
+            ui.weak(format!("-C {}", entry.change.challenge().command));
+        });
+        if let ChallengeChange::Edited { before, after } = &entry.change {
+            egui::CollapsingHeader::new("Changes")
+                .id_source(("whats_new_diff", entry.id))
+                .show(ui, |ui| Self::ui_diff(ui, &before.doc, &after.doc));
+        }
+    }
+
+    fn ui_diff(ui: &mut egui::Ui, before: &str, after: &str) {
+        let diff = diff_lines(before, after);
+        if diff.iter().all(|line| matches!(line, DiffLine::Same(_))) {
+            ui.label("The instructions are the same, something else about it changed");
+            return;
+        }
+        for line in diff {
+            let text = match line {
+                DiffLine::Same(text) => RichText::new(format!("  {}", text)),
+                DiffLine::Added(text) => {
+                    RichText::new(format!("+ {}", text)).color(Color32::from_rgb(80, 180, 80))
+                }
+                DiffLine::Removed(text) => {
+                    RichText::new(format!("- {}", text)).color(Color32::from_rgb(220, 60, 60))
+                }
+            };
+            ui.label(text.monospace());
+        }
+    }
+}
+
+impl super::App for WhatsNewApp {
+    fn name(&self) -> &'static str {
+        "🆕 What's New"
+    }
+
+    fn set_app_state_ref(&mut self, app_state: Arc<Mutex<AppState>>) {
+        self.app_state = app_state;
+    }
+
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        self.notify();
+
+        use super::View as _;
+        egui::Window::new(self.name())
+            .open(open)
+            .default_width(500.0)
+            .default_height(400.0)
+            .vscroll(false)
+            .resizable(true)
+            .show(ctx, |ui| self.ui(ui));
+        self.toasts.show(ctx);
+    }
+}
+
+impl super::View for WhatsNewApp {
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        // newest first
+        let entries: Vec<ChangeEntry> = self
+            .app_state
+            .lock()
+            .unwrap()
+            .challenge_changes
+            .entries()
+            .rev()
+            .cloned()
+            .collect();
+
+        ui.horizontal(|ui| {
+            ui.label("Challenges added, changed or closed since the app was opened");
+            if ui.button("Clear").clicked() {
+                self.app_state.lock().unwrap().challenge_changes.clear();
+            }
+        });
+        ui.separator();
+
+        if entries.is_empty() {
+            ui.label("Nothing has changed yet");
+            return;
+        }
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                for entry in &entries {
+                    Self::ui_entry(ui, entry);
+                    ui.separator();
+                }
+            });
+    }
+}
@@ -1,19 +1,25 @@
 use crate::helpers::{
-    fetchers::{RequestStatus, Requestor},
-    AppState, ChallengeCollection,
+    api::{self, ApiRequest},
+    AppState, Challenge,
 };
+use chrono::{DateTime, Duration, Utc};
 use std::sync::{Arc, Mutex};
 
 #[derive(PartialEq, Clone, Copy)]
 enum State {
     Dirty,
     Fetching,
-    Clean,
+    /// Backing off after a failed fetch
+    Waiting(DateTime<Utc>),
+    /// Fetched at, until it is time to look for changes
+    Clean(DateTime<Utc>),
 }
 
 pub struct ChallengeFetcher {
     state: State,
-    info_fetcher: Option<Requestor>,
+    info_fetcher: Option<ApiRequest<Vec<Challenge>>>,
+    session_key: String,
+    failures: u32,
     app_state: Arc<Mutex<AppState>>,
 }
 
@@ -22,6 +28,8 @@ impl Default for ChallengeFetcher {
         Self {
             info_fetcher: None,
             state: State::Dirty,
+            session_key: String::new(),
+            failures: 0,
             app_state: Arc::new(Mutex::new(AppState::default())),
         }
     }
@@ -32,49 +40,120 @@ impl ChallengeFetcher {
         Self {
             info_fetcher: None,
             state: State::Dirty,
+            session_key: String::new(),
+            failures: 0,
             app_state: app_state.clone(),
         }
     }
 
     pub fn tick(&mut self) {
+        self.check_profile();
+        self.check_backoff();
+        self.check_refresh();
         self.fetch();
         self.check_info_promise();
     }
 
+    fn check_profile(&mut self) {
+        let session_key = self.app_state.lock().unwrap().config.session_key();
+        if session_key != self.session_key {
+            log::debug!("Profile changed to {}", session_key);
+            self.session_key = session_key;
+            if let Some(fetcher) = &mut self.info_fetcher {
+                fetcher.cancel();
+            }
+            self.info_fetcher = None;
+            self.failures = 0;
+            self.state = State::Dirty;
+        }
+    }
+
+    fn check_backoff(&mut self) {
+        if let State::Waiting(at) = self.state {
+            if Utc::now() >= at {
+                self.state = State::Dirty;
+            }
+        }
+    }
+
+    fn check_refresh(&mut self) {
+        if let Some(at) = self.next_refresh() {
+            if Utc::now() >= at {
+                log::debug!("Checking for changed challenges");
+                self.state = State::Dirty;
+            }
+        }
+    }
+
+    /// When the challenges will be fetched again to look for changes.
+    pub fn next_refresh(&self) -> Option<DateTime<Utc>> {
+        let State::Clean(fetched_at) = self.state else {
+            return None;
+        };
+        match self.app_state.lock().unwrap().config.challenge_refresh_secs {
+            0 => None,
+            secs => Some(fetched_at + Duration::seconds(secs as i64)),
+        }
+    }
+
+    /// When the challenges will be fetched again, if the last attempt failed.
+    pub fn next_retry(&self) -> Option<DateTime<Utc>> {
+        match self.state {
+            State::Waiting(at) => Some(at),
+            State::Fetching => self.info_fetcher.as_ref()?.next_retry(),
+            _ => None,
+        }
+    }
+
     fn fetch(&mut self) {
         if self.state != State::Dirty {
             return;
         }
         log::debug!("Fetching challenge info");
         self.state = State::Fetching;
-        let app_state = self.app_state.clone();
-        let my_app_state = self.app_state.clone();
-        self.info_fetcher = my_app_state.lock().unwrap().challenges.fetch(app_state);
+        self.info_fetcher = Some(api::challenges(&self.app_state));
     }
     fn check_info_promise(&mut self) {
         if self.state != State::Fetching {
             return;
         }
 
-        let getter = &mut self.info_fetcher;
-
-        if let Some(getter) = getter {
-            let result = &getter.check_promise();
-            match result {
-                RequestStatus::NotStarted => {}
-                RequestStatus::InProgress => {}
-                RequestStatus::Success(data) => {
-                    log::debug!("Challenge info fetch success: {}", data);
-                    self.info_fetcher = None;
-                    self.state = State::Clean;
-                }
-                RequestStatus::Failed(_) => {
-                    self.info_fetcher = None;
-                    self.state = State::Dirty;
-                }
+        let result = match &mut self.info_fetcher {
+            Some(getter) => getter.check(),
+            None => None,
+        };
+        match result {
+            None => {}
+            Some(Ok(challenges)) => {
+                log::debug!("Challenge info fetch success");
+                self.info_fetcher = None;
+                self.failures = 0;
+                let now = Utc::now();
+                self.state = State::Clean(now);
+                let mut app_state = self.app_state.lock().unwrap();
+                let changes = app_state.challenges.update(challenges, now);
+                app_state.challenge_changes.record(changes, now);
+                app_state.cache_dirty = true;
+            }
+            /* The request has used up its own retries by now,
+            keep trying but don't hammer a backend that is down */
+            Some(Err(e)) => {
+                let delay = self
+                    .app_state
+                    .lock()
+                    .unwrap()
+                    .config
+                    .retry
+                    .delay(self.failures);
+                self.failures = self.failures.saturating_add(1);
+                log::error!(
+                    "Failed to fetch challenges: {}, trying again in {}s",
+                    e,
+                    delay.num_seconds()
+                );
+                self.info_fetcher = None;
+                self.state = State::Waiting(Utc::now() + delay);
             }
-            self.app_state.lock().unwrap().challenges =
-                ChallengeCollection::from_json(&result.to_string());
         }
     }
 }
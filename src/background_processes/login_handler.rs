@@ -11,6 +11,7 @@ enum State {
 pub struct LoginFetcher {
     app_state: Arc<Mutex<AppState>>,
     state: State,
+    session_key: String,
     token_refresh_promise: refresh::RefreshPromise,
 }
 
@@ -19,6 +20,7 @@ impl Default for LoginFetcher {
         Self {
             app_state: Arc::new(Mutex::new(AppState::default())),
             state: State::Idle,
+            session_key: String::new(),
             token_refresh_promise: None,
         }
     }
@@ -29,6 +31,7 @@ impl LoginFetcher {
         Self {
             app_state: app_state.clone(),
             state: State::Idle,
+            session_key: String::new(),
             token_refresh_promise: None,
         }
     }
@@ -39,13 +42,17 @@ impl LoginFetcher {
     }
 
     fn fetch(&mut self) {
-        if !self.app_state.lock().unwrap().needs_refresh() {
+        let session_key = self.app_state.lock().unwrap().config.session_key();
+        // The login window refreshes the session on start, so the first tick only records the profile
+        let profile_changed = !self.session_key.is_empty() && session_key != self.session_key;
+        self.session_key = session_key;
+        if !profile_changed && !self.app_state.lock().unwrap().needs_refresh() {
             return;
         }
         self.app_state.lock().unwrap().last_refresh = chrono::Utc::now().time();
         log::debug!("Refreshing token");
         self.state = State::Fetching;
-        self.token_refresh_promise = refresh::submit_refresh();
+        self.token_refresh_promise = refresh::submit_refresh(&self.app_state);
     }
 
     fn check_info_promise(&mut self) {
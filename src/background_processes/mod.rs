@@ -2,3 +2,5 @@ mod challenge_fetcher;
 pub use challenge_fetcher::ChallengeFetcher;
 mod login_handler;
 pub use login_handler::LoginFetcher;
+mod submission_sender;
+pub use submission_sender::SubmissionSender;
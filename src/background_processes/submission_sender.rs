@@ -0,0 +1,167 @@
+use crate::helpers::{
+    api::{self, ApiError, ApiRequest},
+    retry,
+    submission::{Submission, SubmissionResult},
+    submission_history::SubmissionRecord,
+    submission_queue, AppState, LoginState,
+};
+use chrono::Utc;
+use egui_notify::Toasts;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Works through the submission queue one at a time, oldest first.
+pub struct SubmissionSender {
+    sending: Option<(u64, Submission, ApiRequest<SubmissionResult>)>,
+    session_key: String,
+    failures: u32,
+    was_offline: bool,
+    toasts: Toasts,
+    app_state: Arc<Mutex<AppState>>,
+}
+
+impl Default for SubmissionSender {
+    fn default() -> Self {
+        Self::new(Arc::new(Mutex::new(AppState::default())))
+    }
+}
+
+impl SubmissionSender {
+    pub fn new(app_state: Arc<Mutex<AppState>>) -> Self {
+        Self {
+            sending: None,
+            session_key: String::new(),
+            failures: 0,
+            was_offline: false,
+            toasts: Toasts::default(),
+            app_state,
+        }
+    }
+
+    pub fn tick(&mut self, ctx: &egui::Context) {
+        self.check_profile();
+        self.check_sending();
+        self.send_next();
+
+        let next_attempt = self.app_state.lock().unwrap().submission_queue.next_attempt;
+        retry::repaint_while_waiting(ctx, next_attempt);
+        if let Some((_, _, request)) = &mut self.sending {
+            retry::repaint_while_waiting(ctx, request.next_retry());
+            if request.refresh_context() {
+                ctx.request_repaint();
+            }
+        }
+        self.toasts.show(ctx);
+    }
+
+    /// The queue couldn't be saved, so it would be lost on exit.
+    pub fn persist_failed(&mut self, error: &str) {
+        self.toasts
+            .error(format!("Couldn't keep the submission queue: {}", error))
+            .set_duration(Some(Duration::from_secs(5)));
+    }
+
+    fn check_profile(&mut self) {
+        let session_key = self.app_state.lock().unwrap().config.session_key();
+        if session_key != self.session_key {
+            self.session_key = session_key;
+            if let Some((_, _, request)) = &mut self.sending {
+                request.cancel();
+            }
+            self.sending = None;
+            self.failures = 0;
+        }
+    }
+
+    fn check_sending(&mut self) {
+        let result = match &mut self.sending {
+            Some((_, _, request)) => match request.check() {
+                Some(result) => result,
+                None => return,
+            },
+            None => return,
+        };
+        let (id, submission, _) = self.sending.take().unwrap();
+
+        let mut app_state = self.app_state.lock().unwrap();
+        let delay = app_state.config.retry.delay(self.failures);
+        let queue = &mut app_state.submission_queue;
+        queue.sending = None;
+        let description = match queue.get_mut(id) {
+            Some(item) => item.describe(),
+            None => "Queued submission".to_string(),
+        };
+        match result {
+            Ok(result) => {
+                log::info!("Sent {}: {}", description, result);
+                queue.remove(id);
+                queue.next_attempt = None;
+                if let Some(record) = SubmissionRecord::new(&submission, &result, Utc::now()) {
+                    app_state.submission_history.record(record);
+                    app_state.cache_dirty = true;
+                }
+                self.failures = 0;
+                self.toasts
+                    .info(format!("Sent {}: {}", description, result))
+                    .set_duration(Some(Duration::from_secs(5)));
+            }
+            Err(ApiError::Cancelled) => {}
+            /* Logged out along the way counts as not getting there,
+            it is sent once logged in again */
+            Err(e) if submission_queue::should_queue(&e) || matches!(e, ApiError::Auth(_)) => {
+                log::warn!("{} still can't be sent: {}", description, e);
+                if let Some(item) = queue.get_mut(id) {
+                    item.attempts += 1;
+                    item.error = Some(e.to_string());
+                }
+                queue.next_attempt = Some(Utc::now() + delay);
+                self.failures = self.failures.saturating_add(1);
+            }
+            Err(e) => {
+                log::error!("{} was turned away: {}", description, e);
+                if let Some(item) = queue.get_mut(id) {
+                    item.attempts += 1;
+                    item.error = Some(e.to_string());
+                    item.held = true;
+                }
+                self.toasts
+                    .error(format!("{} was turned away: {}", description, e))
+                    .set_duration(Some(Duration::from_secs(5)));
+            }
+        }
+        app_state.queue_dirty = true;
+    }
+
+    /* Waits out the backoff, unless the backend has just
+    been heard from again or the player asked to send now */
+    fn send_next(&mut self) {
+        if self.sending.is_some() {
+            return;
+        }
+        let (id, submission) = {
+            let mut app_state = self.app_state.lock().unwrap();
+            let offline = app_state.offline_since.is_some();
+            let reconnected = std::mem::replace(&mut self.was_offline, offline) && !offline;
+            if !matches!(app_state.logged_in, LoginState::LoggedIn) {
+                return;
+            }
+            let email = app_state.identity.as_ref().map(|i| i.email.clone());
+            let queue = &mut app_state.submission_queue;
+            let due = queue.next_attempt.map_or(true, |at| Utc::now() >= at);
+            let woken = std::mem::take(&mut queue.wake);
+            if !(due || reconnected || woken) {
+                return;
+            }
+            let (id, submission) = match queue.next(email.as_deref()) {
+                Some(item) => (item.id, item.submission()),
+                None => return,
+            };
+            queue.sending = Some(id);
+            queue.next_attempt = None;
+            (id, submission)
+        };
+        log::info!("Sending queued submission of {}", submission.filename);
+        let request = api::submit(&self.app_state, &submission);
+        self.sending = Some((id, submission, request));
+    }
+}
@@ -1,6 +1,6 @@
 use crate::helpers::{
-    fetchers::Requestor,
-    submission::{Submission, SubmissionResult},
+    retry,
+    submission::{PendingSubmission, Submission, SubmissionResult},
     AppState, Languages,
 };
 use egui::*;
@@ -19,8 +19,6 @@ pub struct CodeEditor {
     instructions: String,
     label: String,
 
-    #[serde(skip)]
-    url: String,
     #[serde(skip)]
     last_result: SubmissionResult,
     #[serde(skip)]
@@ -31,7 +29,7 @@ pub struct CodeEditor {
     selected_challenge: String,
 
     #[serde(skip)]
-    submitter: Option<Requestor>,
+    submitter: Option<PendingSubmission>,
     #[serde(skip)]
     pub app_state: Arc<Mutex<AppState>>,
 }
@@ -46,9 +44,6 @@ impl Default for CodeEditor {
             instructions: "No Challenge Loaded".into(),
             label: "Code Editor".into(),
 
-            url: option_env!("BACKEND_URL")
-                .unwrap_or("http://123.4.5.6:3000/")
-                .to_string(),
             last_result: SubmissionResult::NotStarted,
             toasts: Toasts::default(),
             submitter: None,
@@ -61,16 +56,16 @@ impl Default for CodeEditor {
 
 impl CodeEditor {
     fn submit(&mut self) {
-        let submission = self.run.clone();
-        let url = format!("{}api/game/submit", self.url);
-        let app_state = Arc::clone(&self.app_state);
-        self.submitter = submission.sender(app_state, &url);
+        self.submitter = self.run.clone().sender(&self.app_state);
     }
 }
 
 impl CodeEditor {
     pub fn panels(&mut self, ctx: &egui::Context) {
-        let submission = Submission::check_sender(&mut self.submitter);
+        let submission = Submission::check_sender(&mut self.submitter, &self.app_state);
+        if let Some(submitter) = &self.submitter {
+            retry::repaint_while_waiting(ctx, submitter.next_retry());
+        }
         match submission {
             SubmissionResult::NotStarted => {}
             SubmissionResult::Success { score: _, message } => {
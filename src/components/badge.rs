@@ -0,0 +1,91 @@
+use crate::helpers::{Availability, Challenge, Difficulty, IoMode};
+use chrono::{DateTime, Utc};
+use egui::{Color32, RichText};
+
+/// A small rounded label, coloured to stand out from plain text.
+pub fn badge(ui: &mut egui::Ui, text: impl Into<String>, colour: Color32) -> egui::Response {
+    egui::Frame::none()
+        .fill(colour.linear_multiply(0.15))
+        .stroke(egui::Stroke::new(1.0, colour))
+        .rounding(8.0)
+        .inner_margin(egui::Margin::symmetric(6.0, 1.0))
+        .show(ui, |ui| ui.label(RichText::new(text).small().color(colour)))
+        .inner
+}
+
+fn difficulty_colour(difficulty: Difficulty) -> Color32 {
+    match difficulty {
+        Difficulty::Easy => Color32::from_rgb(80, 180, 80),
+        Difficulty::Medium => Color32::from_rgb(210, 180, 40),
+        Difficulty::Hard => Color32::from_rgb(230, 130, 30),
+        Difficulty::Expert => Color32::from_rgb(220, 60, 60),
+    }
+}
+
+fn availability_colour(availability: Availability) -> Color32 {
+    match availability {
+        Availability::Upcoming => Color32::LIGHT_BLUE,
+        Availability::Open => Color32::from_rgb(80, 180, 80),
+        Availability::Closed => Color32::GRAY,
+    }
+}
+
+/// Everything known about `challenge`, as a row of badges that wraps.
+pub fn challenge_badges(ui: &mut egui::Ui, challenge: &Challenge, now: DateTime<Utc>) {
+    let meta = &challenge.meta;
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 4.0;
+        badge(ui, format!("-C {}", challenge.command), Color32::GRAY)
+            .on_hover_text("Pass this to the judge");
+        if let Some(difficulty) = meta.difficulty {
+            badge(ui, difficulty.to_string(), difficulty_colour(difficulty));
+        }
+        let availability = meta.availability(now);
+        let dates = match (meta.opens, meta.closes) {
+            (None, None) => None,
+            (opens, closes) => Some(format!(
+                "{} until {}",
+                opens.map_or("Any time".to_string(), |d| d.format("%Y-%m-%d").to_string()),
+                closes.map_or("forever".to_string(), |d| d.format("%Y-%m-%d").to_string())
+            )),
+        };
+        if let Some(dates) = dates {
+            badge(
+                ui,
+                availability.to_string(),
+                availability_colour(availability),
+            )
+            .on_hover_text(dates);
+        }
+        badge(
+            ui,
+            match meta.io_mode {
+                IoMode::FileArgument => "📄 File",
+                IoMode::StdinLoop => "⟳ Stdin",
+            },
+            Color32::GRAY,
+        )
+        .on_hover_text(meta.io_mode.to_string());
+        if let Some(samples) = meta.samples {
+            badge(ui, format!("{} samples", samples), Color32::GRAY);
+        }
+        if let Some(limit) = meta.time_limit_ms {
+            badge(
+                ui,
+                format!("⏱ {:.1}s", limit as f64 / 1000.0),
+                Color32::GRAY,
+            )
+            .on_hover_text("Time limit for a run");
+        }
+        if !meta.languages.is_empty() {
+            let languages: Vec<String> = meta.languages.iter().map(|l| l.to_string()).collect();
+            badge(ui, languages.join(", "), Color32::GRAY).on_hover_text("Allowed languages");
+        }
+        for tag in &meta.tags {
+            badge(ui, format!("#{}", tag), Color32::LIGHT_BLUE);
+        }
+        if let Some(author) = &meta.author {
+            badge(ui, format!("✏ {}", author), Color32::GRAY).on_hover_text("Author");
+        }
+    });
+}
@@ -1,29 +1,176 @@
+/// What a new password has to look like, set per deployment in the [`Config`](crate::helpers::Config).
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    /// How many of lower case, upper case, digits and symbols must be used
+    pub min_classes: usize,
+    /// Reject passwords containing the email address or its user name
+    pub forbid_email: bool,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            min_classes: 2,
+            forbid_email: true,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    /// Everything wrong with `password`, empty when it's acceptable.
+    pub fn violations(&self, password: &str, email: &str) -> Vec<String> {
+        let mut violations = Vec::new();
+        if password.chars().count() < self.min_length {
+            violations.push(format!("At least {} characters", self.min_length));
+        }
+        if classes(password) < self.min_classes {
+            violations.push(format!(
+                "At least {} of lower case, upper case, digits and symbols",
+                self.min_classes
+            ));
+        }
+        if self.forbid_email && contains_email(password, email) {
+            violations.push("Must not contain your email address".to_string());
+        }
+        violations
+    }
+
+    pub fn check(&self, password: &str, email: &str) -> Result<(), String> {
+        match self.violations(password, email).into_iter().next() {
+            Some(violation) => Err(format!("Password too weak: {}", violation)),
+            None => Ok(()),
+        }
+    }
+}
+
+fn classes(password: &str) -> usize {
+    [
+        password.chars().any(|c| c.is_lowercase()),
+        password.chars().any(|c| c.is_uppercase()),
+        password.chars().any(|c| c.is_ascii_digit()),
+        password.chars().any(|c| !c.is_alphanumeric()),
+    ]
+    .iter()
+    .filter(|used| **used)
+    .count()
+}
+
+fn contains_email(password: &str, email: &str) -> bool {
+    let password = password.to_lowercase();
+    let email = email.trim().to_lowercase();
+    let user = email.split('@').next().unwrap_or_default();
+    // a short user name like "jo" turns up by chance
+    [email.as_str(), user]
+        .iter()
+        .any(|part| part.chars().count() >= 3 && password.contains(part))
+}
+
+/* Bits of entropy if every character were picked at random from the
+classes used. People aren't random, so a character repeating or
+continuing a run from the one before (aaa, abc, 321) only counts for half */
+pub fn entropy(password: &str) -> f64 {
+    let chars: Vec<char> = password.chars().collect();
+    let mut pool = 0;
+    if chars.iter().any(|c| c.is_ascii_lowercase()) {
+        pool += 26;
+    }
+    if chars.iter().any(|c| c.is_ascii_uppercase()) {
+        pool += 26;
+    }
+    if chars.iter().any(|c| c.is_ascii_digit()) {
+        pool += 10;
+    }
+    if chars
+        .iter()
+        .any(|c| c.is_ascii() && !c.is_ascii_alphanumeric())
+    {
+        pool += 33;
+    }
+    if chars.iter().any(|c| !c.is_ascii()) {
+        pool += 100;
+    }
+    if pool == 0 {
+        return 0.0;
+    }
+
+    let length: f64 = chars
+        .iter()
+        .enumerate()
+        .map(|(i, c)| match i.checked_sub(1).map(|j| chars[j]) {
+            Some(previous) if (*c as i64 - previous as i64).abs() <= 1 => 0.5,
+            _ => 1.0,
+        })
+        .sum();
+    length * (pool as f64).log2()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum Strength {
+    VeryWeak,
+    Weak,
+    Reasonable,
+    Strong,
+    VeryStrong,
+}
+
+impl Strength {
+    pub fn of(password: &str) -> Self {
+        match entropy(password) {
+            bits if bits < 28.0 => Strength::VeryWeak,
+            bits if bits < 36.0 => Strength::Weak,
+            bits if bits < 60.0 => Strength::Reasonable,
+            bits if bits < 128.0 => Strength::Strong,
+            _ => Strength::VeryStrong,
+        }
+    }
+
+    fn color(&self, visuals: &egui::Visuals) -> egui::Color32 {
+        match self {
+            Strength::VeryWeak | Strength::Weak => visuals.error_fg_color,
+            Strength::Reasonable => visuals.warn_fg_color,
+            Strength::Strong | Strength::VeryStrong => egui::Color32::from_rgb(0, 160, 60),
+        }
+    }
+}
+
+impl std::fmt::Display for Strength {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Strength::VeryWeak => write!(f, "Very weak"),
+            Strength::Weak => write!(f, "Weak"),
+            Strength::Reasonable => write!(f, "Reasonable"),
+            Strength::Strong => write!(f, "Strong"),
+            Strength::VeryStrong => write!(f, "Very strong"),
+        }
+    }
+}
+
 #[allow(clippy::ptr_arg)] // false positive
-pub fn _password_ui(ui: &mut egui::Ui, password: &mut String) -> egui::Response {
-    // Generate an id for the state
-    let state_id = ui.id().with("show_plaintext");
+pub fn password_ui(ui: &mut egui::Ui, password: &mut String) -> egui::Response {
+    // Generate an id for the state, fields sharing a grid share its `ui.id()`
+    let state_id = ui.next_auto_id().with("show_plaintext");
 
     // Get state for this widget.
     // You should get state by value, not by reference to avoid borrowing of [`Memory`].
     let mut show_plaintext = ui.data_mut(|d| d.get_temp::<bool>(state_id).unwrap_or(false));
 
     // Process ui, change a local copy of the state
-    // We want TextEdit to fill entire space, and have button after that, so in that case we can
-    // change direction to right_to_left.
-    let result = ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+    let result = ui.horizontal(|ui| {
+        // Show the password field:
+        let response = ui.add(egui::TextEdit::singleline(password).password(!show_plaintext));
+
         // Toggle the `show_plaintext` bool with a button:
-        let response = ui
+        if ui
             .add(egui::SelectableLabel::new(show_plaintext, "👁"))
-            .on_hover_text("Show/hide password");
-
-        if response.clicked() {
+            .on_hover_text("Show/hide password")
+            .clicked()
+        {
             show_plaintext = !show_plaintext;
         }
-        // Show the password field:
-        ui.add_sized(
-            ui.available_size(),
-            egui::TextEdit::singleline(password).password(!show_plaintext),
-        );
+        response
     });
 
     // Store the (possibly changed) state:
@@ -31,7 +178,7 @@ pub fn _password_ui(ui: &mut egui::Ui, password: &mut String) -> egui::Response
 
     // All done! Return the interaction response so the user can check what happened
     // (hovered, clicked, …) and maybe show a tooltip:
-    result.response
+    result.inner
 }
 
 // A wrapper that allows the more idiomatic usage pattern: `ui.add(…)`
@@ -41,6 +188,120 @@ pub fn _password_ui(ui: &mut egui::Ui, password: &mut String) -> egui::Response
 /// ``` ignore
 /// ui.add(password(&mut my_password));
 /// ```
-pub fn _password(password: &mut String) -> impl egui::Widget + '_ {
-    move |ui: &mut egui::Ui| _password_ui(ui, password)
+pub fn password(password: &mut String) -> impl egui::Widget + '_ {
+    move |ui: &mut egui::Ui| password_ui(ui, password)
+}
+
+pub fn new_password_ui(
+    ui: &mut egui::Ui,
+    password: &mut String,
+    policy: &PasswordPolicy,
+    email: &str,
+) -> egui::Response {
+    ui.vertical(|ui| {
+        let response = password_ui(ui, password);
+        if password.is_empty() {
+            return response;
+        }
+
+        let strength = Strength::of(password);
+        let fraction = match strength {
+            Strength::VeryWeak => 0.1,
+            Strength::Weak => 0.3,
+            Strength::Reasonable => 0.55,
+            Strength::Strong => 0.8,
+            Strength::VeryStrong => 1.0,
+        };
+        ui.add(
+            egui::ProgressBar::new(fraction)
+                .desired_width(150.0)
+                .fill(strength.color(ui.visuals()))
+                .text(strength.to_string()),
+        )
+        .on_hover_text(format!("About {:.0} bits of entropy", entropy(password)));
+
+        let error = ui.visuals().error_fg_color;
+        for violation in policy.violations(password, email) {
+            ui.colored_label(error, format!("✖ {}", violation));
+        }
+        response
+    })
+    .inner
+}
+
+/// Password field for choosing a new password, it rates the password's
+/// strength and lists what `policy` still asks for.
+///
+/// ## Example:
+/// ``` ignore
+/// ui.add(new_password(&mut my_password, &policy, &email));
+/// ```
+pub fn new_password<'a>(
+    password: &'a mut String,
+    policy: &'a PasswordPolicy,
+    email: &'a str,
+) -> impl egui::Widget + 'a {
+    move |ui: &mut egui::Ui| new_password_ui(ui, password, policy, email)
+}
+
+pub fn confirm_password_ui(
+    ui: &mut egui::Ui,
+    confirm: &mut String,
+    password: &str,
+) -> egui::Response {
+    ui.vertical(|ui| {
+        let response = password_ui(ui, confirm);
+        if !confirm.is_empty() && confirm != password {
+            ui.colored_label(ui.visuals().error_fg_color, "✖ Passwords do not match");
+        }
+        response
+    })
+    .inner
+}
+
+/// Password field repeating `password`, it says so when they differ.
+///
+/// ## Example:
+/// ``` ignore
+/// ui.add(confirm_password(&mut confirm, &my_password));
+/// ```
+pub fn confirm_password<'a>(confirm: &'a mut String, password: &'a str) -> impl egui::Widget + 'a {
+    move |ui: &mut egui::Ui| confirm_password_ui(ui, confirm, password)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy() {
+        let policy = PasswordPolicy::default();
+        assert!(policy.check("hunter22", "player@dummy.com").is_ok());
+        assert_eq!(policy.violations("hunter2", "").len(), 1);
+        assert_eq!(policy.violations("hunterhunter", "").len(), 1);
+        assert_eq!(
+            policy.violations("player123", "Player@dummy.com"),
+            vec!["Must not contain your email address".to_string()]
+        );
+        assert!(policy.check("jo123456", "jo@dummy.com").is_ok());
+
+        let strict = PasswordPolicy {
+            min_length: 12,
+            min_classes: 4,
+            forbid_email: false,
+        };
+        assert_eq!(strict.violations("player123", "player@dummy.com").len(), 2);
+    }
+
+    #[test]
+    fn test_strength() {
+        assert_eq!(Strength::of(""), Strength::VeryWeak);
+        assert_eq!(Strength::of("aaaaaaaa"), Strength::VeryWeak);
+        assert!(Strength::of("abcdefgh") < Strength::of("qmzrtwxk"));
+        assert_eq!(Strength::of("hunter22"), Strength::Reasonable);
+        assert_eq!(
+            Strength::of("correct horse battery staple"),
+            Strength::VeryStrong
+        );
+    }
 }
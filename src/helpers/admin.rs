@@ -0,0 +1,210 @@
+use chrono::{DateTime, Utc};
+use scoreboard_db::Score;
+use std::collections::VecDeque;
+use std::fmt::{self, Display, Formatter};
+
+pub const ADMIN_ROLE: &str = "admin";
+pub const AUDIT_CAPACITY: usize = 500;
+
+/// An account as listed by `api/admin/users`.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct AdminUser {
+    pub email: String,
+    pub name: String,
+    pub roles: Vec<String>,
+    pub locked: bool,
+    pub verified: bool,
+}
+
+/// Something only an admin may do, each is confirmed before it is sent.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AdminAction {
+    Lock {
+        email: String,
+    },
+    Unlock {
+        email: String,
+    },
+    /// Emails the user a reset link
+    ResetPassword {
+        email: String,
+    },
+    /// Deletes the entry as if it was never submitted
+    RemoveScore {
+        table: String,
+        score: Score,
+    },
+    /// Keeps the entry but takes it off the scoreboard
+    DisqualifyScore {
+        table: String,
+        score: Score,
+    },
+}
+
+impl AdminAction {
+    pub fn path(&self) -> &'static str {
+        match self {
+            AdminAction::Lock { .. } => "api/admin/users/lock",
+            AdminAction::Unlock { .. } => "api/admin/users/unlock",
+            AdminAction::ResetPassword { .. } => "api/admin/users/resetpassword",
+            AdminAction::RemoveScore { .. } => "api/admin/scores/remove",
+            AdminAction::DisqualifyScore { .. } => "api/admin/scores/disqualify",
+        }
+    }
+
+    pub fn payload(&self) -> serde_json::Value {
+        match self {
+            AdminAction::Lock { email }
+            | AdminAction::Unlock { email }
+            | AdminAction::ResetPassword { email } => serde_json::json!({ "email": email }),
+            AdminAction::RemoveScore { table, score }
+            | AdminAction::DisqualifyScore { table, score } => serde_json::json!({
+                "table": table,
+                "name": score.name,
+                "command": score.command,
+                "time_ns": score.time_ns,
+                "language": score.language,
+            }),
+        }
+    }
+}
+
+impl Display for AdminAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AdminAction::Lock { email } => write!(f, "Lock the account of {}", email),
+            AdminAction::Unlock { email } => write!(f, "Unlock the account of {}", email),
+            AdminAction::ResetPassword { email } => {
+                write!(f, "Send {} a password reset link", email)
+            }
+            AdminAction::RemoveScore { table, score } => write!(
+                f,
+                "Remove the {} score by {} from {}",
+                score.language, score.name, table
+            ),
+            AdminAction::DisqualifyScore { table, score } => write!(
+                f,
+                "Disqualify the {} score by {} from {}",
+                score.language, score.name, table
+            ),
+        }
+    }
+}
+
+/// One admin action, as kept in the [`AuditLog`].
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct AuditEntry {
+    pub id: u64,
+    pub time: DateTime<Utc>,
+    /// Who did it
+    pub admin: String,
+    pub action: String,
+    /// `None` while the backend hasn't answered
+    pub outcome: Option<Result<String, String>>,
+}
+
+/// What was done from the admin console on this device, newest last.
+/// It is kept between runs in its own store, see [`offline_cache`](super::offline_cache).
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct AuditLog {
+    entries: VecDeque<AuditEntry>,
+    next_id: u64,
+}
+
+impl AuditLog {
+    /* Recorded before the request goes out, so an action
+    that never gets an answer is still accounted for */
+    pub fn record(&mut self, admin: &str, action: &AdminAction) -> u64 {
+        self.next_id += 1;
+        log::info!("[audit] {} by {}", action, admin);
+        self.entries.push_back(AuditEntry {
+            id: self.next_id,
+            time: Utc::now(),
+            admin: admin.to_string(),
+            action: action.to_string(),
+            outcome: None,
+        });
+        while self.entries.len() > AUDIT_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.next_id
+    }
+
+    pub fn complete(&mut self, id: u64, outcome: Result<String, String>) {
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.id == id) {
+            match &outcome {
+                Ok(_) => log::info!("[audit] {}: done", entry.action),
+                Err(e) => log::warn!("[audit] {}: failed, {}", entry.action, e),
+            }
+            entry.outcome = Some(outcome);
+        }
+    }
+
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &AuditEntry> {
+        self.entries.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Answers that were still awaited when the app closed never come.
+    pub fn close_unanswered(&mut self) {
+        for entry in self.entries.iter_mut().filter(|e| e.outcome.is_none()) {
+            entry.outcome = Some(Err("No answer before the app was closed".to_string()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_log() {
+        let mut log = AuditLog::default();
+        let score = Score {
+            name: "Player1".to_string(),
+            command: "2331".to_string(),
+            time_ns: 1500.0,
+            hash: String::new(),
+            language: "rust".to_string(),
+        };
+        let action = AdminAction::DisqualifyScore {
+            table: "23_3_1".to_string(),
+            score,
+        };
+        assert_eq!(action.payload()["name"], "Player1");
+
+        let first = log.record("admin@dummy.com", &action);
+        let second = log.record(
+            "admin@dummy.com",
+            &AdminAction::Lock {
+                email: "player@dummy.com".to_string(),
+            },
+        );
+        log.complete(second, Err("Forbidden (403)".to_string()));
+
+        let entries: Vec<_> = log.entries().collect();
+        assert_eq!(entries[0].id, first);
+        assert_eq!(
+            entries[0].action,
+            "Disqualify the rust score by Player1 from 23_3_1"
+        );
+        assert_eq!(entries[0].outcome, None);
+        assert_eq!(entries[1].outcome, Some(Err("Forbidden (403)".to_string())));
+
+        let text = serde_json::to_string(&log).unwrap();
+        let mut restored: AuditLog = serde_json::from_str(&text).unwrap();
+        restored.close_unanswered();
+        let entries: Vec<_> = restored.entries().collect();
+        assert_eq!(
+            entries[0].outcome,
+            Some(Err("No answer before the app was closed".to_string()))
+        );
+        // numbering carries on
+        assert_eq!(restored.record("admin@dummy.com", &action), second + 1);
+    }
+}
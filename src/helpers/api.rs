@@ -0,0 +1,411 @@
+use crate::helpers::{
+    admin::{AdminAction, AdminUser},
+    challenges::{AuthoredChallenge, Challenge, ChallengeDraft},
+    downloads::{percent_encode, ChallengeFile},
+    fetchers::{RequestStatus, Requestor},
+    session::Identity,
+    submission::{Submission, SubmissionResult},
+    submission_history::SubmissionRecord,
+    transport::Form,
+    user_profile::UserProfile,
+    AppState,
+};
+use chrono::{DateTime, Utc};
+use scoreboard_db::Score;
+use std::fmt::{self, Display, Formatter};
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ApiError {
+    /// The backend could not be reached
+    Network(String),
+    /// The backend answered with an error status
+    Http { status: u16, message: String },
+    /// Not logged in, or the session could not be refreshed
+    Auth(String),
+    /// The response was not what we expected
+    Decode(String),
+    /// Cancelled, or replaced by a newer request
+    Cancelled,
+}
+
+impl ApiError {
+    /// The backend reports errors as `{"status": "fail", "message": "..."}`,
+    /// fall back to the raw text for anything else.
+    pub fn from_response(status: u16, text: &str) -> Self {
+        let message = serde_json::from_str::<StatusResponse>(text)
+            .map(|response| response.message)
+            .unwrap_or(text.to_string());
+        ApiError::Http { status, message }
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Network(e) => write!(f, "Network error: {}", e),
+            ApiError::Http { status, message } => write!(f, "{} ({})", message, status),
+            ApiError::Auth(e) => write!(f, "{}", e),
+            ApiError::Decode(e) => write!(f, "Unexpected response: {}", e),
+            ApiError::Cancelled => write!(f, "Request cancelled"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct StatusResponse {
+    pub status: String,
+    pub message: String,
+}
+
+impl StatusResponse {
+    pub fn is_success(&self) -> bool {
+        self.status.to_lowercase() == "success"
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum LoginResponse {
+    Success {
+        status: String,
+        access_token: String,
+        /// Given by newer backends, otherwise they come with [`me`]
+        #[serde(default)]
+        roles: Vec<String>,
+    },
+    Failure {
+        status: String,
+        message: String,
+    },
+}
+
+/// `{"status": "success", "data": {"user": {...}}}`, older backends give a single `role`.
+#[derive(serde::Deserialize)]
+struct UserResponse {
+    data: UserData,
+}
+
+#[derive(serde::Deserialize)]
+struct UserData {
+    user: User,
+}
+
+#[derive(serde::Deserialize)]
+struct User {
+    email: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    role: Option<String>,
+    #[serde(default)]
+    roles: Vec<String>,
+}
+
+/// A request whose response is decoded into `T` once it arrives.
+pub struct ApiRequest<T> {
+    requestor: Requestor,
+    decode: fn(&[u8]) -> Result<T, ApiError>,
+}
+
+impl<T> ApiRequest<T> {
+    fn send(mut requestor: Requestor, decode: fn(&[u8]) -> Result<T, ApiError>) -> Self {
+        requestor.send();
+        Self { requestor, decode }
+    }
+
+    /// `None` while the request is still in flight.
+    pub fn check(&mut self) -> Option<Result<T, ApiError>> {
+        match self.requestor.check_promise() {
+            RequestStatus::NotStarted | RequestStatus::InProgress => None,
+            RequestStatus::Success(body) => Some((self.decode)(&body)),
+            RequestStatus::Failed(e) => Some(Err(e)),
+        }
+    }
+
+    pub fn refresh_context(&mut self) -> bool {
+        self.requestor.refresh_context()
+    }
+
+    /// Only the newest request made with `key` will report a response,
+    /// older ones in flight report [`ApiError::Cancelled`].
+    pub fn latest_wins(mut self, key: &str) -> Self {
+        self.requestor.latest_wins(key);
+        self
+    }
+
+    pub fn cancel(&mut self) {
+        self.requestor.cancel();
+    }
+
+    /// Set while waiting to try again after a transient failure.
+    pub fn next_retry(&self) -> Option<DateTime<Utc>> {
+        self.requestor.next_retry()
+    }
+}
+
+fn json<T: serde::de::DeserializeOwned>(body: &[u8]) -> Result<T, ApiError> {
+    serde_json::from_slice(body).map_err(|e| ApiError::Decode(e.to_string()))
+}
+
+fn status(body: &[u8]) -> Result<StatusResponse, ApiError> {
+    let response: StatusResponse = json(body)?;
+    match response.is_success() {
+        true => Ok(response),
+        false => Err(ApiError::Http {
+            status: 200,
+            message: response.message,
+        }),
+    }
+}
+
+fn identity(body: &[u8]) -> Result<Identity, ApiError> {
+    let user = json::<UserResponse>(body)?.data.user;
+    let mut roles = user.roles;
+    if let Some(role) = user.role {
+        if !roles.contains(&role) {
+            roles.push(role);
+        }
+    }
+    Ok(Identity {
+        email: user.email,
+        name: user.name,
+        roles,
+    })
+}
+
+fn ignore(_body: &[u8]) -> Result<(), ApiError> {
+    Ok(())
+}
+
+fn bytes(body: &[u8]) -> Result<Vec<u8>, ApiError> {
+    Ok(body.to_vec())
+}
+
+fn endpoint(app_state: &Arc<Mutex<AppState>>, path: &str) -> String {
+    app_state.lock().unwrap().endpoint(path)
+}
+
+fn to_json<T: serde::Serialize>(data: &T) -> Option<String> {
+    Some(serde_json::to_string(data).expect("API payloads always serialise"))
+}
+
+pub fn challenges(app_state: &Arc<Mutex<AppState>>) -> ApiRequest<Vec<Challenge>> {
+    let url = endpoint(app_state, "api/game/challenge");
+    ApiRequest::send(Requestor::new_get(app_state.clone(), &url, true), json)
+}
+
+/// The raw bytes of `file`, check them with [`ChallengeFile::verify`].
+pub fn download(
+    app_state: &Arc<Mutex<AppState>>,
+    command: &str,
+    file: &ChallengeFile,
+) -> ApiRequest<Vec<u8>> {
+    let url = endpoint(app_state, &file.path(command));
+    ApiRequest::send(Requestor::new_get(app_state.clone(), &url, true), bytes)
+}
+
+pub fn scores(app_state: &Arc<Mutex<AppState>>, table: &str) -> ApiRequest<Vec<Score>> {
+    let url = endpoint(app_state, &format!("api/game/scores/{}", table));
+    ApiRequest::send(Requestor::new_get(app_state.clone(), &url, true), json)
+}
+
+/// Everything the logged in player has submitted.
+pub fn submissions(app_state: &Arc<Mutex<AppState>>) -> ApiRequest<Vec<SubmissionRecord>> {
+    let url = endpoint(app_state, "api/game/submissions");
+    ApiRequest::send(Requestor::new_get(app_state.clone(), &url, true), json)
+}
+
+pub fn login(
+    app_state: &Arc<Mutex<AppState>>,
+    email: &str,
+    password: &str,
+) -> ApiRequest<LoginResponse> {
+    let url = endpoint(app_state, "api/auth/login");
+    let data = to_json(&serde_json::json!({ "email": email, "password": password }));
+    ApiRequest::send(
+        Requestor::new_post(app_state.clone(), &url, true, data).without_auth_retry(),
+        json,
+    )
+}
+
+pub fn logout(app_state: &Arc<Mutex<AppState>>) -> ApiRequest<()> {
+    let url = endpoint(app_state, "api/auth/logout");
+    ApiRequest::send(
+        Requestor::new_post(app_state.clone(), &url, true, None),
+        ignore,
+    )
+}
+
+/// Checks whether the refresh cookie still gives us a session.
+pub fn refresh(app_state: &Arc<Mutex<AppState>>) -> ApiRequest<StatusResponse> {
+    let url = endpoint(app_state, "api/auth/refresh");
+    ApiRequest::send(
+        Requestor::new_get(app_state.clone(), &url, true).without_auth_retry(),
+        status,
+    )
+}
+
+/// Who the session belongs to.
+pub fn me(app_state: &Arc<Mutex<AppState>>) -> ApiRequest<Identity> {
+    let url = endpoint(app_state, "api/users/me");
+    ApiRequest::send(Requestor::new_get(app_state.clone(), &url, true), identity)
+}
+
+pub fn profile(app_state: &Arc<Mutex<AppState>>) -> ApiRequest<UserProfile> {
+    let url = endpoint(app_state, "api/users/me/profile");
+    ApiRequest::send(Requestor::new_get(app_state.clone(), &url, true), json)
+}
+
+/// Saves everything but the avatar, see [`upload_avatar`].
+pub fn update_profile(
+    app_state: &Arc<Mutex<AppState>>,
+    profile: &UserProfile,
+) -> ApiRequest<StatusResponse> {
+    let url = endpoint(app_state, "api/users/me/profile");
+    ApiRequest::send(
+        Requestor::new_post(app_state.clone(), &url, true, to_json(profile)),
+        status,
+    )
+}
+
+pub fn upload_avatar(
+    app_state: &Arc<Mutex<AppState>>,
+    filename: &str,
+    bytes: &[u8],
+) -> ApiRequest<StatusResponse> {
+    let url = endpoint(app_state, "api/users/me/avatar");
+    let mut form = Form::new();
+    form.file("avatar", filename, bytes);
+    ApiRequest::send(
+        Requestor::new_form_post(app_state.clone(), &url, true, Some(form)),
+        status,
+    )
+}
+
+/// Confirms the email address a verification link was sent to.
+pub fn verify_email(app_state: &Arc<Mutex<AppState>>, token: &str) -> ApiRequest<StatusResponse> {
+    let url = endpoint(
+        app_state,
+        &format!("api/auth/verifyemail/{}", percent_encode(token)),
+    );
+    ApiRequest::send(Requestor::new_get(app_state.clone(), &url, false), status)
+}
+
+pub fn register(
+    app_state: &Arc<Mutex<AppState>>,
+    name: &str,
+    email: &str,
+    password: &str,
+) -> ApiRequest<StatusResponse> {
+    let url = endpoint(app_state, "api/auth/register");
+    let data = to_json(&serde_json::json!({
+        "name": name,
+        "email": email,
+        "password": password,
+    }));
+    ApiRequest::send(
+        Requestor::new_post(app_state.clone(), &url, false, data),
+        status,
+    )
+}
+
+pub fn forgot_password(
+    app_state: &Arc<Mutex<AppState>>,
+    email: &str,
+) -> ApiRequest<StatusResponse> {
+    let url = endpoint(app_state, "api/auth/forgotpassword");
+    let data = to_json(&serde_json::json!({ "email": email }));
+    ApiRequest::send(
+        Requestor::new_post(app_state.clone(), &url, false, data),
+        status,
+    )
+}
+
+pub fn reset_password(
+    app_state: &Arc<Mutex<AppState>>,
+    token: &str,
+    password: &str,
+) -> ApiRequest<()> {
+    let url = endpoint(
+        app_state,
+        &format!("api/auth/resetpassword/{}", percent_encode(token)),
+    );
+    let data = to_json(&serde_json::json!({ "password": password }));
+    ApiRequest::send(
+        Requestor::new_post(app_state.clone(), &url, true, data),
+        ignore,
+    )
+}
+
+/// Changes the password of a logged in user, `logout_other_sessions` is
+/// ignored by backends that can't end the other sessions.
+pub fn change_password(
+    app_state: &Arc<Mutex<AppState>>,
+    email: &str,
+    old_password: &str,
+    new_password: &str,
+    logout_other_sessions: bool,
+) -> ApiRequest<StatusResponse> {
+    let url = endpoint(app_state, "api/auth/changepassword");
+    let data = to_json(&serde_json::json!({
+        "email": email,
+        "old_password": old_password,
+        "new_password": new_password,
+        "logout_other_sessions": logout_other_sessions,
+    }));
+    ApiRequest::send(
+        Requestor::new_post(app_state.clone(), &url, true, data),
+        status,
+    )
+}
+
+pub fn admin_users(app_state: &Arc<Mutex<AppState>>) -> ApiRequest<Vec<AdminUser>> {
+    let url = endpoint(app_state, "api/admin/users");
+    ApiRequest::send(Requestor::new_get(app_state.clone(), &url, true), json)
+}
+
+pub fn admin(app_state: &Arc<Mutex<AppState>>, action: &AdminAction) -> ApiRequest<StatusResponse> {
+    let url = endpoint(app_state, action.path());
+    let data = to_json(&action.payload());
+    ApiRequest::send(
+        Requestor::new_post(app_state.clone(), &url, true, data),
+        status,
+    )
+}
+
+/// Creates or updates a challenge, attachments included.
+/// Every challenge, drafts included, for the authoring window.
+pub fn admin_challenges(app_state: &Arc<Mutex<AppState>>) -> ApiRequest<Vec<AuthoredChallenge>> {
+    let url = endpoint(app_state, "api/admin/challenges");
+    ApiRequest::send(Requestor::new_get(app_state.clone(), &url, true), json)
+}
+
+pub fn save_challenge(
+    app_state: &Arc<Mutex<AppState>>,
+    draft: &ChallengeDraft,
+) -> ApiRequest<StatusResponse> {
+    let url = endpoint(app_state, "api/admin/challenges");
+    ApiRequest::send(
+        Requestor::new_form_post(app_state.clone(), &url, true, Some(draft.to_form())),
+        status,
+    )
+}
+
+/// Code is posted as JSON, binaries as a multipart form.
+pub fn submit(
+    app_state: &Arc<Mutex<AppState>>,
+    submission: &Submission,
+) -> ApiRequest<SubmissionResult> {
+    let requestor = match submission.code {
+        Some(_) => {
+            let url = endpoint(app_state, "api/game/submit");
+            Requestor::new_post(app_state.clone(), &url, true, to_json(submission))
+        }
+        None => {
+            let url = endpoint(app_state, "api/game/binary");
+            Requestor::new_form_post(app_state.clone(), &url, true, submission.to_form())
+        }
+    };
+    ApiRequest::send(requestor, json)
+}
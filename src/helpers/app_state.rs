@@ -1,20 +1,88 @@
-use crate::helpers::ChallengeCollection;
+use crate::helpers::{
+    admin::AuditLog,
+    changes::ChangeLog,
+    deep_link::DeepLink,
+    network_log::NetworkLog,
+    offline_cache::{self, CacheSnapshot, CacheStore, MemoryCache},
+    session::{self, Identity, MemoryStore, SessionStore},
+    submission_history::SubmissionHistory,
+    submission_queue::SubmissionQueue,
+    transport::{self, Transport},
+    user_profile::UserProfile,
+    ChallengeCollection, Config,
+};
+use chrono::{DateTime, Utc};
+use scoreboard_db::Score;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, Mutex};
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub enum LoginState {
     LoggedIn,
+    #[default]
     LoggedOut,
 }
 
+/// What is kept for a profile while another one is active.
+#[derive(Default)]
+struct Session {
+    logged_in: LoginState,
+    identity: Option<Identity>,
+    user_profile: Option<UserProfile>,
+    challenges: ChallengeCollection,
+    scoreboards: BTreeMap<String, Vec<Score>>,
+    scoreboard_times: BTreeMap<String, DateTime<Utc>>,
+    submission_queue: SubmissionQueue,
+    submission_history: SubmissionHistory,
+}
+
 pub struct AppState {
     pub counter: usize,
     pub logged_in: LoginState,
+    /// Who is, or was last, logged in
+    pub identity: Option<Identity>,
+    /// Fetched by the profile window
+    pub user_profile: Option<UserProfile>,
     pub challenges: ChallengeCollection,
+    /// Challenges added, edited or closed since the app started
+    pub challenge_changes: ChangeLog,
+    /// Last scores fetched for each table
+    pub scoreboards: BTreeMap<String, Vec<Score>>,
+    /// When each of the scoreboards was fetched
+    pub scoreboard_times: BTreeMap<String, DateTime<Utc>>,
+    /// Since when the backend couldn't be reached, `None` while it can
+    pub offline_since: Option<DateTime<Utc>>,
+    pub cache_store: Arc<dyn CacheStore>,
+    /// The challenges, scoreboards or history changed since they were last written out
+    pub cache_dirty: bool,
+    pub queue_store: Arc<dyn CacheStore>,
+    /// The queue changed since it was last written out
+    pub queue_dirty: bool,
+    pub audit_store: Arc<dyn CacheStore>,
+    /// The audit log changed since it was last written out
+    pub audit_dirty: bool,
+    /// Taken from profiles switched away from, waiting to be written out
+    unsaved: Vec<CacheSnapshot>,
+    /// Submissions waiting for the backend
+    pub submission_queue: SubmissionQueue,
+    /// What was submitted and how it went
+    pub submission_history: SubmissionHistory,
+    sessions: HashMap<String, Session>,
+    /// Newest request sent for each "latest wins" key
+    latest_requests: HashMap<String, u64>,
     pub last_refresh: chrono::NaiveTime,
     pub last_activity: chrono::NaiveTime,
     pub activity_timeout: chrono::Duration,
     pub refresh_period: chrono::Duration,
+    pub transport: Arc<dyn Transport>,
+    pub config: Config,
+    /// Recent requests for the network inspector
+    pub network_log: NetworkLog,
+    pub session_store: Arc<dyn SessionStore>,
+    /// Followed link waiting for the window that handles it
+    pub deep_link: Option<DeepLink>,
+    /// Actions taken from the admin console
+    pub audit_log: AuditLog,
 }
 
 impl Default for AppState {
@@ -22,11 +90,34 @@ impl Default for AppState {
         Self {
             counter: 1,
             logged_in: LoginState::LoggedOut,
+            identity: None,
+            user_profile: None,
             challenges: ChallengeCollection::default(),
+            challenge_changes: ChangeLog::default(),
+            scoreboards: BTreeMap::new(),
+            scoreboard_times: BTreeMap::new(),
+            offline_since: None,
+            cache_store: Arc::new(MemoryCache::default()),
+            cache_dirty: false,
+            queue_store: Arc::new(MemoryCache::default()),
+            queue_dirty: false,
+            audit_store: Arc::new(MemoryCache::default()),
+            audit_dirty: false,
+            unsaved: Vec::new(),
+            submission_queue: SubmissionQueue::default(),
+            submission_history: SubmissionHistory::default(),
+            sessions: HashMap::new(),
+            latest_requests: HashMap::new(),
             last_refresh: chrono::Utc::now().time(),
             last_activity: chrono::Utc::now().time(),
             activity_timeout: chrono::Duration::minutes(10),
             refresh_period: chrono::Duration::minutes(5),
+            transport: transport::default_transport(),
+            config: Config::default(),
+            network_log: NetworkLog::default(),
+            session_store: Arc::new(MemoryStore::default()),
+            deep_link: None,
+            audit_log: AuditLog::default(),
         }
     }
 }
@@ -38,9 +129,158 @@ impl AppState {
         app.logged_in = LoginState::LoggedIn;
     }
     pub fn set_logged_out(app_state: &Arc<Mutex<AppState>>) {
-        let app = Arc::clone(app_state);
-        let mut app = app.lock().unwrap();
-        app.logged_in = LoginState::LoggedOut;
+        {
+            let mut app = app_state.lock().unwrap();
+            app.logged_in = LoginState::LoggedOut;
+            app.identity = None;
+            app.user_profile = None;
+            // whoever logs in next doesn't get to see it
+            app.submission_history = SubmissionHistory::default();
+            app.cache_dirty = true;
+        }
+        session::save(app_state);
+    }
+
+    /// Points the app at a different backend, the session and challenges
+    /// from the previous one are dropped.
+    pub fn set_backend_url(&mut self, url: &str) -> Result<(), String> {
+        self.stash_unsaved();
+        self.config.set_backend_url(url)?;
+        self.logged_in = LoginState::LoggedOut;
+        self.identity = None;
+        self.user_profile = None;
+        self.challenges = ChallengeCollection::default();
+        self.challenge_changes.clear();
+        self.scoreboards.clear();
+        self.scoreboard_times.clear();
+        self.submission_queue = SubmissionQueue::default();
+        self.submission_history = SubmissionHistory::default();
+        self.offline_since = None;
+        offline_cache::restore(self);
+        Ok(())
+    }
+
+    /// Stashes the active profile's session and restores the one
+    /// last used with `name`, if there was one.
+    pub fn switch_profile(&mut self, name: &str) -> Result<(), String> {
+        let previous = self.config.active_profile().to_string();
+        if previous == name {
+            return Ok(());
+        }
+        self.stash_unsaved();
+        self.config.select_profile(name)?;
+        log::info!("Switching profile from {} to {}", previous, name);
+
+        let next = self.sessions.remove(name).unwrap_or_default();
+        let session = Session {
+            logged_in: std::mem::replace(&mut self.logged_in, next.logged_in),
+            identity: std::mem::replace(&mut self.identity, next.identity),
+            user_profile: std::mem::replace(&mut self.user_profile, next.user_profile),
+            challenges: std::mem::replace(&mut self.challenges, next.challenges),
+            scoreboards: std::mem::replace(&mut self.scoreboards, next.scoreboards),
+            scoreboard_times: std::mem::replace(&mut self.scoreboard_times, next.scoreboard_times),
+            submission_queue: std::mem::replace(&mut self.submission_queue, next.submission_queue),
+            submission_history: std::mem::replace(
+                &mut self.submission_history,
+                next.submission_history,
+            ),
+        };
+        self.sessions.insert(previous, session);
+        self.challenge_changes.clear();
+        self.offline_since = None;
+        offline_cache::restore(self);
+        Ok(())
+    }
+
+    pub fn remove_profile(&mut self, name: &str) -> Result<(), String> {
+        self.config.remove_profile(name)?;
+        self.sessions.remove(name);
+        Ok(())
+    }
+
+    /// Keeps freshly fetched scores for `table`, in the offline cache too.
+    pub fn store_scores(&mut self, table: &str, scores: Vec<Score>) {
+        self.scoreboards.insert(table.to_string(), scores);
+        self.scoreboard_times.insert(table.to_string(), Utc::now());
+        self.cache_dirty = true;
+    }
+
+    /* The session key is about to change, so what is dirty
+    has to be taken while it still belongs to the active backend */
+    fn stash_unsaved(&mut self) {
+        if let Some(snapshot) = CacheSnapshot::take(self) {
+            self.unsaved.push(snapshot);
+        }
+    }
+
+    pub fn has_unsaved(&self) -> bool {
+        self.cache_dirty || self.queue_dirty || self.audit_dirty || !self.unsaved.is_empty()
+    }
+
+    /// Everything waiting to be written out, see [`offline_cache::flush`].
+    pub fn take_unsaved(&mut self) -> Vec<CacheSnapshot> {
+        self.stash_unsaved();
+        std::mem::take(&mut self.unsaved)
+    }
+
+    /// Notes whether the last request got any answer from the backend.
+    pub fn set_reachable(&mut self, reachable: bool) {
+        match (reachable, self.offline_since) {
+            (true, Some(_)) => {
+                log::info!("The backend is reachable again");
+                self.offline_since = None;
+            }
+            (false, None) => {
+                log::warn!("The backend can't be reached, showing what was cached");
+                self.offline_since = Some(Utc::now());
+            }
+            _ => {}
+        }
+    }
+
+    /// Marks a new request for `key`, any older one is now stale.
+    pub fn supersede(&mut self, key: &str) -> u64 {
+        let generation = self.latest_requests.entry(key.to_string()).or_default();
+        *generation += 1;
+        *generation
+    }
+
+    pub fn is_latest(&self, key: &str, generation: u64) -> bool {
+        self.latest_requests.get(key) == Some(&generation)
+    }
+
+    /// Hands over the pending link if `handles` wants it.
+    pub fn take_deep_link(&mut self, handles: impl Fn(&DeepLink) -> bool) -> Option<DeepLink> {
+        match &self.deep_link {
+            Some(link) if handles(link) => self.deep_link.take(),
+            _ => None,
+        }
+    }
+
+    /// Only while logged in, a remembered identity proves nothing.
+    pub fn has_role(&self, role: &str) -> bool {
+        matches!(self.logged_in, LoginState::LoggedIn)
+            && self
+                .identity
+                .as_ref()
+                .is_some_and(|identity| identity.has_role(role))
+    }
+
+    /// Whether the player has a score on `table`, `None` until its scores are fetched.
+    pub fn solved(&self, table: &str) -> Option<bool> {
+        if !matches!(self.logged_in, LoginState::LoggedIn) {
+            return None;
+        }
+        let identity = self.identity.as_ref()?;
+        let profile_name = self.user_profile.as_ref().map(|p| p.name.as_str());
+        let scores = self.scoreboards.get(table)?;
+        Some(scores.iter().any(|score| {
+            score.name == identity.display_name() || Some(score.name.as_str()) == profile_name
+        }))
+    }
+
+    pub fn endpoint(&self, path: &str) -> String {
+        self.config.endpoint(path)
     }
 
     /* Keep refreshing every refresh period
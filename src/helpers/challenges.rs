@@ -1,18 +1,405 @@
-use crate::helpers::{fetchers::Requestor, AppState};
-use std::sync::{Arc, Mutex};
+use super::downloads::{sha1_hex, ChallengeFile};
+use super::transport::Form;
+use super::Languages;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeSet;
+use std::fmt::{self, Display, Formatter};
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "Easy"),
+            Difficulty::Medium => write!(f, "Medium"),
+            Difficulty::Hard => write!(f, "Hard"),
+            Difficulty::Expert => write!(f, "Expert"),
+        }
+    }
+}
+
+/// How the judge hands the samples to a player's program.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum IoMode {
+    /// All the samples in one file, its name the only argument
+    #[default]
+    FileArgument,
+    /// One sample per line on stdin, answered line by line until told to quit
+    StdinLoop,
+}
+
+impl Display for IoMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            IoMode::FileArgument => write!(f, "File argument"),
+            IoMode::StdinLoop => write!(f, "Stdin loop"),
+        }
+    }
+}
+
+/// Whether scores are taken, going by the challenge's dates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Availability {
+    Upcoming,
+    Open,
+    Closed,
+}
+
+impl Display for Availability {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Availability::Upcoming => write!(f, "Upcoming"),
+            Availability::Open => write!(f, "Open"),
+            Availability::Closed => write!(f, "Closed"),
+        }
+    }
+}
+
+/* Everything here is optional, older backends
+only send the name, command, table and doc */
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ChallengeMeta {
+    pub difficulty: Option<Difficulty>,
+    pub tags: Vec<String>,
+    pub author: Option<String>,
+    pub opens: Option<DateTime<Utc>>,
+    pub closes: Option<DateTime<Utc>>,
+    pub io_mode: IoMode,
+    /// How many samples a real run is judged against
+    pub samples: Option<u32>,
+    /// For the whole run, in milliseconds
+    pub time_limit_ms: Option<u64>,
+    /// Empty when any language is allowed
+    pub languages: Vec<Languages>,
+    /// Sample data and checkers to download
+    pub files: Vec<ChallengeFile>,
+}
+
+impl ChallengeMeta {
+    pub fn availability(&self, now: DateTime<Utc>) -> Availability {
+        match (self.opens, self.closes) {
+            (Some(opens), _) if now < opens => Availability::Upcoming,
+            (_, Some(closes)) if now >= closes => Availability::Closed,
+            _ => Availability::Open,
+        }
+    }
+
+    pub fn allows(&self, language: Languages) -> bool {
+        self.languages.is_empty() || self.languages.contains(&language)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Challenge {
     pub name: String,
     pub command: String,
     pub table: String,
-    doc: String,
+    pub doc: String,
+    #[serde(flatten)]
+    pub meta: ChallengeMeta,
+}
+
+impl Challenge {
+    pub fn new(name: &str, command: &str, table: &str, doc: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            command: command.to_string(),
+            table: table.to_string(),
+            doc: doc.to_string(),
+            meta: ChallengeMeta::default(),
+        }
+    }
+
+    /// Changes whenever anything about the challenge does.
+    pub fn content_hash(&self) -> String {
+        sha1_hex(&serde_json::to_vec(self).expect("challenges always serialise"))
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ChallengeSort {
+    /// As the backend lists them
+    #[default]
+    Command,
+    Name,
+    Difficulty,
+    /// Soonest to close first
+    Closing,
+}
+
+impl ChallengeSort {
+    pub fn iter() -> impl Iterator<Item = Self> {
+        use ChallengeSort::*;
+        [Command, Name, Difficulty, Closing].iter().copied()
+    }
+}
+
+impl Display for ChallengeSort {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ChallengeSort::Command => write!(f, "Command"),
+            ChallengeSort::Name => write!(f, "Name"),
+            ChallengeSort::Difficulty => write!(f, "Difficulty"),
+            ChallengeSort::Closing => write!(f, "Closing"),
+        }
+    }
+}
+
+/// What the challenge browser is narrowed down to.
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ChallengeQuery {
+    /// Matched against the name, command, tags and author
+    pub search: String,
+    pub tag: Option<String>,
+    pub availability: Option<Availability>,
+    pub unsolved_only: bool,
+    pub sort: ChallengeSort,
+}
+
+impl ChallengeQuery {
+    /// `solved` is `None` when it isn't known yet, those are kept by `unsolved_only`.
+    pub fn matches(&self, challenge: &Challenge, now: DateTime<Utc>, solved: Option<bool>) -> bool {
+        let search = self.search.trim().to_lowercase();
+        let meta = &challenge.meta;
+        let found = search.is_empty()
+            || challenge.name.to_lowercase().contains(&search)
+            || challenge.command.contains(&search)
+            || meta.tags.iter().any(|t| t.to_lowercase().contains(&search))
+            || meta
+                .author
+                .as_ref()
+                .is_some_and(|a| a.to_lowercase().contains(&search));
+        found
+            && self
+                .tag
+                .as_ref()
+                .map_or(true, |tag| meta.tags.contains(tag))
+            && self
+                .availability
+                .map_or(true, |a| meta.availability(now) == a)
+            && !(self.unsolved_only && solved == Some(true))
+    }
+
+    pub fn apply<'a>(
+        &self,
+        challenges: &'a [Challenge],
+        now: DateTime<Utc>,
+        solved: impl Fn(&Challenge) -> Option<bool>,
+    ) -> Vec<&'a Challenge> {
+        let mut found: Vec<&Challenge> = challenges
+            .iter()
+            .filter(|c| self.matches(c, now, solved(c)))
+            .collect();
+        match self.sort {
+            ChallengeSort::Command => {}
+            ChallengeSort::Name => found.sort_by_key(|c| c.name.to_lowercase()),
+            // unknown last
+            ChallengeSort::Difficulty => {
+                found.sort_by_key(|c| (c.meta.difficulty.is_none(), c.meta.difficulty))
+            }
+            ChallengeSort::Closing => {
+                found.sort_by_key(|c| (c.meta.closes.is_none(), c.meta.closes))
+            }
+        }
+        found
+    }
+}
+
+/// Sample data offered with a challenge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attachment {
+    pub filename: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Attachments are sent with every save, keep them to what a player would download.
+pub const MAX_ATTACHMENT_BYTES: usize = 10 * 1024 * 1024;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum Publication {
+    /// Only admins can see it
+    #[default]
+    Draft,
+    Published,
+}
+
+/// A challenge as admins see it, from `GET api/admin/challenges` with the drafts too.
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize)]
+pub struct AuthoredChallenge {
+    #[serde(flatten)]
+    pub challenge: Challenge,
+    #[serde(default)]
+    pub published: bool,
+}
+
+impl AuthoredChallenge {
+    pub fn publication(&self) -> Publication {
+        match self.published {
+            true => Publication::Published,
+            false => Publication::Draft,
+        }
+    }
+}
+
+/// A challenge being written or edited by an admin.
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ChallengeDraft {
+    pub name: String,
+    pub command: String,
+    pub table: String,
+    pub doc: String,
+    pub publication: Publication,
+    /// Kept as it was for the local copy, the backend keeps its own and isn't sent it
+    pub meta: ChallengeMeta,
+    /// The command of the challenge being edited, `None` for a new one
+    pub editing: Option<String>,
+    #[serde(skip)]
+    pub attachments: Vec<Attachment>,
+}
+
+impl ChallengeDraft {
+    pub fn edit(authored: &AuthoredChallenge) -> Self {
+        let challenge = &authored.challenge;
+        Self {
+            name: challenge.name.clone(),
+            command: challenge.command.clone(),
+            table: challenge.table.clone(),
+            doc: challenge.doc.clone(),
+            publication: authored.publication(),
+            meta: challenge.meta.clone(),
+            editing: Some(challenge.command.clone()),
+            attachments: Vec::new(),
+        }
+    }
+
+    /// Checks the draft against the challenges the backend already has, drafts too when known.
+    pub fn validate(&self, existing: &[Challenge]) -> Result<(), String> {
+        let identifier = |value: &str| {
+            !value.is_empty()
+                && value
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        };
+        if self.name.trim().is_empty() {
+            return Err("The challenge needs a name".to_string());
+        }
+        if !identifier(&self.command) {
+            return Err("Commands can only have letters, numbers, - and _".to_string());
+        }
+        if !identifier(&self.table) {
+            return Err("Tables can only have letters, numbers, - and _".to_string());
+        }
+        if self.doc.trim().is_empty() {
+            return Err("The challenge needs instructions".to_string());
+        }
+
+        let others = existing
+            .iter()
+            .filter(|c| Some(&c.command) != self.editing.as_ref());
+        for other in others {
+            if other.command == self.command {
+                return Err(format!(
+                    "{} already uses command {}",
+                    other.name, self.command
+                ));
+            }
+            if other.table == self.table {
+                return Err(format!("{} already uses table {}", other.name, self.table));
+            }
+        }
+
+        let size: usize = self.attachments.iter().map(|a| a.bytes.len()).sum();
+        if size > MAX_ATTACHMENT_BYTES {
+            return Err(format!(
+                "Attachments can be at most {} MiB together",
+                MAX_ATTACHMENT_BYTES / 1024 / 1024
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn to_form(&self) -> Form {
+        let mut form = Form::new();
+        form.text("name", self.name.trim());
+        form.text("command", &self.command);
+        form.text("table", &self.table);
+        form.text("doc", &self.doc);
+        form.text(
+            "published",
+            &(self.publication == Publication::Published).to_string(),
+        );
+        if let Some(editing) = &self.editing {
+            form.text("editing", editing);
+        }
+        for attachment in &self.attachments {
+            form.file("attachment", &attachment.filename, &attachment.bytes);
+        }
+        form
+    }
+
+    pub fn to_challenge(&self) -> Challenge {
+        Challenge {
+            meta: self.meta.clone(),
+            ..Challenge::new(self.name.trim(), &self.command, &self.table, &self.doc)
+        }
+    }
+}
+
+/// How a challenge differs from when it was last fetched.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChallengeChange {
+    Added(Challenge),
+    Edited {
+        /// Boxed to keep the other changes small
+        before: Box<Challenge>,
+        after: Challenge,
+    },
+    /// Stopped taking scores, or was taken off the list
+    Closed(Challenge),
+}
+
+impl ChallengeChange {
+    pub fn challenge(&self) -> &Challenge {
+        match self {
+            ChallengeChange::Added(challenge)
+            | ChallengeChange::Edited {
+                after: challenge, ..
+            }
+            | ChallengeChange::Closed(challenge) => challenge,
+        }
+    }
+}
+
+impl Display for ChallengeChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ChallengeChange::Added(c) => write!(f, "New challenge: {}", c.name),
+            ChallengeChange::Edited { after, .. } => write!(f, "{} was changed", after.name),
+            ChallengeChange::Closed(c) => write!(f, "{} has closed", c.name),
+        }
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ChallengeCollection {
     pub items: Vec<Challenge>,
-    url: String,
+    /// When `items` came from the backend
+    #[serde(default)]
+    pub fetched_at: Option<DateTime<Utc>>,
+    /// Restored from the offline cache and not fetched again since
+    #[serde(skip)]
+    pub from_cache: bool,
 }
 
 impl Default for ChallengeCollection {
@@ -23,35 +410,92 @@ impl Default for ChallengeCollection {
 
 impl ChallengeCollection {
     pub fn new() -> Self {
-        let url = option_env!("BACKEND_URL")
-            .unwrap_or("http://123.4.5.6:3000/")
-            .to_string();
-
         Self {
             items: Vec::new(),
-            url,
+            fetched_at: None,
+            from_cache: false,
         }
     }
 
-    #[allow(dead_code)] //inhibit warnings when target =/= WASM
-    pub fn fetch(&mut self, app_state: Arc<Mutex<AppState>>) -> Option<Requestor> {
-        let url = format!("{}api/game/challenge", self.url);
-
-        log::debug!("Fetching challenge info");
-        let app_state = Arc::clone(&app_state.clone());
-        let mut getter = Requestor::new_get(app_state, &url, true);
-        getter.send();
-        Some(getter)
-    }
-
     #[allow(dead_code)] //inhibit warnings when target =/= WASM
     pub fn from_json(json: &str) -> Self {
         let items: Vec<Challenge> = serde_json::from_str(json).unwrap_or_default();
+        Self::from_items(items)
+    }
+
+    pub fn from_items(items: Vec<Challenge>) -> Self {
         log::debug!("Found {} challenges", items.len());
         Self {
             items,
-            ..Default::default()
+            fetched_at: None,
+            from_cache: false,
+        }
+    }
+
+    /* Replaces the challenges with a fresh fetch. Nothing is
+    reported when there were none before, everything would be new,
+    nor against a cached list, that changed before this run */
+    pub fn update(&mut self, items: Vec<Challenge>, now: DateTime<Utc>) -> Vec<ChallengeChange> {
+        let then = self.fetched_at.unwrap_or(now);
+        let was_open = |c: &Challenge| c.meta.availability(then) != Availability::Closed;
+        let mut changes = Vec::new();
+        if !self.items.is_empty() && !self.from_cache {
+            for after in &items {
+                let before = self.items.iter().find(|c| c.command == after.command);
+                match before {
+                    None => changes.push(ChallengeChange::Added(after.clone())),
+                    Some(before)
+                        if was_open(before)
+                            && after.meta.availability(now) == Availability::Closed =>
+                    {
+                        changes.push(ChallengeChange::Closed(after.clone()))
+                    }
+                    Some(before) if before.content_hash() != after.content_hash() => {
+                        changes.push(ChallengeChange::Edited {
+                            before: Box::new(before.clone()),
+                            after: after.clone(),
+                        })
+                    }
+                    Some(_) => {}
+                }
+            }
+            for before in &self.items {
+                if was_open(before) && !items.iter().any(|c| c.command == before.command) {
+                    changes.push(ChallengeChange::Closed(before.clone()));
+                }
+            }
         }
+        log::debug!(
+            "Found {} challenges, {} changed",
+            items.len(),
+            changes.len()
+        );
+        self.items = items;
+        self.fetched_at = Some(now);
+        self.from_cache = false;
+        changes
+    }
+
+    /// Adds `challenge`, or replaces the one that had the command `previous`.
+    pub fn upsert(&mut self, previous: Option<&str>, challenge: Challenge) {
+        let previous = previous.unwrap_or(&challenge.command).to_string();
+        match self.items.iter_mut().find(|c| c.command == previous) {
+            Some(existing) => *existing = challenge,
+            None => self.items.push(challenge),
+        }
+    }
+
+    /// Drops the challenge with `command`, e.g. when it goes back to being a draft.
+    pub fn remove(&mut self, command: &str) {
+        self.items.retain(|c| c.command != command);
+    }
+
+    /// Every tag used, for picking one to filter by.
+    pub fn tags(&self) -> BTreeSet<String> {
+        self.items
+            .iter()
+            .flat_map(|c| c.meta.tags.iter().cloned())
+            .collect()
     }
 
     pub fn get_instructions(&self, challenge: String) -> Option<String> {
@@ -111,4 +555,196 @@ mod tests {
 
         assert_eq!(challenges.items[0].name, "Find the odd one out");
     }
+
+    #[test]
+    fn test_metadata() {
+        let challenges = ChallengeCollection::from_json(
+            r##"[
+                { "name": "Old", "command": "2331", "table": "23_3_1", "doc": "# Old" },
+                {
+                    "name": "New", "command": "2411", "table": "24_1_1", "doc": "# New",
+                    "difficulty": "Hard", "tags": ["arrays"], "io_mode": "StdinLoop",
+                    "opens": "2024-01-01T00:00:00Z", "closes": "2024-02-01T00:00:00Z",
+                    "languages": ["Rust", "C"], "samples": 10000
+                }
+            ]"##,
+        );
+        let old = &challenges.items[0].meta;
+        assert_eq!(old, &ChallengeMeta::default());
+        assert!(old.allows(Languages::Python));
+
+        let new = &challenges.items[1].meta;
+        assert_eq!(new.difficulty, Some(Difficulty::Hard));
+        assert_eq!(new.io_mode, IoMode::StdinLoop);
+        assert!(!new.allows(Languages::Python));
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(
+            new.availability(at("2023-12-31T23:00:00Z")),
+            Availability::Upcoming
+        );
+        assert_eq!(
+            new.availability(at("2024-01-15T00:00:00Z")),
+            Availability::Open
+        );
+        assert_eq!(
+            new.availability(at("2024-02-01T00:00:00Z")),
+            Availability::Closed
+        );
+
+        let authored = AuthoredChallenge {
+            challenge: challenges.items[1].clone(),
+            published: true,
+        };
+        let edited = ChallengeDraft::edit(&authored).to_challenge();
+        assert_eq!(edited, challenges.items[1]);
+    }
+
+    #[test]
+    fn test_query() {
+        let mut challenges = vec![
+            Challenge::new("Find Odds", "2331", "23_3_1", "# Find Odds"),
+            Challenge::new("How big?", "2333", "23_3_3", "# How Big?!"),
+            Challenge::new("Bad characters", "2334", "23_3_4", "# Bad"),
+        ];
+        challenges[0].meta.tags = vec!["arrays".to_string()];
+        challenges[0].meta.difficulty = Some(Difficulty::Medium);
+        challenges[1].meta.difficulty = Some(Difficulty::Easy);
+        challenges[2].meta.closes = Some("2024-01-01T00:00:00Z".parse().unwrap());
+        let now: DateTime<Utc> = "2024-06-01T00:00:00Z".parse().unwrap();
+        let names = |found: Vec<&Challenge>| -> Vec<String> {
+            found.iter().map(|c| c.name.clone()).collect()
+        };
+
+        let mut query = ChallengeQuery {
+            search: "ARRAY".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            names(query.apply(&challenges, now, |_| None)),
+            ["Find Odds"]
+        );
+
+        query.search = String::new();
+        query.sort = ChallengeSort::Difficulty;
+        assert_eq!(
+            names(query.apply(&challenges, now, |_| None)),
+            ["How big?", "Find Odds", "Bad characters"]
+        );
+
+        query.availability = Some(Availability::Open);
+        query.unsolved_only = true;
+        let solved = |c: &Challenge| Some(c.command == "2333");
+        assert_eq!(names(query.apply(&challenges, now, solved)), ["Find Odds"]);
+
+        let collection = ChallengeCollection::from_items(challenges);
+        assert_eq!(
+            collection.tags().into_iter().collect::<Vec<_>>(),
+            ["arrays"]
+        );
+    }
+
+    #[test]
+    fn test_update() {
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        let odds = Challenge::new("Find Odds", "2331", "23_3_1", "# Find Odds");
+        let big = Challenge::new("How big?", "2333", "23_3_3", "# How Big?!");
+        let mut closing = Challenge::new("Bad characters", "2334", "23_3_4", "# Bad");
+        closing.meta.closes = Some(at("2024-01-02T00:00:00Z"));
+
+        let mut challenges = ChallengeCollection::new();
+        let first = vec![odds.clone(), big.clone(), closing.clone()];
+        assert!(challenges
+            .update(first, at("2024-01-01T00:00:00Z"))
+            .is_empty());
+
+        let mut edited = big.clone();
+        edited.doc.push_str("\nNow with negative numbers");
+        assert_ne!(edited.content_hash(), big.content_hash());
+        let added = Challenge::new("Sorting", "2411", "24_1_1", "# Sort");
+        let changes = challenges.update(
+            vec![edited.clone(), closing.clone(), added.clone()],
+            at("2024-01-03T00:00:00Z"),
+        );
+        assert_eq!(
+            changes,
+            vec![
+                ChallengeChange::Edited {
+                    before: Box::new(big),
+                    after: edited.clone()
+                },
+                ChallengeChange::Closed(closing.clone()),
+                ChallengeChange::Added(added.clone()),
+                ChallengeChange::Closed(odds),
+            ]
+        );
+
+        // already closed, so not again
+        let changes = challenges.update(vec![edited.clone(), added], at("2024-01-04T00:00:00Z"));
+        assert!(changes.is_empty());
+
+        // what changed while the app was closed isn't news
+        challenges.from_cache = true;
+        assert!(challenges
+            .update(vec![edited.clone()], at("2024-01-05T00:00:00Z"))
+            .is_empty());
+        assert_eq!(
+            challenges.update(Vec::new(), at("2024-01-06T00:00:00Z")),
+            vec![ChallengeChange::Closed(edited)]
+        );
+    }
+
+    #[test]
+    fn test_draft() {
+        let mut challenges = ChallengeCollection::from_items(vec![
+            Challenge::new("Find Odds", "2331", "23_3_1", "# Find Odds"),
+            Challenge::new("How big?", "2333", "23_3_3", "# How Big?!"),
+        ]);
+
+        let mut draft = ChallengeDraft {
+            name: "Bad characters".to_string(),
+            command: "2334".to_string(),
+            table: "23_3_1".to_string(),
+            doc: "# Find the bad character".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            draft.validate(&challenges.items),
+            Err("Find Odds already uses table 23_3_1".to_string())
+        );
+        draft.table = "23 3 4".to_string();
+        assert!(draft.validate(&challenges.items).is_err());
+        draft.table = "23_3_4".to_string();
+        assert!(draft.validate(&challenges.items).is_ok());
+        draft.attachments.push(Attachment {
+            filename: "sample.json".to_string(),
+            bytes: vec![0; MAX_ATTACHMENT_BYTES + 1],
+        });
+        assert!(draft.validate(&challenges.items).is_err());
+
+        // an edit may keep its own command and table
+        let mut edit = ChallengeDraft::edit(&AuthoredChallenge {
+            challenge: challenges.items[1].clone(),
+            published: true,
+        });
+        assert_eq!(edit.publication, Publication::Published);
+        assert!(edit.validate(&challenges.items).is_ok());
+        edit.command = "2331".to_string();
+        assert!(edit.validate(&challenges.items).is_err());
+        edit.command = "2335".to_string();
+        challenges.upsert(edit.editing.as_deref(), edit.to_challenge());
+        assert_eq!(challenges.items.len(), 2);
+        assert_eq!(challenges.items[1].command, "2335");
+
+        let authored: Vec<AuthoredChallenge> = serde_json::from_str(
+            r##"[{ "name": "Primes", "command": "2412", "table": "24_1_2", "doc": "# Primes", "tags": ["maths"], "published": false }]"##,
+        )
+        .unwrap();
+        assert_eq!(authored[0].publication(), Publication::Draft);
+        assert_eq!(authored[0].challenge.meta.tags, vec!["maths".to_string()]);
+        assert!(!draft
+            .to_form()
+            .fields
+            .iter()
+            .any(|(name, _)| name == "meta"));
+    }
 }
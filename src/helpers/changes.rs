@@ -0,0 +1,128 @@
+use super::ChallengeChange;
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+
+const CHANGES_CAPACITY: usize = 100;
+
+/// One change to the challenges, as kept in the [`ChangeLog`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChangeEntry {
+    pub id: u64,
+    pub time: DateTime<Utc>,
+    pub change: ChallengeChange,
+}
+
+/// What changed in the challenges while the app was open, newest last.
+#[derive(Default)]
+pub struct ChangeLog {
+    entries: VecDeque<ChangeEntry>,
+    next_id: u64,
+}
+
+impl ChangeLog {
+    pub fn record(&mut self, changes: Vec<ChallengeChange>, time: DateTime<Utc>) {
+        for change in changes {
+            log::info!("{}", change);
+            self.next_id += 1;
+            self.entries.push_back(ChangeEntry {
+                id: self.next_id,
+                time,
+                change,
+            });
+        }
+        while self.entries.len() > CHANGES_CAPACITY {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Ids carry on from before, so what was already seen stays seen.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &ChangeEntry> {
+        self.entries.iter()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DiffLine {
+    Same(String),
+    Added(String),
+    Removed(String),
+}
+
+/* The longest common subsequence of lines, instructions
+are short enough that the quadratic table doesn't matter */
+pub fn diff_lines(before: &str, after: &str) -> Vec<DiffLine> {
+    let before: Vec<&str> = before.lines().collect();
+    let after: Vec<&str> = after.lines().collect();
+    let (n, m) = (before.len(), after.len());
+
+    // common[i][j] is the LCS length of before[i..] and after[j..]
+    let mut common = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            common[i][j] = match before[i] == after[j] {
+                true => common[i + 1][j + 1] + 1,
+                false => common[i + 1][j].max(common[i][j + 1]),
+            };
+        }
+    }
+
+    let mut diff = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && before[i] == after[j] {
+            diff.push(DiffLine::Same(before[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || common[i + 1][j] >= common[i][j + 1]) {
+            diff.push(DiffLine::Removed(before[i].to_string()));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(after[j].to_string()));
+            j += 1;
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::Challenge;
+
+    #[test]
+    fn test_diff_lines() {
+        let before = "# Find Odds\n\nGiven an array\nReturn a number\nGood luck";
+        let after = "# Find Odds\n\nGiven an array of bytes\nReturn a number\nGood luck\n";
+        assert_eq!(
+            diff_lines(before, after),
+            vec![
+                DiffLine::Same("# Find Odds".to_string()),
+                DiffLine::Same("".to_string()),
+                DiffLine::Removed("Given an array".to_string()),
+                DiffLine::Added("Given an array of bytes".to_string()),
+                DiffLine::Same("Return a number".to_string()),
+                DiffLine::Same("Good luck".to_string()),
+            ]
+        );
+        assert_eq!(
+            diff_lines("", "one"),
+            vec![DiffLine::Added("one".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_change_log() {
+        let mut log = ChangeLog::default();
+        let challenge = Challenge::new("Sorting", "2411", "24_1_1", "# Sort");
+        log.record(vec![ChallengeChange::Added(challenge.clone())], Utc::now());
+        log.clear();
+        log.record(vec![ChallengeChange::Closed(challenge)], Utc::now());
+        let entries: Vec<_> = log.entries().collect();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, 2);
+    }
+}
@@ -0,0 +1,360 @@
+use crate::components::password::PasswordPolicy;
+use crate::helpers::retry::RetryPolicy;
+
+/// Storage key for the [`Config`], kept apart from the window state.
+pub const CONFIG_KEY: &str = "config";
+
+const FALLBACK_BACKEND_URL: &str = "http://localhost:3000/";
+const DEFAULT_PROFILE: &str = "Default";
+const DEFAULT_CHALLENGE_REFRESH_SECS: u64 = 300;
+
+/// A named backend, e.g. one per office or one for practice rounds.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Profile {
+    pub name: String,
+    backend_url: String,
+}
+
+impl Profile {
+    pub fn new(name: &str, backend_url: &str) -> Result<Self, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Profile name is empty".to_string());
+        }
+        Ok(Self {
+            name: name.to_string(),
+            backend_url: normalise_url(backend_url)?,
+        })
+    }
+
+    pub fn backend_url(&self) -> &str {
+        &self.backend_url
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Config {
+    profiles: Vec<Profile>,
+    active_profile: String,
+    pub retry: RetryPolicy,
+    pub password_policy: PasswordPolicy,
+    /// How often the challenges are fetched again, 0 never does
+    pub challenge_refresh_secs: u64,
+    #[serde(skip)]
+    default_backend_url: String,
+    /// What is saved as the active profile while an override picked another for this run
+    #[serde(skip)]
+    saved_active_profile: Option<String>,
+    /// Asked for by an override but no profile has it, waiting to be confirmed in the settings
+    #[serde(skip)]
+    pending_backend: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let backend_url = option_env!("BACKEND_URL")
+            .and_then(|url| normalise_url(url).ok())
+            .unwrap_or(FALLBACK_BACKEND_URL.to_string());
+        Self::with_default(&backend_url)
+    }
+}
+
+impl Config {
+    fn with_default(backend_url: &str) -> Self {
+        Self {
+            profiles: vec![Profile {
+                name: DEFAULT_PROFILE.to_string(),
+                backend_url: backend_url.to_string(),
+            }],
+            active_profile: DEFAULT_PROFILE.to_string(),
+            retry: RetryPolicy::default(),
+            password_policy: PasswordPolicy::default(),
+            challenge_refresh_secs: DEFAULT_CHALLENGE_REFRESH_SECS,
+            default_backend_url: backend_url.to_string(),
+            saved_active_profile: None,
+            pending_backend: None,
+        }
+    }
+
+    /* An explicit override (query/hash parameter on web, CLI flag or
+    environment variable on native) wins over the saved settings,
+    which win over the config file and the built in default */
+    pub fn load(cc: &eframe::CreationContext<'_>) -> Self {
+        let stored: Option<Config> = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, CONFIG_KEY));
+        let default_backend_url = Self::platform_default(cc);
+        let mut config = stored
+            .or_else(Self::from_file)
+            .filter(|config| !config.profiles.is_empty())
+            .unwrap_or_else(|| Self::with_default(&default_backend_url));
+        config.default_backend_url = default_backend_url;
+        if config.active().is_none() {
+            config.active_profile = config.profiles[0].name.clone();
+        }
+
+        if let Some(url) = Self::override_url(cc) {
+            if let Err(e) = config.override_backend_url(&url) {
+                log::error!("Ignoring backend override {}: {}", url, e);
+            }
+        }
+        log::info!(
+            "Using profile {}: {}",
+            config.active_profile,
+            config.backend_url()
+        );
+        config
+    }
+
+    fn active(&self) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.name == self.active_profile)
+    }
+
+    pub fn profiles(&self) -> &[Profile] {
+        &self.profiles
+    }
+
+    pub fn active_profile(&self) -> &str {
+        &self.active_profile
+    }
+
+    /// Identifies the active backend, it changes whenever the profile or its URL does.
+    pub fn session_key(&self) -> String {
+        format!("{}@{}", self.active_profile, self.backend_url())
+    }
+
+    pub fn backend_url(&self) -> &str {
+        match self.active() {
+            Some(profile) => &profile.backend_url,
+            None => &self.default_backend_url,
+        }
+    }
+
+    pub fn set_backend_url(&mut self, url: &str) -> Result<(), String> {
+        let url = normalise_url(url)?;
+        let active = self.active_profile.clone();
+        match self.profiles.iter_mut().find(|p| p.name == active) {
+            Some(profile) => profile.backend_url = url,
+            None => self.profiles.push(Profile::new(&active, &url)?),
+        }
+        Ok(())
+    }
+
+    pub fn select_profile(&mut self, name: &str) -> Result<(), String> {
+        if !self.profiles.iter().any(|p| p.name == name) {
+            return Err(format!("No profile called {}", name));
+        }
+        self.active_profile = name.to_string();
+        self.saved_active_profile = None;
+        Ok(())
+    }
+
+    pub fn add_profile(&mut self, profile: Profile) -> Result<(), String> {
+        if self.profiles.iter().any(|p| p.name == profile.name) {
+            return Err(format!("A profile called {} already exists", profile.name));
+        }
+        self.profiles.push(profile);
+        Ok(())
+    }
+
+    pub fn remove_profile(&mut self, name: &str) -> Result<(), String> {
+        if name == self.active_profile {
+            return Err("The active profile can't be removed".to_string());
+        }
+        self.profiles.retain(|p| p.name != name);
+        Ok(())
+    }
+
+    /* An override only lasts for this run. A URL no profile points at
+    may come from a link anyone could have sent, and the password typed next
+    would go to it, so it isn't used until confirmed in the settings */
+    pub fn override_backend_url(&mut self, url: &str) -> Result<(), String> {
+        let url = normalise_url(url)?;
+        let name = match self.profiles.iter().find(|p| p.backend_url == url) {
+            Some(profile) => profile.name.clone(),
+            None => {
+                log::warn!("{} has no profile, waiting for it to be confirmed", url);
+                self.pending_backend = Some(url);
+                return Ok(());
+            }
+        };
+        if name != self.active_profile {
+            let active = std::mem::replace(&mut self.active_profile, name);
+            self.saved_active_profile.get_or_insert(active);
+        }
+        Ok(())
+    }
+
+    pub fn pending_backend(&self) -> Option<&str> {
+        self.pending_backend.as_deref()
+    }
+
+    /// Adds a profile for the pending backend, the name of which is returned to switch to.
+    pub fn accept_pending_backend(&mut self) -> Result<String, String> {
+        let url = self
+            .pending_backend
+            .take()
+            .ok_or("No backend is waiting to be added")?;
+        let profile = Profile::new(&url, &url)?;
+        let name = profile.name.clone();
+        self.add_profile(profile)?;
+        Ok(name)
+    }
+
+    pub fn dismiss_pending_backend(&mut self) {
+        self.pending_backend = None;
+    }
+
+    /// The config as it is saved, leaving out what was only overridden for this run.
+    pub fn to_saved(&self) -> Self {
+        let mut saved = self.clone();
+        if let Some(active) = saved.saved_active_profile.take() {
+            saved.active_profile = active;
+        }
+        saved.pending_backend = None;
+        saved
+    }
+
+    /// Full URL for an API path such as `api/game/challenge`.
+    pub fn endpoint(&self, path: &str) -> String {
+        format!("{}{}", self.backend_url(), path.trim_start_matches('/'))
+    }
+
+    pub fn default_backend_url(&self) -> &str {
+        &self.default_backend_url
+    }
+
+    /* Without a build time BACKEND_URL the web app
+    expects the backend to be served from the same origin */
+    #[cfg(target_arch = "wasm32")]
+    fn platform_default(cc: &eframe::CreationContext<'_>) -> String {
+        let default = Self::default().default_backend_url;
+        if option_env!("BACKEND_URL").is_some() {
+            return default;
+        }
+        normalise_url(&cc.integration_info.web_info.location.origin).unwrap_or(default)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn platform_default(_cc: &eframe::CreationContext<'_>) -> String {
+        Self::default().default_backend_url
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn override_url(cc: &eframe::CreationContext<'_>) -> Option<String> {
+        let location = &cc.integration_info.web_info.location;
+        location.query_map.get("backend").cloned().or_else(|| {
+            let (_, query) = location.hash.split_once('?')?;
+            query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(key, _)| *key == "backend")
+                .map(|(_, value)| super::deep_link::percent_decode(value))
+        })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn override_url(_cc: &eframe::CreationContext<'_>) -> Option<String> {
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            if arg == "--backend" {
+                return args.next();
+            }
+            if let Some(url) = arg.strip_prefix("--backend=") {
+                return Some(url.to_string());
+            }
+        }
+        std::env::var("BACKEND_URL").ok()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn from_file() -> Option<Self> {
+        None
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn from_file() -> Option<Self> {
+        let path = directories_next::ProjectDirs::from("", "", "challenge_frontend")?
+            .config_dir()
+            .join("config.json");
+        let text = std::fs::read_to_string(&path).ok()?;
+        match serde_json::from_str::<Config>(&text) {
+            Ok(mut config) => {
+                for profile in config.profiles.iter_mut() {
+                    profile.backend_url = normalise_url(&profile.backend_url).ok()?;
+                }
+                Some(config)
+            }
+            Err(e) => {
+                log::error!("Failed to read {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+}
+
+fn normalise_url(url: &str) -> Result<String, String> {
+    let url = url.trim();
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err("URL must start with http:// or https://".to_string());
+    }
+    match url.ends_with('/') {
+        true => Ok(url.to_string()),
+        false => Ok(format!("{}/", url)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint() {
+        let mut config = Config::default();
+        config.set_backend_url("https://kata.example.com").unwrap();
+        assert_eq!(config.backend_url(), "https://kata.example.com/");
+        assert_eq!(
+            config.endpoint("api/game/challenge"),
+            "https://kata.example.com/api/game/challenge"
+        );
+        assert!(config.set_backend_url("kata.example.com").is_err());
+    }
+
+    #[test]
+    fn test_profiles() {
+        let mut config = Config::default();
+        let practice = Profile::new("Practice", "http://practice.example.com").unwrap();
+        config.add_profile(practice.clone()).unwrap();
+        assert!(config.add_profile(practice).is_err());
+
+        config.select_profile("Practice").unwrap();
+        assert_eq!(config.backend_url(), "http://practice.example.com/");
+        assert!(config.remove_profile("Practice").is_err());
+
+        // a backend nobody has added yet has to be confirmed first
+        config
+            .override_backend_url("http://staging.example.com")
+            .unwrap();
+        assert_eq!(config.profiles().len(), 2);
+        assert_eq!(config.backend_url(), "http://practice.example.com/");
+        assert_eq!(
+            config.pending_backend(),
+            Some("http://staging.example.com/")
+        );
+        let name = config.accept_pending_backend().unwrap();
+        assert_eq!(config.profiles().len(), 3);
+        assert!(config.pending_backend().is_none());
+        config.select_profile(&name).unwrap();
+        assert_eq!(config.backend_url(), "http://staging.example.com/");
+
+        // a known one is used for this run, but not saved as the active one
+        config
+            .override_backend_url("http://practice.example.com/")
+            .unwrap();
+        assert_eq!(config.active_profile(), "Practice");
+        assert_eq!(config.to_saved().active_profile(), name);
+        config.select_profile("Practice").unwrap();
+        assert_eq!(config.to_saved().active_profile(), "Practice");
+    }
+}
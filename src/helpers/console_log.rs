@@ -0,0 +1,102 @@
+use chrono::{DateTime, Local};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+const CAPACITY: usize = 1000;
+
+#[derive(Clone, Debug)]
+pub struct LogLine {
+    pub time: DateTime<Local>,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for LogLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {:<5} {}: {}",
+            self.time.format("%H:%M:%S%.3f"),
+            self.level,
+            self.target,
+            self.message
+        )
+    }
+}
+
+/* The logger has to be 'static,
+so the lines it captures can't live in the AppState */
+static LINES: Mutex<VecDeque<LogLine>> = Mutex::new(VecDeque::new());
+
+/// Passes everything on to the platform logger,
+/// and keeps the recent lines for the console window.
+struct ConsoleLogger {
+    inner: Box<dyn Log>,
+}
+
+impl ConsoleLogger {
+    /* Our own debug output is useful in the console,
+    the libraries' is mostly noise */
+    fn captures(&self, metadata: &Metadata<'_>) -> bool {
+        let max = match metadata.target().starts_with(env!("CARGO_CRATE_NAME")) {
+            true => Level::Debug,
+            false => Level::Info,
+        };
+        metadata.level() <= max
+    }
+}
+
+impl Log for ConsoleLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.captures(metadata) || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if self.inner.enabled(record.metadata()) {
+            self.inner.log(record);
+        }
+        if !self.captures(record.metadata()) {
+            return;
+        }
+        let line = LogLine {
+            time: Local::now(),
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+        };
+        if let Ok(mut lines) = LINES.lock() {
+            lines.push_back(line);
+            while lines.len() > CAPACITY {
+                lines.pop_front();
+            }
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Installs the console logger in front of `inner`,
+/// which still gets whatever it lets through up to `max_level`.
+pub fn init(inner: Box<dyn Log>, max_level: LevelFilter) -> Result<(), log::SetLoggerError> {
+    log::set_logger(Box::leak(Box::new(ConsoleLogger { inner })))?;
+    log::set_max_level(max_level.max(LevelFilter::Debug));
+    Ok(())
+}
+
+/// Don't log from inside `f`, the lines are locked.
+pub fn with_lines<R>(f: impl FnOnce(&VecDeque<LogLine>) -> R) -> R {
+    match LINES.lock() {
+        Ok(lines) => f(&lines),
+        Err(poisoned) => f(&poisoned.into_inner()),
+    }
+}
+
+pub fn clear() {
+    if let Ok(mut lines) = LINES.lock() {
+        lines.clear();
+    }
+}
@@ -0,0 +1,138 @@
+use super::submission::Submission;
+use std::fmt::{self, Display, Formatter};
+
+/// Something a link into the app asks for, e.g. from a password reset email.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DeepLink {
+    /// `#reset?token=...&email=...`
+    ResetPassword {
+        token: String,
+        email: Option<String>,
+    },
+    /// `#verify?token=...`
+    VerifyEmail { token: String },
+    /// `#challenge?command=...`, also followed from the challenge browser
+    OpenChallenge { command: String },
+    /// Never from a URL, the submission history opens old code this way
+    EditSubmission { submission: Submission },
+}
+
+impl DeepLink {
+    /// Parses a location hash, with or without the leading `#`.
+    pub fn parse(hash: &str) -> Option<Self> {
+        let hash = hash.trim_start_matches('#');
+        let (route, query) = hash.split_once('?').unwrap_or((hash, ""));
+        let param = |name: &str| {
+            query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| percent_decode(value))
+                .filter(|value| !value.is_empty())
+        };
+
+        // tokens end up in the request path, so nothing else is let through
+        let token = || param("token").filter(|token| is_token(token));
+
+        match route {
+            "reset" => Some(DeepLink::ResetPassword {
+                token: token()?,
+                email: param("email"),
+            }),
+            "verify" => Some(DeepLink::VerifyEmail { token: token()? }),
+            "challenge" => Some(DeepLink::OpenChallenge {
+                command: param("command")?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+fn is_token(value: &str) -> bool {
+    value
+        .bytes()
+        .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-')
+}
+
+/// Leaves out the token, links are logged.
+impl Display for DeepLink {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DeepLink::ResetPassword { .. } => write!(f, "password reset"),
+            DeepLink::VerifyEmail { .. } => write!(f, "email verification"),
+            DeepLink::OpenChallenge { command } => write!(f, "challenge {}", command),
+            DeepLink::EditSubmission { submission } => {
+                write!(f, "submission {}", submission.filename)
+            }
+        }
+    }
+}
+
+/* Emails often have a `+` in them,
+so unlike a form it is left as it is */
+pub(crate) fn percent_decode(value: &str) -> String {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let escaped = match (byte, tail) {
+            (b'%', [high, low, ..]) => std::str::from_utf8(&[*high, *low])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match escaped {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            DeepLink::parse("#reset?token=abc123&email=player%2Bone%40dummy.com"),
+            Some(DeepLink::ResetPassword {
+                token: "abc123".to_string(),
+                email: Some("player+one@dummy.com".to_string()),
+            })
+        );
+        assert_eq!(
+            DeepLink::parse("reset?email=&token=abc"),
+            Some(DeepLink::ResetPassword {
+                token: "abc".to_string(),
+                email: None,
+            })
+        );
+        assert_eq!(
+            DeepLink::parse("#verify?token=Ab_9-z"),
+            Some(DeepLink::VerifyEmail {
+                token: "Ab_9-z".to_string()
+            })
+        );
+        assert_eq!(DeepLink::parse("#verify?token=x%2"), None);
+        assert_eq!(
+            DeepLink::parse("#reset?token=..%2F..%2Fadmin%2Fusers%2Flock"),
+            None
+        );
+        assert_eq!(
+            DeepLink::parse("#challenge?command=2331"),
+            Some(DeepLink::OpenChallenge {
+                command: "2331".to_string()
+            })
+        );
+        assert_eq!(DeepLink::parse("#reset?email=player%40dummy.com"), None);
+        assert_eq!(DeepLink::parse("#Landing"), None);
+        assert_eq!(DeepLink::parse(""), None);
+    }
+}
@@ -0,0 +1,189 @@
+use sha1::{Digest, Sha1};
+use std::fmt::{self, Display, Formatter};
+use std::sync::mpsc::Sender;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum FileKind {
+    /// Data to try a solution against
+    #[default]
+    Sample,
+    /// A script that runs a solution against the samples, like `check.sh`
+    Checker,
+    Other,
+}
+
+impl Display for FileKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FileKind::Sample => write!(f, "Sample data"),
+            FileKind::Checker => write!(f, "Checker"),
+            FileKind::Other => write!(f, "File"),
+        }
+    }
+}
+
+/// A file offered with a challenge, from `api/game/challenge/{command}/files/{name}`.
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ChallengeFile {
+    pub name: String,
+    pub kind: FileKind,
+    /// In bytes, when the backend gives one
+    pub size: Option<u64>,
+    /// Hex encoded, when the backend gives one
+    pub sha1: Option<String>,
+}
+
+impl ChallengeFile {
+    pub fn path(&self, command: &str) -> String {
+        format!(
+            "api/game/challenge/{}/files/{}",
+            command,
+            percent_encode(&self.name)
+        )
+    }
+
+    /// Checks what was downloaded is what the backend listed.
+    pub fn verify(&self, bytes: &[u8]) -> Result<(), String> {
+        match self.size {
+            Some(size) if bytes.len() as u64 != size => {
+                return Err(format!(
+                    "{} is {} but should be {}",
+                    self.name,
+                    format_size(bytes.len() as u64),
+                    format_size(size)
+                ))
+            }
+            _ => {}
+        }
+        match &self.sha1 {
+            Some(expected) if !expected.eq_ignore_ascii_case(&sha1_hex(bytes)) => Err(format!(
+                "{} is corrupt, its checksum doesn't match",
+                self.name
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/* File names can have spaces, `#` or `?` in them, and tokens from links
+anything at all, so anything but the unreserved characters is escaped.
+What comes out is always a single path segment */
+pub(crate) fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+pub fn sha1_hex(bytes: &[u8]) -> String {
+    Sha1::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+pub fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1_048_575 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1024.0 / 1024.0),
+    }
+}
+
+/// Where a file was saved, or why it wasn't.
+pub type SaveResult = Result<String, String>;
+
+/// Asks where to keep `bytes` and writes them there, `done` hears where.
+/// Nothing is sent if the dialog is cancelled.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_file(filename: &str, bytes: Vec<u8>, ctx: egui::Context, done: Sender<SaveResult>) {
+    let dialog = rfd::AsyncFileDialog::new()
+        .set_file_name(filename)
+        .save_file();
+    super::execute(async move {
+        if let Some(handle) = dialog.await {
+            let result = handle
+                .write(&bytes)
+                .await
+                .map(|_| handle.path().display().to_string())
+                .map_err(|e| format!("Couldn't save {}: {}", handle.file_name(), e));
+            let _ = done.send(result);
+            ctx.request_repaint();
+        }
+    });
+}
+
+/* rfd can't save on web,
+so the browser downloads it instead */
+#[cfg(target_arch = "wasm32")]
+pub fn save_file(filename: &str, bytes: Vec<u8>, ctx: egui::Context, done: Sender<SaveResult>) {
+    let result = browser_download(filename, &bytes)
+        .map(|_| filename.to_string())
+        .map_err(|e| format!("Couldn't save {}: {}", filename, e));
+    let _ = done.send(result);
+    ctx.request_repaint();
+}
+
+#[cfg(target_arch = "wasm32")]
+fn browser_download(filename: &str, bytes: &[u8]) -> Result<(), String> {
+    use wasm_bindgen::JsCast;
+    let error = |e: wasm_bindgen::JsValue| format!("{:?}", e);
+
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let blob = web_sys::Blob::new_with_u8_array_sequence(&parts).map_err(error)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(error)?;
+    let anchor = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("No document to download from")?
+        .create_element("a")
+        .map_err(error)?
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .map_err(|_| "Not an anchor".to_string())?;
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+    web_sys::Url::revoke_object_url(&url).map_err(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify() {
+        let bytes = b"[[7], [0], [1, 1, 2]]";
+        let mut file = ChallengeFile {
+            name: "sample.json".to_string(),
+            size: Some(bytes.len() as u64),
+            sha1: Some(sha1_hex(bytes).to_uppercase()),
+            ..Default::default()
+        };
+        assert!(file.verify(bytes).is_ok());
+        assert_eq!(
+            file.verify(b"[[7]]"),
+            Err("sample.json is 5 B but should be 21 B".to_string())
+        );
+        assert!(file.verify(b"[[7], [0], [1, 1, 3]]").is_err());
+        file.sha1 = None;
+        assert!(file.verify(b"[[7], [0], [1, 1, 3]]").is_ok());
+        // not every listing says how big a file is
+        file.size = None;
+        assert!(file.verify(b"[[7]]").is_ok());
+
+        file.name = "sample #2?.json".to_string();
+        assert_eq!(
+            file.path("2331"),
+            "api/game/challenge/2331/files/sample%20%232%3F.json"
+        );
+
+        assert_eq!(sha1_hex(b""), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(format_size(2048), "2.0 KiB");
+        assert_eq!(format_size(3 * 1024 * 1024), "3.0 MiB");
+    }
+}
@@ -0,0 +1,15 @@
+use std::future::Future;
+
+/// Runs a future to completion in the background.
+///
+/// On native every future gets its own thread so blocking work, like reading
+/// a large file picked with `rfd`, never stalls the UI.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn execute<F: Future<Output = ()> + Send + 'static>(f: F) {
+    std::thread::spawn(move || pollster::block_on(f));
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn execute<F: Future<Output = ()> + 'static>(f: F) {
+    wasm_bindgen_futures::spawn_local(f);
+}
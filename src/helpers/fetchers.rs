@@ -1,21 +1,20 @@
-use crate::helpers::{refresh, AppState};
-use gloo_net::http;
-use poll_promise::Promise;
+use crate::helpers::{
+    api::ApiError,
+    refresh,
+    retry::{self, RetryPolicy},
+    transport::{Body, Form, HttpMethod, HttpRequest, HttpResponse, InFlight},
+    AppState,
+};
+use chrono::{DateTime, Utc};
 use std::sync::{Arc, Mutex};
-use web_sys::{FormData, RequestCredentials};
 
 #[derive(Clone, Debug)]
 pub enum RequestStatus {
     NotStarted,
     InProgress,
-    Success(String),
-    Failed(String),
-}
-
-enum Method {
-    Get,
-    Post,
-    Refresh,
+    /// The response body
+    Success(Vec<u8>),
+    Failed(ApiError),
 }
 
 impl std::fmt::Display for RequestStatus {
@@ -23,40 +22,49 @@ impl std::fmt::Display for RequestStatus {
         match self {
             RequestStatus::NotStarted => write!(f, "Not started"),
             RequestStatus::InProgress => write!(f, "Loading..."),
-            RequestStatus::Success(s) => write!(f, "{}", s),
-            RequestStatus::Failed(s) => write!(f, "{}", s),
+            RequestStatus::Success(body) => write!(f, "{}", String::from_utf8_lossy(body)),
+            RequestStatus::Failed(e) => write!(f, "{}", e),
         }
     }
 }
 
 enum FetchResponse {
     Success(RequestStatus),
-    Failure(String),
+    Failure(ApiError),
+    /// Worth trying again, optionally after the delay the server asked for
+    Transient(ApiError, Option<chrono::Duration>),
     FailAuth,
 }
 
 pub struct Requestor {
-    promise: Option<Promise<Result<FetchResponse, String>>>,
+    promise: Option<InFlight>,
     with_credentials: bool,
     url: String,
+    session: String,
     retry_count: usize,
     state_has_changed: bool,
     token_refresh_promise: refresh::RefreshPromise,
-    post_data: Option<String>,
-    form_data: Option<FormData>,
-    method: Method,
+    body: Body,
+    method: HttpMethod,
+    retry: RetryPolicy,
+    attempt: u32,
+    next_retry: Option<DateTime<Utc>>,
+    /// "latest wins" key and the generation this request holds
+    latest: Option<(String, u64)>,
+    cancelled: bool,
+    sent_at: DateTime<Utc>,
     pub app_state: Arc<Mutex<AppState>>,
 }
 
 impl Requestor {
     pub fn new_get(app_state: Arc<Mutex<AppState>>, url: &str, with_credentials: bool) -> Self {
-        Self::new(app_state, url, with_credentials, None, None, Method::Get)
-    }
-
-    pub fn new_refresh(app_state: Arc<Mutex<AppState>>) -> Self {
-        let mut s = Self::new(app_state, "", true, None, None, Method::Refresh);
-        s.refresh_login();
-        s
+        Self::new(
+            app_state,
+            url,
+            with_credentials,
+            Body::Empty,
+            HttpMethod::Get,
+        )
     }
 
     pub fn new_post(
@@ -65,27 +73,42 @@ impl Requestor {
         with_credentials: bool,
         data: Option<String>,
     ) -> Self {
-        Self::new(app_state, url, with_credentials, data, None, Method::Post)
+        let body = match data {
+            Some(data) => Body::Json(data),
+            None => Body::Empty,
+        };
+        Self::new(app_state, url, with_credentials, body, HttpMethod::Post)
     }
     pub fn new_form_post(
         app_state: Arc<Mutex<AppState>>,
         url: &str,
         with_credentials: bool,
-        data: Option<FormData>,
+        data: Option<Form>,
     ) -> Self {
-        Self::new(app_state, url, with_credentials, None, data, Method::Post)
+        let body = match data {
+            Some(form) => Body::Form(form),
+            None => Body::Empty,
+        };
+        Self::new(app_state, url, with_credentials, body, HttpMethod::Post)
     }
 
     fn new(
         app_state: Arc<Mutex<AppState>>,
         url: &str,
         with_credentials: bool,
-        data: Option<String>,
-        form: Option<FormData>,
-        method: Method,
+        body: Body,
+        method: HttpMethod,
     ) -> Self {
+        let (retry, session) = {
+            let app_state = app_state.lock().unwrap();
+            (
+                app_state.config.retry.clone(),
+                app_state.config.session_key(),
+            )
+        };
         Self {
             url: url.to_string(),
+            session,
             promise: None,
             with_credentials,
             retry_count: match with_credentials {
@@ -94,14 +117,77 @@ impl Requestor {
             },
             state_has_changed: false,
             token_refresh_promise: None,
-            post_data: data,
-            form_data: form,
+            body,
             method,
+            retry,
+            attempt: 0,
+            next_retry: None,
+            latest: None,
+            cancelled: false,
+            sent_at: Utc::now(),
             app_state,
         }
     }
 
+    /// Don't try to refresh the token when this request is rejected,
+    /// e.g. for the token refresh itself.
+    pub fn without_auth_retry(mut self) -> Self {
+        self.retry_count = 0;
+        self
+    }
+
+    /// Supersedes any other request made with the same `key`.
+    pub fn latest_wins(&mut self, key: &str) {
+        let generation = self.app_state.lock().unwrap().supersede(key);
+        self.latest = Some((key.to_string(), generation));
+    }
+
+    fn is_superseded(&self) -> bool {
+        match &self.latest {
+            Some((key, generation)) => !self.app_state.lock().unwrap().is_latest(key, *generation),
+            None => false,
+        }
+    }
+
+    /// Stops waiting for the response, it will never be reported.
+    pub fn cancel(&mut self) {
+        if !self.cancelled {
+            log::debug!("Cancelling {}", self.url);
+        }
+        if self.promise.as_ref().is_some_and(|p| p.ready().is_none()) {
+            self.log(Err("Cancelled"));
+        }
+        self.promise = None;
+        self.token_refresh_promise = None;
+        self.next_retry = None;
+        self.cancelled = true;
+    }
+
+    /// When the next attempt will be made, while waiting to retry.
+    pub fn next_retry(&self) -> Option<DateTime<Utc>> {
+        self.next_retry
+    }
+
     pub fn check_promise(&mut self) -> RequestStatus {
+        if !self.cancelled && self.is_superseded() {
+            log::debug!("Dropping superseded request to {}", self.url);
+            self.cancel();
+            self.state_has_changed = true;
+            return RequestStatus::Failed(ApiError::Cancelled);
+        }
+        if self.cancelled {
+            return RequestStatus::Failed(ApiError::Cancelled);
+        }
+        if let Some(at) = self.next_retry {
+            if Utc::now() < at {
+                return RequestStatus::InProgress;
+            }
+            log::debug!("Retrying {} (attempt {})", self.url, self.attempt);
+            self.next_retry = None;
+            self.send();
+            return RequestStatus::InProgress;
+        }
+
         match refresh::check_refresh_promise(&mut self.token_refresh_promise) {
             refresh::RefreshStatus::NotStarted => {}
             refresh::RefreshStatus::InProgress => {}
@@ -114,41 +200,60 @@ impl Requestor {
             }
             refresh::RefreshStatus::Failed(_) => {
                 self.state_has_changed = true;
-                return RequestStatus::Failed("Failed to authenticate".to_string());
+                AppState::set_logged_out(&self.app_state);
+                return RequestStatus::Failed(ApiError::Auth("Failed to authenticate".to_string()));
             }
         }
 
-        let mut res = RequestStatus::NotStarted;
-        if let Some(promise) = &self.promise {
-            res = match promise.ready() {
-                Some(result) => match result {
-                    Ok(FetchResponse::Success(status)) => status.clone(),
-                    Ok(FetchResponse::Failure(e)) => RequestStatus::Failed(e.to_string()),
-                    Ok(FetchResponse::FailAuth) => {
-                        if self.retry_count > 0 {
-                            log::debug!("Retrying auth");
-                            self.retry_count -= 1;
-                            self.token_refresh_promise = refresh::submit_refresh();
-                            RequestStatus::InProgress
-                        } else {
-                            AppState::set_logged_out(&self.app_state);
-                            RequestStatus::Failed("Authentication failed".to_string())
-                        }
-                    }
-
-                    Err(e) => RequestStatus::Failed(e.to_string()),
-                },
-                None => RequestStatus::InProgress,
-            };
-            self.state_has_changed = true;
-        }
-        match res {
-            RequestStatus::InProgress => {}
-            _ => {
-                self.promise = None;
+        let response = match &self.promise {
+            None => return RequestStatus::NotStarted,
+            Some(promise) => match promise.ready() {
+                None => return RequestStatus::InProgress,
+                Some(Ok(response)) => {
+                    self.log(Ok(response));
+                    self.app_state.lock().unwrap().set_reachable(true);
+                    self.classify(response)
+                }
+                Some(Err(e)) => {
+                    self.log(Err(e));
+                    self.app_state.lock().unwrap().set_reachable(false);
+                    self.network_error(e)
+                }
+            },
+        };
+        self.promise = None;
+        self.state_has_changed = true;
+
+        match response {
+            FetchResponse::Success(status) => status,
+            FetchResponse::Failure(e) => RequestStatus::Failed(e),
+            FetchResponse::Transient(e, after) => {
+                if self.attempt >= self.retry.max_retries {
+                    return RequestStatus::Failed(e);
+                }
+                let delay = after.unwrap_or_else(|| self.retry.delay(self.attempt));
+                self.attempt += 1;
+                self.next_retry = Some(Utc::now() + delay);
+                log::warn!(
+                    "{}, retrying {} in {}ms",
+                    e,
+                    self.url,
+                    delay.num_milliseconds()
+                );
+                RequestStatus::InProgress
+            }
+            FetchResponse::FailAuth => {
+                if self.retry_count > 0 {
+                    log::debug!("Retrying auth");
+                    self.retry_count -= 1;
+                    self.token_refresh_promise = refresh::submit_refresh(&self.app_state);
+                    RequestStatus::InProgress
+                } else {
+                    AppState::set_logged_out(&self.app_state);
+                    RequestStatus::Failed(ApiError::Auth("Authentication failed".to_string()))
+                }
             }
         }
-        res
     }
 
     pub fn refresh_context(&mut self) -> bool {
@@ -164,86 +269,65 @@ impl Requestor {
 
 impl Requestor {
     pub fn send(&mut self) {
-        match self.method {
-            Method::Get => self.get(),
-            Method::Post => self.post(),
-            Method::Refresh => (),
-        }
+        let transport = self.app_state.lock().unwrap().transport.clone();
+        self.sent_at = Utc::now();
+        self.promise = Some(transport.fetch(self.request()));
     }
 
-    fn refresh_login(&mut self) {
-        self.token_refresh_promise = refresh::submit_refresh();
+    fn request(&self) -> HttpRequest {
+        HttpRequest {
+            method: self.method,
+            url: self.url.clone(),
+            with_credentials: self.with_credentials,
+            session: self.session.clone(),
+            body: self.body.clone(),
+            timeout: Some(self.retry.timeout()),
+        }
     }
 
-    fn get(&mut self) {
-        let url = self.url.clone();
-        let with_credentials = self.with_credentials;
-        let promise = Promise::spawn_local(async move {
-            let request = http::Request::get(&url);
-            let request = match with_credentials {
-                true => request.credentials(RequestCredentials::Include),
-                false => request,
-            };
-            let response = request.send().await.map_err(|e| e.to_string())?;
-            let text = response.text().await.map_err(|e| e.to_string())?;
-
-            let result = match response.status() {
-                200 => FetchResponse::Success(RequestStatus::Success(text)),
-                401 => {
-                    log::warn!("Auth Error: {}", text);
-                    FetchResponse::FailAuth
-                }
-                _ => {
-                    log::error!("Response: {}", text);
-                    FetchResponse::Failure(text)
-                }
-            };
-            Ok(result)
-        });
-        self.promise = Some(promise);
-    }
-
-    fn post(&mut self) {
-        let url = self.url.clone();
-        let with_credentials = self.with_credentials;
-        let json_data = self.post_data.clone();
-        let form_data = self.form_data.clone();
-
-        let promise = Promise::spawn_local(async move {
-            let request = http::Request::post(&url);
-            let request = match with_credentials {
-                true => request.credentials(RequestCredentials::Include),
-                false => request,
-            };
-            let request = {
-                if let Some(data) = json_data {
-                    request
-                        .header("Content-Type", "application/json")
-                        .body(data)
-                        .map_err(|e| e.to_string())?
-                } else if let Some(data) = form_data {
-                    request.body(data).map_err(|e| e.to_string())?
-                } else {
-                    request.build().map_err(|e| e.to_string())?
-                }
-            };
+    /// Keeps a record of the attempt for the network inspector.
+    fn log(&self, result: Result<&HttpResponse, &str>) {
+        self.app_state.lock().unwrap().network_log.record(
+            &self.request(),
+            self.sent_at,
+            self.attempt,
+            result,
+        );
+    }
 
-            let response = request.send().await.map_err(|e| e.to_string())?;
-            let text = response.text().await.map_err(|e| e.to_string())?;
+    /* A GET can always be repeated, but a POST that failed along the way
+    may already have been handled by the backend, e.g. a submission.
+    Those are only retried when the backend says it turned them away */
+    fn classify(&self, response: &HttpResponse) -> FetchResponse {
+        let text = response.text();
+        let idempotent = self.method == HttpMethod::Get;
+        match response.status {
+            200..=299 => FetchResponse::Success(RequestStatus::Success(response.body.clone())),
+            401 => {
+                log::warn!("Auth Error: {}", text);
+                FetchResponse::FailAuth
+            }
+            status @ (429 | 503) => {
+                let after = response
+                    .header("Retry-After")
+                    .and_then(retry::parse_retry_after);
+                FetchResponse::Transient(ApiError::from_response(status, &text), after)
+            }
+            status @ 500..=599 if idempotent => {
+                FetchResponse::Transient(ApiError::from_response(status, &text), None)
+            }
+            status => {
+                log::error!("Response: {}", text);
+                FetchResponse::Failure(ApiError::from_response(status, &text))
+            }
+        }
+    }
 
-            let result = match response.status() {
-                200 => FetchResponse::Success(RequestStatus::Success(text)),
-                401 => {
-                    log::warn!("Auth Error: {}", text);
-                    FetchResponse::FailAuth
-                }
-                _ => {
-                    log::error!("Response: {}", text);
-                    FetchResponse::Failure(text)
-                }
-            };
-            Ok(result)
-        });
-        self.promise = Some(promise);
+    fn network_error(&self, e: &str) -> FetchResponse {
+        let error = ApiError::Network(e.to_string());
+        match self.method {
+            HttpMethod::Get => FetchResponse::Transient(error, None),
+            HttpMethod::Post => FetchResponse::Failure(error),
+        }
     }
 }
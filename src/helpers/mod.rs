@@ -1,11 +1,33 @@
 mod challenges;
-pub use challenges::ChallengeCollection;
+pub mod console_log;
+pub mod deep_link;
+pub use challenges::{
+    Attachment, AuthoredChallenge, Availability, Challenge, ChallengeChange, ChallengeCollection,
+    ChallengeDraft, ChallengeMeta, ChallengeQuery, ChallengeSort, Difficulty, IoMode, Publication,
+};
 mod languages;
 pub use languages::Languages;
 pub mod refresh;
+pub mod retry;
 pub mod submission;
+pub mod submission_history;
+pub mod submission_queue;
 
+pub mod admin;
+pub mod api;
+pub mod changes;
+pub mod downloads;
+mod executor;
 pub mod fetchers;
+pub mod network_log;
+pub mod offline_cache;
+pub mod session;
+pub mod transport;
+pub mod user_profile;
+pub use executor::execute;
+
+pub mod config;
+pub use config::Config;
 
 mod app_state;
 pub use app_state::AppState;
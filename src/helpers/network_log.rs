@@ -0,0 +1,205 @@
+use crate::helpers::transport::{Body, FormValue, HttpMethod, HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+
+const CAPACITY: usize = 200;
+const PREVIEW_LENGTH: usize = 2048;
+
+/// One attempt at a request, as seen by the network inspector.
+#[derive(Clone, Debug)]
+pub struct NetworkEntry {
+    pub id: u64,
+    pub method: HttpMethod,
+    pub url: String,
+    pub with_credentials: bool,
+    /// What was sent, with passwords blanked out
+    pub body: Body,
+    pub started: DateTime<Utc>,
+    pub duration: chrono::Duration,
+    /// 0 for the first attempt
+    pub retry: u32,
+    pub result: Result<u16, String>,
+    pub request_size: usize,
+    pub response_size: usize,
+    /// The start of the response
+    pub response: String,
+}
+
+impl NetworkEntry {
+    pub fn is_error(&self) -> bool {
+        !matches!(self.result, Ok(200..=299))
+    }
+
+    pub fn status(&self) -> String {
+        match &self.result {
+            Ok(status) => status.to_string(),
+            Err(e) => e.clone(),
+        }
+    }
+
+    /// Something to paste in a terminal to repeat the request.
+    pub fn to_curl(&self) -> String {
+        let mut curl = format!("curl -X {} {}", self.method, shell_quote(&self.url));
+        if self.with_credentials {
+            curl.push_str(" -b cookies.txt -c cookies.txt");
+        }
+        match &self.body {
+            Body::Empty => {}
+            Body::Json(data) => {
+                curl.push_str(" -H 'Content-Type: application/json'");
+                curl.push_str(&format!(" --data {}", shell_quote(data)));
+            }
+            Body::Form(form) => {
+                for (name, value) in &form.fields {
+                    // --form-string so a value starting with @ or < isn't read as a file
+                    let field = match value {
+                        FormValue::Text(text) => {
+                            format!(
+                                " --form-string {}",
+                                shell_quote(&format!("{}={}", name, text))
+                            )
+                        }
+                        FormValue::File { filename, .. } => {
+                            format!(" -F {}", shell_quote(&format!("{}=@{}", name, filename)))
+                        }
+                    };
+                    curl.push_str(&field);
+                }
+            }
+        }
+        curl
+    }
+}
+
+/// The most recent requests, oldest are dropped first.
+#[derive(Default)]
+pub struct NetworkLog {
+    entries: VecDeque<NetworkEntry>,
+    next_id: u64,
+}
+
+impl NetworkLog {
+    pub fn record(
+        &mut self,
+        request: &HttpRequest,
+        started: DateTime<Utc>,
+        retry: u32,
+        result: Result<&HttpResponse, &str>,
+    ) {
+        let (result, response_size, response) = match result {
+            Ok(response) => {
+                let preview = &response.body[..response.body.len().min(PREVIEW_LENGTH)];
+                (
+                    Ok(response.status),
+                    response.body.len(),
+                    String::from_utf8_lossy(preview).to_string(),
+                )
+            }
+            Err(e) => (Err(e.to_string()), 0, String::new()),
+        };
+
+        self.next_id += 1;
+        self.entries.push_back(NetworkEntry {
+            id: self.next_id,
+            method: request.method,
+            url: request.url.clone(),
+            with_credentials: request.with_credentials,
+            body: redact(&request.body),
+            started,
+            duration: Utc::now() - started,
+            retry,
+            result,
+            request_size: body_size(&request.body),
+            response_size,
+            response,
+        });
+        while self.entries.len() > CAPACITY {
+            self.entries.pop_front();
+        }
+    }
+
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &NetworkEntry> {
+        self.entries.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Wraps `value` in single quotes, so the shell takes it as it is.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn body_size(body: &Body) -> usize {
+    match body {
+        Body::Empty => 0,
+        Body::Json(data) => data.len(),
+        Body::Form(form) => form
+            .fields
+            .iter()
+            .map(|(_, value)| match value {
+                FormValue::Text(text) => text.len(),
+                FormValue::File { bytes, .. } => bytes.len(),
+            })
+            .sum(),
+    }
+}
+
+/* Whoever is looking over your shoulder at the inspector
+doesn't need to see your password.
+Files aren't kept either, they can be large */
+fn redact(body: &Body) -> Body {
+    match body {
+        Body::Empty => Body::Empty,
+        Body::Json(data) => match serde_json::from_str::<serde_json::Value>(data) {
+            Ok(serde_json::Value::Object(mut map)) => {
+                for (key, value) in map.iter_mut() {
+                    if key.contains("password") {
+                        *value = serde_json::Value::String("***".to_string());
+                    }
+                }
+                Body::Json(serde_json::Value::Object(map).to_string())
+            }
+            _ => body.clone(),
+        },
+        Body::Form(form) => {
+            let mut form = form.clone();
+            for (_, value) in form.fields.iter_mut() {
+                if let FormValue::File { bytes, .. } = value {
+                    bytes.clear();
+                }
+            }
+            Body::Form(form)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::transport::Form;
+
+    #[test]
+    fn test_to_curl() {
+        let mut form = Form::new();
+        form.text("filename", "it's.py");
+        form.file("file", "o'neil.py", b"print(7)");
+        let request = HttpRequest {
+            method: HttpMethod::Post,
+            url: "http://localhost:3000/api/game/binary?x='; rm -rf ~".to_string(),
+            with_credentials: false,
+            session: String::new(),
+            body: Body::Form(form),
+            timeout: None,
+        };
+        let mut log = NetworkLog::default();
+        log.record(&request, Utc::now(), 0, Err("Connection refused"));
+        assert_eq!(
+            log.entries().next().unwrap().to_curl(),
+            "curl -X POST 'http://localhost:3000/api/game/binary?x='\\''; rm -rf ~' \
+             --form-string 'filename=it'\\''s.py' -F 'file=@o'\\''neil.py'"
+        );
+    }
+}
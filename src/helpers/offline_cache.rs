@@ -0,0 +1,433 @@
+//! Keeps the last challenges and scoreboards fetched, to show while the backend can't be reached,
+//! and apart from that the submissions waiting for it and the admin audit log.
+//! Changes are only marked dirty while AppState is locked, and written out later by [`flush`].
+use crate::helpers::{
+    admin::AuditLog, submission_history::SubmissionHistory, submission_queue::SubmissionQueue,
+    AppState, ChallengeCollection,
+};
+use chrono::{DateTime, Local, Utc};
+use scoreboard_db::Score;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct CachedScores {
+    pub scores: Vec<Score>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// What was last fetched from one backend.
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct CachedBackend {
+    pub challenges: ChallengeCollection,
+    pub scoreboards: BTreeMap<String, CachedScores>,
+    pub history: SubmissionHistory,
+    /// Where the queue was kept before it got a store of its own
+    #[serde(skip_serializing)]
+    submissions: SubmissionQueue,
+}
+
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct OfflineCache {
+    /// By [`Config::session_key`](super::Config::session_key)
+    pub backends: BTreeMap<String, CachedBackend>,
+}
+
+/// The submissions waiting for each backend, by session key.
+pub type QueuedSubmissions = BTreeMap<String, SubmissionQueue>;
+
+/// Somewhere to keep the [`OfflineCache`], the [`QueuedSubmissions`] or the [`AuditLog`] between runs, as JSON.
+pub trait CacheStore {
+    fn load(&self) -> Option<String>;
+    fn save(&self, text: &str) -> Result<(), String>;
+}
+
+/// Forgets everything on exit.
+#[derive(Default)]
+pub struct MemoryCache {
+    text: Mutex<Option<String>>,
+}
+
+impl CacheStore for MemoryCache {
+    fn load(&self) -> Option<String> {
+        self.text.lock().unwrap().clone()
+    }
+
+    fn save(&self, text: &str) -> Result<(), String> {
+        *self.text.lock().unwrap() = Some(text.to_string());
+        Ok(())
+    }
+}
+
+/// Keeps the JSON in a file, nothing in it is secret.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileCache {
+    path: std::path::PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileCache {
+    pub fn new(path: &std::path::Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+
+    pub fn in_cache_dir() -> Option<Self> {
+        let dirs = directories_next::ProjectDirs::from("", "", "challenge_frontend")?;
+        Some(Self::new(&dirs.cache_dir().join(CACHE_FILE)))
+    }
+
+    /* Unlike the cache, the queue and audit log can't be fetched again,
+    so they aren't kept where the system may clear them */
+    pub fn in_data_dir(file: &str) -> Option<Self> {
+        let dirs = directories_next::ProjectDirs::from("", "", "challenge_frontend")?;
+        Some(Self::new(&dirs.data_dir().join(file)))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const CACHE_FILE: &str = "offline_cache.json";
+#[cfg(not(target_arch = "wasm32"))]
+const QUEUE_FILE: &str = "submission_queue.json";
+#[cfg(not(target_arch = "wasm32"))]
+const AUDIT_FILE: &str = "audit_log.json";
+
+#[cfg(not(target_arch = "wasm32"))]
+impl CacheStore for FileCache {
+    fn load(&self) -> Option<String> {
+        std::fs::read_to_string(&self.path).ok()
+    }
+
+    fn save(&self, text: &str) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(&self.path, text)
+            .map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))
+    }
+}
+
+/// Keeps the JSON in local storage, next to where eframe keeps its own.
+#[cfg(target_arch = "wasm32")]
+pub struct LocalStorageCache {
+    key: &'static str,
+}
+
+#[cfg(target_arch = "wasm32")]
+const CACHE_KEY: &str = "challenge_frontend_cache";
+#[cfg(target_arch = "wasm32")]
+const QUEUE_KEY: &str = "challenge_frontend_queue";
+#[cfg(target_arch = "wasm32")]
+const AUDIT_KEY: &str = "challenge_frontend_audit";
+
+#[cfg(target_arch = "wasm32")]
+impl CacheStore for LocalStorageCache {
+    fn load(&self) -> Option<String> {
+        eframe::web::storage::local_storage_get(self.key)
+    }
+
+    /* eframe's own setter drops the error, and running out
+    of quota is exactly when it matters */
+    fn save(&self, text: &str) -> Result<(), String> {
+        let storage = web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or("Local storage isn't available")?;
+        storage
+            .set_item(self.key, text)
+            .map_err(|e| format!("Failed to write local storage: {:?}", e))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn default_store() -> Arc<dyn CacheStore> {
+    match FileCache::in_cache_dir() {
+        Some(store) => Arc::new(store),
+        None => {
+            log::warn!("No cache directory, nothing will be shown offline");
+            Arc::new(MemoryCache::default())
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn default_queue_store() -> Arc<dyn CacheStore> {
+    match FileCache::in_data_dir(QUEUE_FILE) {
+        Some(store) => Arc::new(store),
+        None => {
+            log::warn!("No data directory, queued submissions are lost on exit");
+            Arc::new(MemoryCache::default())
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn default_audit_store() -> Arc<dyn CacheStore> {
+    match FileCache::in_data_dir(AUDIT_FILE) {
+        Some(store) => Arc::new(store),
+        None => {
+            log::warn!("No data directory, the audit log is lost on exit");
+            Arc::new(MemoryCache::default())
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn default_store() -> Arc<dyn CacheStore> {
+    Arc::new(LocalStorageCache { key: CACHE_KEY })
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn default_queue_store() -> Arc<dyn CacheStore> {
+    Arc::new(LocalStorageCache { key: QUEUE_KEY })
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn default_audit_store() -> Arc<dyn CacheStore> {
+    Arc::new(LocalStorageCache { key: AUDIT_KEY })
+}
+
+fn load<T: serde::de::DeserializeOwned + Default>(store: &dyn CacheStore) -> T {
+    let text = match store.load() {
+        Some(text) => text,
+        None => return T::default(),
+    };
+    serde_json::from_str(&text)
+        .map_err(|e| log::warn!("Ignoring broken offline cache: {}", e))
+        .unwrap_or_default()
+}
+
+/// Brings back what was kept for the active backend,
+/// unless something newer is already there.
+pub fn restore(app_state: &mut AppState) {
+    let key = app_state.config.session_key();
+    let mut queued: QueuedSubmissions = load(app_state.queue_store.as_ref());
+    let mut cache: OfflineCache = load(app_state.cache_store.as_ref());
+    let cached = cache.backends.remove(&key).unwrap_or_default();
+
+    // the audit log isn't kept per backend
+    if app_state.audit_log.is_empty() {
+        let mut audit_log: AuditLog = load(app_state.audit_store.as_ref());
+        audit_log.close_unanswered();
+        app_state.audit_log = audit_log;
+    }
+
+    let submissions = queued.remove(&key).unwrap_or(cached.submissions);
+    if app_state.submission_queue.is_empty() && !submissions.is_empty() {
+        log::info!(
+            "{} submissions are waiting to be sent",
+            submissions.items().len()
+        );
+        app_state.submission_queue = submissions;
+    }
+    if app_state.submission_history.is_empty() {
+        app_state.submission_history = cached.history;
+    }
+    if !app_state.challenges.items.is_empty() || cached.challenges.items.is_empty() {
+        return;
+    }
+    log::info!(
+        "Restored {} challenges and {} scoreboards from the offline cache",
+        cached.challenges.items.len(),
+        cached.scoreboards.len()
+    );
+    app_state.challenges = cached.challenges;
+    app_state.challenges.from_cache = true;
+    for (table, cached) in cached.scoreboards {
+        app_state.scoreboards.insert(table.clone(), cached.scores);
+        app_state.scoreboard_times.insert(table, cached.fetched_at);
+    }
+}
+
+/// What changed for one backend since it was last written out.
+pub struct CacheSnapshot {
+    key: String,
+    cache: Option<(Arc<dyn CacheStore>, CachedBackend)>,
+    queue: Option<(Arc<dyn CacheStore>, SubmissionQueue)>,
+    audit: Option<(Arc<dyn CacheStore>, AuditLog)>,
+}
+
+impl CacheSnapshot {
+    /// Takes whatever is marked dirty for the active backend, `None` when nothing is.
+    pub fn take(app_state: &mut AppState) -> Option<Self> {
+        let cache = std::mem::take(&mut app_state.cache_dirty).then(|| {
+            let scoreboards = app_state
+                .scoreboards
+                .iter()
+                .filter_map(|(table, scores)| {
+                    let fetched_at = *app_state.scoreboard_times.get(table)?;
+                    let scores = scores.clone();
+                    Some((table.clone(), CachedScores { scores, fetched_at }))
+                })
+                .collect();
+            let backend = CachedBackend {
+                challenges: app_state.challenges.clone(),
+                scoreboards,
+                history: app_state.submission_history.clone(),
+                submissions: SubmissionQueue::default(),
+            };
+            (app_state.cache_store.clone(), backend)
+        });
+        let queue = std::mem::take(&mut app_state.queue_dirty).then(|| {
+            (
+                app_state.queue_store.clone(),
+                app_state.submission_queue.clone(),
+            )
+        });
+        let audit = std::mem::take(&mut app_state.audit_dirty)
+            .then(|| (app_state.audit_store.clone(), app_state.audit_log.clone()));
+        if cache.is_none() && queue.is_none() && audit.is_none() {
+            return None;
+        }
+        Some(Self {
+            key: app_state.config.session_key(),
+            cache,
+            queue,
+            audit,
+        })
+    }
+
+    /* Reads and writes the stores, natively whole files,
+    so it is done with AppState unlocked. Only failing to keep the queue
+    is reported, the cache can be fetched again and the audit log is also in the log */
+    pub fn write(self) -> Result<(), String> {
+        if let Some((store, audit_log)) = self.audit {
+            if let Err(e) = save(store.as_ref(), &audit_log) {
+                log::error!("Failed to save the audit log: {}", e);
+            }
+        }
+        if let Some((store, backend)) = self.cache {
+            let mut cache: OfflineCache = load(store.as_ref());
+            cache.backends.insert(self.key.clone(), backend);
+            if let Err(e) = save(store.as_ref(), &cache) {
+                log::error!("Failed to save the offline cache: {}", e);
+            }
+        }
+        if let Some((store, queue)) = self.queue {
+            let mut queued: QueuedSubmissions = load(store.as_ref());
+            match queue.is_empty() {
+                true => queued.remove(&self.key),
+                false => queued.insert(self.key, queue),
+            };
+            if let Err(e) = save(store.as_ref(), &queued) {
+                log::error!("Failed to save the submission queue: {}", e);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn save<T: serde::Serialize>(store: &dyn CacheStore, value: &T) -> Result<(), String> {
+    let text = serde_json::to_string(value).map_err(|e| e.to_string())?;
+    store.save(&text)
+}
+
+/// Writes out everything marked dirty since the last flush.
+pub fn flush(app_state: &Arc<Mutex<AppState>>) -> Result<(), String> {
+    let snapshots = app_state.lock().unwrap().take_unsaved();
+    snapshots
+        .into_iter()
+        .map(CacheSnapshot::write)
+        .fold(Ok(()), Result::and)
+}
+
+/// e.g. "stale since 14 Mar 09:30"
+pub fn stale_since(at: DateTime<Utc>) -> String {
+    format!(
+        "stale since {}",
+        at.with_timezone(&Local).format("%d %b %H:%M")
+    )
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::helpers::{admin::AdminAction, submission::Submission, Challenge};
+
+    #[test]
+    fn test_file_cache() {
+        let path = |name: &str| {
+            std::env::temp_dir().join(format!(
+                "challenge_frontend_{}_{}.json",
+                name,
+                std::process::id()
+            ))
+        };
+        let (cache_path, queue_path, audit_path) = (path("cache"), path("queue"), path("audit"));
+        let stores = |app_state: &mut AppState| {
+            app_state.cache_store = Arc::new(FileCache::new(&cache_path));
+            app_state.queue_store = Arc::new(FileCache::new(&queue_path));
+            app_state.audit_store = Arc::new(FileCache::new(&audit_path));
+        };
+        let mut app_state = AppState::default();
+        stores(&mut app_state);
+        restore(&mut app_state);
+        assert!(app_state.challenges.items.is_empty());
+        assert!(CacheSnapshot::take(&mut app_state).is_none());
+
+        let fetched_at = Utc::now();
+        app_state.challenges = ChallengeCollection::from_items(vec![Challenge::new(
+            "Sorting", "2411", "24_1_1", "# Sort",
+        )]);
+        app_state.challenges.fetched_at = Some(fetched_at);
+        app_state.store_scores("24_1_1", Vec::new());
+        // never fetched, so not worth keeping
+        app_state
+            .scoreboards
+            .insert("23_3_1".to_string(), Vec::new());
+        // only marked, nothing is written until the snapshot is
+        assert!(!cache_path.exists());
+        let snapshot = CacheSnapshot::take(&mut app_state).unwrap();
+        assert!(snapshot.queue.is_none());
+        snapshot.write().unwrap();
+        assert!(CacheSnapshot::take(&mut app_state).is_none());
+
+        app_state.submission_queue.push(
+            Submission {
+                filename: "odds.py".to_string(),
+                ..Default::default()
+            },
+            "Network error",
+            "player@dummy.com",
+        );
+        app_state.queue_dirty = true;
+        app_state.audit_log.record(
+            "admin@dummy.com",
+            &AdminAction::Lock {
+                email: "player@dummy.com".to_string(),
+            },
+        );
+        app_state.audit_dirty = true;
+        CacheSnapshot::take(&mut app_state)
+            .unwrap()
+            .write()
+            .unwrap();
+
+        let mut restored = AppState::default();
+        stores(&mut restored);
+        restore(&mut restored);
+        assert_eq!(restored.challenges.items, app_state.challenges.items);
+        assert_eq!(restored.challenges.fetched_at, Some(fetched_at));
+        assert!(restored.challenges.from_cache);
+        assert_eq!(restored.scoreboards.len(), 1);
+        assert!(restored.scoreboard_times.contains_key("24_1_1"));
+        assert_eq!(restored.submission_queue.items().len(), 1);
+        let audit: Vec<_> = restored.audit_log.entries().collect();
+        assert_eq!(audit[0].action, "Lock the account of player@dummy.com");
+        assert!(matches!(audit[0].outcome, Some(Err(_))));
+        let cached = std::fs::read_to_string(&cache_path).unwrap();
+        assert!(!cached.contains("odds.py"));
+
+        std::fs::write(&cache_path, "not json").unwrap();
+        assert!(load::<OfflineCache>(&FileCache::new(&cache_path))
+            .backends
+            .is_empty());
+        std::fs::remove_file(&cache_path).unwrap();
+        std::fs::remove_file(&queue_path).unwrap();
+        std::fs::remove_file(&audit_path).unwrap();
+    }
+}
@@ -1,6 +1,9 @@
-use gloo_net::http;
-use poll_promise::Promise;
-use web_sys::RequestCredentials;
+use crate::helpers::{
+    transport::{Body, HttpMethod, HttpRequest, InFlight},
+    AppState,
+};
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, Mutex};
 
 #[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct RefreshResponse {
@@ -14,40 +17,71 @@ pub enum RefreshStatus {
     Failed(String),
 }
 
-pub type RefreshPromise = Option<Promise<Result<RefreshResponse, String>>>;
+/// A refresh on its way. It skips the `Requestor`,
+/// so it keeps what the network inspector needs itself.
+pub struct PendingRefresh {
+    request: HttpRequest,
+    started: DateTime<Utc>,
+    in_flight: InFlight,
+    app_state: Arc<Mutex<AppState>>,
+}
+
+pub type RefreshPromise = Option<PendingRefresh>;
 
-pub fn submit_refresh() -> RefreshPromise {
-    let url = option_env!("BACKEND_URL")
-        .unwrap_or("http://123.4.5.6:3000/")
-        .to_string();
-    let url = format!("{}api/auth/refresh", url);
+pub fn submit_refresh(app_state: &Arc<Mutex<AppState>>) -> RefreshPromise {
+    let (url, session, timeout, transport) = {
+        let app_state = app_state.lock().unwrap();
+        (
+            app_state.endpoint("api/auth/refresh"),
+            app_state.config.session_key(),
+            app_state.config.retry.timeout(),
+            app_state.transport.clone(),
+        )
+    };
     log::debug!("Refreshing token");
 
-    Some(Promise::spawn_local(async move {
-        let response = http::Request::get(&url)
-            .credentials(RequestCredentials::Include)
-            .send()
-            .await
-            .unwrap();
-        response
-            .json::<RefreshResponse>()
-            .await
-            .map_err(|e| e.to_string())
-    }))
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url,
+        with_credentials: true,
+        session,
+        body: Body::Empty,
+        timeout: Some(timeout),
+    };
+    Some(PendingRefresh {
+        started: Utc::now(),
+        in_flight: transport.fetch(request.clone()),
+        request,
+        app_state: app_state.clone(),
+    })
 }
 
 pub fn check_refresh_promise(promise: &mut RefreshPromise) -> RefreshStatus {
     let mut res = RefreshStatus::NotStarted;
     if let Some(p) = promise {
         res = RefreshStatus::InProgress;
-        if let Some(result) = p.ready() {
-            if let Ok(result) = result {
-                if "success" == result.status {
+        if let Some(result) = p.in_flight.ready() {
+            p.app_state.lock().unwrap().network_log.record(
+                &p.request,
+                p.started,
+                0,
+                result.as_ref().map_err(|e| e.as_str()),
+            );
+            let result = result.as_ref().map_err(|e| e.to_string()).and_then(|r| {
+                serde_json::from_slice::<RefreshResponse>(&r.body).map_err(|e| e.to_string())
+            });
+            match result {
+                Ok(result) if "success" == result.status => {
                     log::info!("Token refreshed");
                     res = RefreshStatus::Success;
-                } else {
+                }
+                Ok(result) => {
                     log::error!("Failed to refresh token: {:?}", result);
-                    res = RefreshStatus::Failed(result.message.clone());
+                    res = RefreshStatus::Failed(result.message);
+                }
+                Err(e) => {
+                    log::error!("Failed to refresh token: {}", e);
+                    res = RefreshStatus::Failed(e);
                 }
             }
             *promise = None;
@@ -0,0 +1,99 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// How long to wait for the backend and how to retry when it is unavailable.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+    pub timeout_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 4,
+            base_delay_ms: 1000,
+            max_delay_ms: 30_000,
+            timeout_ms: 15_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.timeout_ms)
+    }
+
+    /* Exponential backoff with jitter, so a backend coming back up
+    isn't hit by every client at the same moment.
+    The delay lands somewhere between half and all of base * 2^attempt */
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let max = self.max_delay_ms.max(1);
+        let delay = self
+            .base_delay_ms
+            .saturating_mul(1 << attempt.min(20))
+            .clamp(1, max);
+        let jittered = delay / 2 + (jitter() * (delay / 2) as f64) as u64;
+        Duration::milliseconds(jittered as i64)
+    }
+}
+
+/// A number between 0 and 1, random enough to spread out retries.
+fn jitter() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_i64(Utc::now().timestamp_nanos_opt().unwrap_or_default());
+    (hasher.finish() % 1000) as f64 / 1000.0
+}
+
+/// `Retry-After` holds either a number of seconds or an HTTP date.
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<i64>() {
+        return Some(Duration::seconds(seconds.max(0)));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((at.with_timezone(&Utc) - Utc::now()).max(Duration::zero()))
+}
+
+/// e.g. "retrying in 8s"
+pub fn countdown(at: DateTime<Utc>) -> String {
+    let seconds = (at - Utc::now()).num_seconds().max(0);
+    format!("retrying in {}s", seconds + 1)
+}
+
+/// Keeps frames coming while waiting for a retry so it actually gets sent,
+/// and a countdown stays current.
+pub fn repaint_while_waiting(ctx: &egui::Context, next_retry: Option<DateTime<Utc>>) {
+    if let Some(at) = next_retry {
+        let wait = (at - Utc::now())
+            .to_std()
+            .unwrap_or_default()
+            .min(std::time::Duration::from_secs(1));
+        ctx.request_repaint_after(wait);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::default();
+        for attempt in 0..10 {
+            let delay = policy.delay(attempt).num_milliseconds() as u64;
+            let full = (policy.base_delay_ms << attempt).min(policy.max_delay_ms);
+            assert!(delay >= full / 2 && delay <= full, "{} {}", attempt, delay);
+        }
+        assert_eq!(parse_retry_after("8"), Some(Duration::seconds(8)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::zero())
+        );
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+}
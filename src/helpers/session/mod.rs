@@ -0,0 +1,156 @@
+//! Remembers who is logged in between runs.
+#[cfg(not(target_arch = "wasm32"))]
+mod native;
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::EncryptedFileStore;
+#[cfg(target_arch = "wasm32")]
+mod web;
+#[cfg(target_arch = "wasm32")]
+pub use web::LocalStorageStore;
+
+use crate::helpers::AppState;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// Who is logged in, as told by the backend.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Identity {
+    pub email: String,
+    pub name: String,
+    pub roles: Vec<String>,
+}
+
+impl Identity {
+    /// The name if the backend gave one, the email otherwise.
+    pub fn display_name(&self) -> &str {
+        match self.name.is_empty() {
+            true => &self.email,
+            false => &self.name,
+        }
+    }
+
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r.eq_ignore_ascii_case(role))
+    }
+}
+
+/// What is kept between runs.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SavedSession {
+    /// The last identity seen on each backend, by [`Config::session_key`](super::Config::session_key)
+    pub identities: BTreeMap<String, Identity>,
+    /// From [`Transport::credentials`](super::transport::Transport::credentials)
+    pub credentials: Option<String>,
+}
+
+/// Somewhere to keep the session. The credentials include the refresh
+/// token, so anything that persists them must keep them from other users.
+pub trait SessionStore {
+    fn load(&self) -> Option<SavedSession>;
+    fn save(&self, session: &SavedSession) -> Result<(), String>;
+}
+
+/// Forgets everything on exit.
+#[derive(Default)]
+pub struct MemoryStore {
+    session: Mutex<Option<SavedSession>>,
+}
+
+impl SessionStore for MemoryStore {
+    fn load(&self) -> Option<SavedSession> {
+        self.session.lock().unwrap().clone()
+    }
+
+    fn save(&self, session: &SavedSession) -> Result<(), String> {
+        *self.session.lock().unwrap() = Some(session.clone());
+        Ok(())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn default_store() -> Arc<dyn SessionStore> {
+    match EncryptedFileStore::in_data_dir() {
+        Some(store) => Arc::new(store),
+        None => {
+            log::warn!("No data directory, sessions won't be remembered");
+            Arc::new(MemoryStore::default())
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn default_store() -> Arc<dyn SessionStore> {
+    Arc::new(LocalStorageStore)
+}
+
+/// Hands the saved credentials back to the transport and brings back
+/// who was logged in, to show until the session has been checked.
+pub fn restore(app_state: &mut AppState) {
+    let saved = match app_state.session_store.load() {
+        Some(saved) => saved,
+        None => return,
+    };
+    if let Some(credentials) = &saved.credentials {
+        app_state.transport.restore_credentials(credentials);
+    }
+    app_state.identity = saved
+        .identities
+        .get(&app_state.config.session_key())
+        .cloned();
+}
+
+/// What [`save`] writes, taken while [`AppState`] is locked.
+pub struct SessionSnapshot {
+    store: Arc<dyn SessionStore>,
+    key: String,
+    identity: Option<Identity>,
+    credentials: Option<String>,
+}
+
+impl SessionSnapshot {
+    pub fn of(app_state: &AppState) -> Self {
+        Self {
+            store: app_state.session_store.clone(),
+            key: app_state.config.session_key(),
+            identity: app_state.identity.clone(),
+            credentials: app_state.transport.credentials(),
+        }
+    }
+
+    /* Reads and writes the store, natively an encrypted file,
+    so it is done with AppState unlocked */
+    pub fn write(self) {
+        let mut saved = self.store.load().unwrap_or_default();
+        match self.identity {
+            Some(identity) => saved.identities.insert(self.key, identity),
+            None => saved.identities.remove(&self.key),
+        };
+        saved.credentials = self.credentials;
+        if let Err(e) = self.store.save(&saved) {
+            log::error!("Failed to save session: {}", e);
+        }
+    }
+}
+
+/// Saves the identity for the active backend along with the current credentials.
+pub fn save(app_state: &Arc<Mutex<AppState>>) {
+    let snapshot = SessionSnapshot::of(&app_state.lock().unwrap());
+    snapshot.write();
+}
+
+/* Logging in as someone else without logging out first
+drops the submission history of whoever was there before */
+pub fn set_identity(app_state: &Arc<Mutex<AppState>>, identity: Option<Identity>) {
+    {
+        let mut app_state = app_state.lock().unwrap();
+        let email = |identity: &Option<Identity>| identity.as_ref().map(|i| i.email.clone());
+        if email(&app_state.identity) != email(&identity) {
+            app_state.submission_history = Default::default();
+            app_state.cache_dirty = true;
+        }
+        app_state.identity = identity;
+    }
+    save(app_state);
+}
@@ -0,0 +1,157 @@
+use super::{SavedSession, SessionStore};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+const KEY_FILE: &str = "session.key";
+const SESSION_FILE: &str = "session.bin";
+const AAD: &[u8] = b"challenge_frontend session";
+
+/// Keeps the session encrypted, with the key in a separate file only the user can read.
+///
+/// A copied or synced session file is useless without the key, and
+/// nothing is written where eframe keeps its plain text storage.
+pub struct EncryptedFileStore {
+    dir: PathBuf,
+}
+
+impl EncryptedFileStore {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    pub fn in_data_dir() -> Option<Self> {
+        let dirs = directories_next::ProjectDirs::from("", "", "challenge_frontend")?;
+        Some(Self::new(dirs.data_local_dir()))
+    }
+
+    /* Only a missing key is replaced, one that can't be read right now
+    would otherwise be overwritten and the saved session lost with it */
+    fn key(&self) -> Result<LessSafeKey, String> {
+        let path = self.dir.join(KEY_FILE);
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() != ErrorKind::NotFound => {
+                return Err(format!("Couldn't read {}: {}", path.display(), e))
+            }
+            Err(_) => {
+                let mut bytes = vec![0; CHACHA20_POLY1305.key_len()];
+                SystemRandom::new()
+                    .fill(&mut bytes)
+                    .map_err(|_| "No randomness for a session key".to_string())?;
+                write_private(&path, &bytes)?;
+                bytes
+            }
+        };
+        let key = UnboundKey::new(&CHACHA20_POLY1305, &bytes)
+            .map_err(|_| format!("{} is not a valid key", path.display()))?;
+        Ok(LessSafeKey::new(key))
+    }
+}
+
+impl SessionStore for EncryptedFileStore {
+    fn load(&self) -> Option<SavedSession> {
+        let data = fs::read(self.dir.join(SESSION_FILE)).ok()?;
+        if data.len() < NONCE_LEN {
+            return None;
+        }
+        let key = self.key().ok()?;
+        let (nonce, sealed) = data.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).ok()?;
+        let mut sealed = sealed.to_vec();
+        let plain = match key.open_in_place(nonce, Aad::from(AAD), &mut sealed) {
+            Ok(plain) => plain,
+            Err(_) => {
+                log::warn!("Saved session could not be decrypted, ignoring it");
+                return None;
+            }
+        };
+        serde_json::from_slice(plain).ok()
+    }
+
+    fn save(&self, session: &SavedSession) -> Result<(), String> {
+        fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        let key = self.key()?;
+
+        let mut nonce = [0; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| "No randomness for a nonce".to_string())?;
+        let mut data = serde_json::to_vec(session).map_err(|e| e.to_string())?;
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(AAD),
+            &mut data,
+        )
+        .map_err(|_| "Failed to encrypt session".to_string())?;
+
+        let mut file = nonce.to_vec();
+        file.append(&mut data);
+        write_private(&self.dir.join(SESSION_FILE), &file)
+    }
+}
+
+fn write_private(path: &Path, data: &[u8]) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(data))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::session::Identity;
+
+    #[test]
+    fn test_encrypted_round_trip() {
+        let dir =
+            std::env::temp_dir().join(format!("challenge_frontend_session_{}", std::process::id()));
+        let store = EncryptedFileStore::new(&dir);
+        assert_eq!(store.load(), None);
+
+        let mut session = SavedSession {
+            credentials: Some("refresh_token=secret".to_string()),
+            ..Default::default()
+        };
+        session.identities.insert(
+            "Default@http://localhost/".to_string(),
+            Identity {
+                email: "player@dummy.com".to_string(),
+                name: "Player One".to_string(),
+                roles: vec!["user".to_string()],
+            },
+        );
+        store.save(&session).unwrap();
+        assert_eq!(store.load(), Some(session));
+
+        let data = fs::read(dir.join(SESSION_FILE)).unwrap();
+        assert!(!String::from_utf8_lossy(&data).contains("secret"));
+
+        // a session encrypted with another key is ignored
+        fs::write(dir.join(KEY_FILE), [7; 32]).unwrap();
+        assert_eq!(store.load(), None);
+
+        // and a key that can't be read isn't replaced
+        fs::remove_file(dir.join(KEY_FILE)).unwrap();
+        fs::create_dir(dir.join(KEY_FILE)).unwrap();
+        assert!(store.save(&SavedSession::default()).is_err());
+        assert!(dir.join(KEY_FILE).is_dir());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
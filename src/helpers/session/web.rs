@@ -0,0 +1,27 @@
+use super::{SavedSession, SessionStore};
+use eframe::web::storage::{local_storage_get, local_storage_set};
+
+const STORAGE_KEY: &str = "challenge_frontend_session";
+
+/// Keeps who was logged in in local storage.
+///
+/// The browser holds on to the auth cookies itself and doesn't let us
+/// read them, so there are never any credentials to store here.
+pub struct LocalStorageStore;
+
+impl SessionStore for LocalStorageStore {
+    fn load(&self) -> Option<SavedSession> {
+        let text = local_storage_get(STORAGE_KEY)?;
+        serde_json::from_str(&text).ok()
+    }
+
+    fn save(&self, session: &SavedSession) -> Result<(), String> {
+        let session = SavedSession {
+            credentials: None,
+            ..session.clone()
+        };
+        let text = serde_json::to_string(&session).map_err(|e| e.to_string())?;
+        local_storage_set(STORAGE_KEY, &text);
+        Ok(())
+    }
+}
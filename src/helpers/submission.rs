@@ -1,10 +1,12 @@
 use super::{
-    fetchers::{RequestStatus, Requestor},
+    api::{self, ApiRequest},
+    submission_history, submission_queue,
+    transport::Form,
     AppState, Languages,
 };
+use chrono::{DateTime, Utc};
 use std::fmt::Display;
 use std::sync::{Arc, Mutex};
-use web_sys::FormData;
 
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Submission {
@@ -19,74 +21,94 @@ pub struct Submission {
 }
 
 impl Submission {
-    pub fn to_formdata(&self) -> Option<FormData> {
+    pub fn to_form(&self) -> Option<Form> {
         let challenge = match &self.challenge {
             Some(challenge) => challenge,
             None => return None,
         };
 
-        let form = FormData::new().unwrap();
-        form.append_with_str("challenge", challenge).unwrap();
-        form.append_with_str("filename", &self.filename).unwrap();
-        form.append_with_str("language", &self.language.to_string())
-            .unwrap();
-        form.append_with_str("test", &self.test.to_string())
-            .unwrap();
+        let mut form = Form::new();
+        form.text("challenge", challenge);
+        form.text("filename", &self.filename);
+        form.text("language", &self.language.to_string());
+        form.text("test", &self.test.to_string());
         if let Some(code) = &self.code {
-            form.append_with_str("code", code).unwrap();
+            form.text("code", code);
         }
         if let Some(binary) = &self.binary {
-            let uint8arr =
-                js_sys::Uint8Array::new(&unsafe { js_sys::Uint8Array::view(binary) }.into());
-            let array = js_sys::Array::new();
-            array.push(&uint8arr.buffer());
-            let blob = web_sys::Blob::new_with_u8_array_sequence(array.as_ref()).unwrap();
-            form.append_with_blob("binary", &blob).unwrap();
+            form.file("binary", &self.filename, binary);
         }
 
-        log::info!("Form: {:?}", form);
-
         Some(form)
     }
 
-    pub fn check_sender(sender: &mut Option<Requestor>) -> SubmissionResult {
-        if let Some(requestor) = sender {
-            let result = &requestor.check_promise();
-
-            match result {
-                RequestStatus::Success(text) => {
-                    *sender = None;
-                    match serde_json::from_str::<SubmissionResult>(text) {
-                        Ok(submission_response) => submission_response.clone(),
-                        Err(error) => SubmissionResult::Failure {
-                            message: error.to_string(),
-                        },
-                    }
-                }
-                RequestStatus::Failed(e) => {
-                    *sender = None;
-                    SubmissionResult::Failure {
-                        message: e.to_string(),
-                    }
+    /// A submission that doesn't get to the backend is queued to be sent later,
+    /// what the judge said about one that did goes in the history.
+    pub fn check_sender(
+        sender: &mut Option<PendingSubmission>,
+        app_state: &Arc<Mutex<AppState>>,
+    ) -> SubmissionResult {
+        let (submission, result) = match sender {
+            Some(PendingSubmission::Sending {
+                submission,
+                request,
+            }) => match request.check() {
+                Some(result) => (submission.clone(), result),
+                None => return SubmissionResult::Busy,
+            },
+            Some(PendingSubmission::Queued) => {
+                *sender = None;
+                return SubmissionResult::Queued;
+            }
+            Some(PendingSubmission::Failed(message)) => {
+                let message = message.clone();
+                *sender = None;
+                return SubmissionResult::Failure { message };
+            }
+            None => return SubmissionResult::NotStarted,
+        };
+        *sender = None;
+        match result {
+            Ok(submission_response) => {
+                submission_history::record(app_state, &submission, &submission_response);
+                submission_response
+            }
+            Err(error) if submission_queue::should_queue(&error) => {
+                match submission_queue::enqueue(app_state, submission, &error.to_string()) {
+                    Ok(()) => SubmissionResult::Queued,
+                    Err(e) => SubmissionResult::Failure {
+                        message: format!("{}, and {}", error, e),
+                    },
                 }
-                RequestStatus::InProgress => SubmissionResult::Busy,
-                RequestStatus::NotStarted => SubmissionResult::NotStarted,
             }
-        } else {
-            SubmissionResult::NotStarted
+            Err(error) => SubmissionResult::Failure {
+                message: error.to_string(),
+            },
         }
     }
 
-    pub fn sender(&self, app_state: Arc<Mutex<AppState>>, url: &str) -> Option<Requestor> {
-        let mut submitter = if self.code.is_some() {
-            let submission = Some(serde_json::to_string(&self).unwrap());
-            Requestor::new_post(app_state, url, true, submission)
-        } else {
-            let submission = self.to_formdata();
-            Requestor::new_form_post(app_state, url, true, submission)
+    /* Anything already queued goes first, so this
+    waits its turn rather than overtaking it */
+    pub fn sender(&self, app_state: &Arc<Mutex<AppState>>) -> Option<PendingSubmission> {
+        let waiting = {
+            let app_state = app_state.lock().unwrap();
+            let email = app_state.identity.as_ref().map(|i| i.email.as_str());
+            app_state.submission_queue.next(email).is_some()
         };
-        submitter.send();
-        Some(submitter)
+        if waiting {
+            return match submission_queue::enqueue(
+                app_state,
+                self.clone(),
+                "Behind earlier submissions",
+            ) {
+                Ok(()) => Some(PendingSubmission::Queued),
+                Err(e) => Some(PendingSubmission::Failed(e)),
+            };
+        }
+        Some(PendingSubmission::Sending {
+            submission: self.clone(),
+            request: api::submit(app_state, self),
+        })
     }
 
     pub fn validate(&self) -> Result<(), String> {
@@ -109,6 +131,34 @@ impl Submission {
     }
 }
 
+/// A submission on its way, kept to be queued if it doesn't get there.
+pub enum PendingSubmission {
+    Sending {
+        submission: Submission,
+        request: ApiRequest<SubmissionResult>,
+    },
+    /// Went straight into the queue
+    Queued,
+    /// Had to wait behind the queue, but couldn't be queued
+    Failed(String),
+}
+
+impl PendingSubmission {
+    pub fn next_retry(&self) -> Option<DateTime<Utc>> {
+        match self {
+            PendingSubmission::Sending { request, .. } => request.next_retry(),
+            PendingSubmission::Queued | PendingSubmission::Failed(_) => None,
+        }
+    }
+
+    pub fn refresh_context(&mut self) -> bool {
+        match self {
+            PendingSubmission::Sending { request, .. } => request.refresh_context(),
+            PendingSubmission::Queued | PendingSubmission::Failed(_) => true,
+        }
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum SubmissionResult {
     #[default]
@@ -122,6 +172,8 @@ pub enum SubmissionResult {
     },
     NotAuthorized,
     Busy,
+    /// Didn't get to the backend, it is in the submission queue
+    Queued,
 }
 
 impl Display for SubmissionResult {
@@ -134,6 +186,9 @@ impl Display for SubmissionResult {
             SubmissionResult::Failure { message } => write!(f, "Failure: {}", message),
             SubmissionResult::NotAuthorized => write!(f, "Not authorized"),
             SubmissionResult::Busy => write!(f, "Busy"),
+            SubmissionResult::Queued => {
+                write!(f, "Queued, it will be sent once the backend can be reached")
+            }
         }
     }
 }
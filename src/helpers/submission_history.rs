@@ -0,0 +1,155 @@
+//! Everything submitted, and how it went.
+use super::{
+    submission::{Submission, SubmissionResult},
+    AppState, Languages,
+};
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, Mutex};
+
+/// One submission, as listed by `GET api/game/submissions`.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SubmissionRecord {
+    pub challenge: String,
+    pub filename: String,
+    pub language: Languages,
+    pub test: bool,
+    pub submitted_at: DateTime<Utc>,
+    /// Only when it passed
+    pub score: Option<u32>,
+    pub message: String,
+    /// Binaries aren't kept, so only code can be opened again
+    pub code: Option<String>,
+}
+
+impl SubmissionRecord {
+    /// `None` for anything the judge didn't answer.
+    pub fn new(
+        submission: &Submission,
+        result: &SubmissionResult,
+        at: DateTime<Utc>,
+    ) -> Option<Self> {
+        let (score, message) = match result {
+            SubmissionResult::Success { score, message } => (Some(*score), message.clone()),
+            SubmissionResult::Failure { message } => (None, message.clone()),
+            _ => return None,
+        };
+        Some(Self {
+            challenge: submission.challenge.clone().unwrap_or_default(),
+            filename: submission.filename.clone(),
+            language: submission.language,
+            test: submission.test,
+            submitted_at: at,
+            score,
+            message,
+            code: submission.code.clone(),
+        })
+    }
+
+    pub fn to_submission(&self) -> Submission {
+        Submission {
+            challenge: Some(self.challenge.clone()),
+            filename: self.filename.clone(),
+            language: self.language,
+            test: self.test,
+            code: self.code.clone(),
+            binary: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SubmissionHistory {
+    /// Oldest first
+    records: Vec<SubmissionRecord>,
+    /// When the backend last listed them
+    pub fetched_at: Option<DateTime<Utc>>,
+}
+
+impl SubmissionHistory {
+    pub fn record(&mut self, record: SubmissionRecord) {
+        self.records.push(record);
+    }
+
+    /* The backend's list is the real one, only what was
+    judged since it was made is kept from before */
+    pub fn replace(&mut self, mut records: Vec<SubmissionRecord>, now: DateTime<Utc>) {
+        records.sort_by_key(|record| record.submitted_at);
+        if let Some(newest) = records.last().map(|record| record.submitted_at) {
+            self.records.retain(|record| record.submitted_at > newest);
+        }
+        records.append(&mut self.records);
+        self.records = records;
+        self.fetched_at = Some(now);
+    }
+
+    pub fn records(&self) -> &[SubmissionRecord] {
+        &self.records
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+/// Keeps the judge's answer to `submission`, in the offline cache too.
+pub fn record(
+    app_state: &Arc<Mutex<AppState>>,
+    submission: &Submission,
+    result: &SubmissionResult,
+) {
+    if let Some(record) = SubmissionRecord::new(submission, result, Utc::now()) {
+        let mut app_state = app_state.lock().unwrap();
+        app_state.submission_history.record(record);
+        app_state.cache_dirty = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history() {
+        let at = |time: &str| time.parse::<DateTime<Utc>>().unwrap();
+        let submission = Submission {
+            challenge: Some("2331".to_string()),
+            filename: "odds.py".to_string(),
+            language: Languages::Python,
+            code: Some("print(7)".to_string()),
+            ..Default::default()
+        };
+        let passed = SubmissionResult::Success {
+            score: 1500,
+            message: "Passed all tests".to_string(),
+        };
+        assert!(SubmissionRecord::new(
+            &submission,
+            &SubmissionResult::Queued,
+            at("2024-01-01T00:00:00Z")
+        )
+        .is_none());
+        let record =
+            SubmissionRecord::new(&submission, &passed, at("2024-01-02T00:00:00Z")).unwrap();
+        assert_eq!(record.score, Some(1500));
+        assert_eq!(record.to_submission(), submission);
+
+        let mut history = SubmissionHistory::default();
+        history.record(record.clone());
+        let later = SubmissionRecord {
+            submitted_at: at("2024-01-04T00:00:00Z"),
+            ..record.clone()
+        };
+        history.record(later.clone());
+
+        // the backend knows about the first but not yet the later one
+        let fetched = vec![SubmissionRecord {
+            submitted_at: at("2024-01-03T00:00:00Z"),
+            ..record
+        }];
+        history.replace(fetched.clone(), at("2024-01-04T00:00:01Z"));
+        assert_eq!(history.records(), &[fetched[0].clone(), later]);
+        assert!(history.fetched_at.is_some());
+    }
+}
@@ -0,0 +1,235 @@
+//! Submissions that didn't reach the backend, sent again in order once it can be reached.
+use super::{api::ApiError, submission::Submission, AppState};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, Mutex};
+
+/// Bigger binaries aren't kept, they would fill up local storage
+pub const MAX_QUEUED_BINARY: usize = 1024 * 1024;
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct QueuedSubmission {
+    pub id: u64,
+    /// Email of who queued it, it is only sent while they are logged in
+    pub owner: String,
+    submission: Submission,
+    /// Base64, [`Submission`] leaves binaries out when serialised
+    binary: Option<String>,
+    pub queued_at: DateTime<Utc>,
+    pub attempts: u32,
+    /// Why the last attempt didn't go through
+    pub error: Option<String>,
+    /// Turned away by the backend, it waits to be edited or sent by hand
+    pub held: bool,
+}
+
+impl QueuedSubmission {
+    fn set_submission(&mut self, mut submission: Submission) {
+        self.binary = submission.binary.take().map(|bytes| STANDARD.encode(bytes));
+        self.submission = submission;
+    }
+
+    /// The submission as it will be sent, binary included.
+    pub fn submission(&self) -> Submission {
+        let binary = self
+            .binary
+            .as_ref()
+            .and_then(|binary| STANDARD.decode(binary).ok());
+        Submission {
+            binary,
+            ..self.submission.clone()
+        }
+    }
+
+    pub fn belongs_to(&self, email: Option<&str>) -> bool {
+        email == Some(self.owner.as_str())
+    }
+
+    /// e.g. "Test of odds.py for 2331"
+    pub fn describe(&self) -> String {
+        let kind = match self.submission.test {
+            true => "Test",
+            false => "Submission",
+        };
+        format!(
+            "{} of {} for {}",
+            kind,
+            self.submission.filename,
+            self.submission
+                .challenge
+                .as_deref()
+                .unwrap_or("no challenge")
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SubmissionQueue {
+    items: Vec<QueuedSubmission>,
+    next_id: u64,
+    /// Being sent right now
+    #[serde(skip)]
+    pub sending: Option<u64>,
+    /// When the queue will be tried again, while the backend is away
+    #[serde(skip)]
+    pub next_attempt: Option<DateTime<Utc>>,
+    /// Asked to be sent without waiting
+    #[serde(skip)]
+    pub wake: bool,
+}
+
+impl SubmissionQueue {
+    pub fn push(&mut self, submission: Submission, error: &str, owner: &str) -> u64 {
+        self.next_id += 1;
+        let mut item = QueuedSubmission {
+            id: self.next_id,
+            owner: owner.to_string(),
+            queued_at: Utc::now(),
+            error: Some(error.to_string()),
+            ..Default::default()
+        };
+        item.set_submission(submission);
+        log::warn!("Queued {}: {}", item.describe(), error);
+        self.items.push(item);
+        self.next_id
+    }
+
+    pub fn items(&self) -> &[QueuedSubmission] {
+        &self.items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// The oldest submission `email` can send.
+    pub fn next(&self, email: Option<&str>) -> Option<&QueuedSubmission> {
+        self.items
+            .iter()
+            .find(|item| !item.held && item.belongs_to(email))
+    }
+
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut QueuedSubmission> {
+        self.items.iter_mut().find(|item| item.id == id)
+    }
+
+    pub fn remove(&mut self, id: u64) -> Option<QueuedSubmission> {
+        let index = self.items.iter().position(|item| item.id == id)?;
+        Some(self.items.remove(index))
+    }
+
+    /// Swaps in an edited submission, it keeps its place in the queue.
+    pub fn replace(&mut self, id: u64, submission: Submission) {
+        if let Some(item) = self.get_mut(id) {
+            item.set_submission(submission);
+            item.held = false;
+            item.error = None;
+        }
+    }
+
+    /// Tries everything again straight away, held submissions too.
+    pub fn send_now(&mut self) {
+        for item in &mut self.items {
+            item.held = false;
+        }
+        self.wake = true;
+    }
+}
+
+/* Only what never got to be judged is queued. A submission the
+backend answered, even with an error, would just fail the same again */
+pub fn should_queue(error: &ApiError) -> bool {
+    matches!(
+        error,
+        ApiError::Network(_)
+            | ApiError::Http {
+                status: 429 | 503,
+                ..
+            }
+    )
+}
+
+/// Queues `submission` behind any others of whoever is logged in, and marks the queue to be saved.
+/// It just failed, so the first try is after the retry delay.
+pub fn enqueue(
+    app_state: &Arc<Mutex<AppState>>,
+    submission: Submission,
+    error: &str,
+) -> Result<(), String> {
+    let size = submission.binary.as_ref().map_or(0, Vec::len);
+    if size > MAX_QUEUED_BINARY {
+        return Err(format!(
+            "{} is too big to queue, at most {} KiB",
+            submission.filename,
+            MAX_QUEUED_BINARY / 1024
+        ));
+    }
+    let mut app_state = app_state.lock().unwrap();
+    let first_try = Utc::now() + app_state.config.retry.delay(0);
+    let owner = app_state
+        .identity
+        .as_ref()
+        .map(|identity| identity.email.clone())
+        .unwrap_or_default();
+    let queue = &mut app_state.submission_queue;
+    queue.push(submission, error, &owner);
+    if queue.sending.is_none() && queue.next_attempt.is_none() {
+        queue.next_attempt = Some(first_try);
+    }
+    app_state.queue_dirty = true;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OWNER: &str = "player@dummy.com";
+
+    #[test]
+    fn test_queue() {
+        let mut queue = SubmissionQueue::default();
+        let binary = Submission {
+            challenge: Some("2331".to_string()),
+            filename: "odds".to_string(),
+            binary: Some(vec![0x7f, b'E', b'L', b'F']),
+            ..Default::default()
+        };
+        let first = queue.push(binary.clone(), "Network error: Connection refused", OWNER);
+        let code = Submission {
+            code: Some("print(1)".to_string()),
+            ..binary.clone()
+        };
+        let second = queue.push(code, "Service unavailable (503)", OWNER);
+        // someone else's waits for them to log in
+        queue.push(binary.clone(), "Network error", "admin@dummy.com");
+
+        // the binary survives being saved
+        let saved = serde_json::to_string(&queue).unwrap();
+        let mut queue: SubmissionQueue = serde_json::from_str(&saved).unwrap();
+        assert_eq!(queue.next(Some(OWNER)).unwrap().submission(), binary);
+
+        queue.get_mut(first).unwrap().held = true;
+        assert_eq!(queue.next(Some(OWNER)).unwrap().id, second);
+        queue.replace(first, binary);
+        assert_eq!(queue.next(Some(OWNER)).unwrap().id, first);
+        assert_eq!(
+            queue.next(Some(OWNER)).unwrap().describe(),
+            "Submission of odds for 2331"
+        );
+
+        queue.remove(first);
+        queue.remove(second);
+        assert_eq!(queue.items().len(), 1);
+        assert!(queue.next(Some(OWNER)).is_none());
+        assert!(queue.next(None).is_none());
+        assert!(queue.next(Some("admin@dummy.com")).is_some());
+        assert!(should_queue(&ApiError::Network("timed out".to_string())));
+        assert!(!should_queue(&ApiError::Http {
+            status: 400,
+            message: "Unknown challenge".to_string()
+        }));
+    }
+}
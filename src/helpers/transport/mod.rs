@@ -0,0 +1,166 @@
+#[cfg(not(target_arch = "wasm32"))]
+mod native;
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::NativeTransport;
+#[cfg(target_arch = "wasm32")]
+mod web;
+#[cfg(target_arch = "wasm32")]
+pub use web::WebTransport;
+
+use poll_promise::Promise;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HttpMethod {
+    Get,
+    Post,
+}
+
+impl std::fmt::Display for HttpMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HttpMethod::Get => write!(f, "GET"),
+            HttpMethod::Post => write!(f, "POST"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FormValue {
+    Text(String),
+    File { filename: String, bytes: Vec<u8> },
+}
+
+/// A multipart form that can be sent by any transport.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Form {
+    pub fields: Vec<(String, FormValue)>,
+}
+
+impl Form {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(&mut self, name: &str, value: &str) {
+        self.fields
+            .push((name.to_string(), FormValue::Text(value.to_string())));
+    }
+
+    pub fn file(&mut self, name: &str, filename: &str, bytes: &[u8]) {
+        self.fields.push((
+            name.to_string(),
+            FormValue::File {
+                filename: filename.to_string(),
+                bytes: bytes.to_vec(),
+            },
+        ));
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Body {
+    #[default]
+    Empty,
+    Json(String),
+    Form(Form),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpRequest {
+    pub method: HttpMethod,
+    pub url: String,
+    pub with_credentials: bool,
+    /// Whose cookies go with it, a [`Config::session_key`](super::Config::session_key)
+    pub session: String,
+    pub body: Body,
+    /// Give up on the request if it takes longer than this
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Header names are case insensitive.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).to_string()
+    }
+}
+
+pub type ResponsePromise = Promise<Result<HttpResponse, String>>;
+
+/// A request that has been sent, it is cancelled when dropped before it completes.
+pub struct InFlight {
+    promise: ResponsePromise,
+    abort: Option<Box<dyn FnOnce()>>,
+}
+
+impl InFlight {
+    pub fn new(promise: ResponsePromise) -> Self {
+        Self {
+            promise,
+            abort: None,
+        }
+    }
+
+    /// Called to stop the request, if the transport is able to.
+    pub fn on_cancel(mut self, abort: impl FnOnce() + 'static) -> Self {
+        self.abort = Some(Box::new(abort));
+        self
+    }
+
+    pub fn ready(&self) -> Option<&Result<HttpResponse, String>> {
+        self.promise.ready()
+    }
+}
+
+/* Without a way to abort, the response is still received
+but nobody is left to look at it */
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if self.promise.ready().is_none() {
+            if let Some(abort) = self.abort.take() {
+                abort();
+            }
+        }
+    }
+}
+
+/// Sends HTTP requests for [`Requestor`](super::fetchers::Requestor).
+///
+/// Cookies must be kept and sent for requests made `with_credentials`,
+/// the backend relies on them for the access and refresh tokens.
+pub trait Transport {
+    fn fetch(&self, request: HttpRequest) -> InFlight;
+
+    /// The cookies to keep between runs, when they're held by the transport and not a browser.
+    fn credentials(&self) -> Option<String> {
+        None
+    }
+
+    /// Takes back what [`Transport::credentials`] gave.
+    fn restore_credentials(&self, _credentials: &str) {}
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn default_transport() -> Arc<dyn Transport> {
+    Arc::new(NativeTransport::default())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn default_transport() -> Arc<dyn Transport> {
+    Arc::new(WebTransport)
+}
@@ -0,0 +1,165 @@
+use super::{Body, Form, FormValue, HttpRequest, HttpResponse, InFlight};
+use poll_promise::Promise;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+
+/// Blocking `ureq` requests run on a background thread each.
+///
+/// Credentialed requests share an agent per session so the auth cookies set by the
+/// backend are sent back on the following requests, like a browser would.
+#[derive(Clone)]
+pub struct NativeTransport {
+    /* By session key. Cookies don't care about the port, so profiles on
+    localhost:3000 and localhost:4000 would otherwise share their logins.
+    Replaced when credentials are restored, an agent's cookies can't be */
+    credentialed: Arc<Mutex<BTreeMap<String, ureq::Agent>>>,
+    anonymous: ureq::Agent,
+}
+
+impl Default for NativeTransport {
+    fn default() -> Self {
+        Self {
+            credentialed: Arc::default(),
+            anonymous: ureq::AgentBuilder::new().build(),
+        }
+    }
+}
+
+impl NativeTransport {
+    fn agent_for(&self, session: &str) -> ureq::Agent {
+        self.credentialed
+            .lock()
+            .unwrap()
+            .entry(session.to_string())
+            .or_insert_with(|| ureq::AgentBuilder::new().build())
+            .clone()
+    }
+}
+
+impl super::Transport for NativeTransport {
+    /* A blocking request can't be interrupted,
+    cancelling only drops the promise it reports to */
+    fn fetch(&self, request: HttpRequest) -> InFlight {
+        let agent = match request.with_credentials {
+            true => self.agent_for(&request.session),
+            false => self.anonymous.clone(),
+        };
+        InFlight::new(Promise::spawn_thread("http_request", move || {
+            send(&agent, request)
+        }))
+    }
+
+    /* Session cookies are kept as well, the backend's refresh token
+    is one and we stand in for a browser that is never closed */
+    fn credentials(&self) -> Option<String> {
+        let mut sessions = BTreeMap::new();
+        for (session, agent) in self.credentialed.lock().unwrap().iter() {
+            let mut json = Vec::new();
+            agent
+                .cookie_store()
+                .save_incl_expired_and_nonpersistent_json(&mut json)
+                .ok()?;
+            sessions.insert(session.clone(), String::from_utf8(json).ok()?);
+        }
+        serde_json::to_string(&sessions).ok()
+    }
+
+    fn restore_credentials(&self, credentials: &str) {
+        let sessions: BTreeMap<String, String> = match serde_json::from_str(credentials) {
+            Ok(sessions) => sessions,
+            Err(e) => {
+                log::warn!("Ignoring saved credentials: {}", e);
+                return;
+            }
+        };
+        let mut agents = self.credentialed.lock().unwrap();
+        for (session, cookies) in sessions {
+            match cookie_store::CookieStore::load_json_all(cookies.as_bytes()) {
+                Ok(cookies) => {
+                    let agent = ureq::AgentBuilder::new().cookie_store(cookies).build();
+                    agents.insert(session, agent);
+                }
+                Err(e) => log::warn!("Ignoring saved credentials for {}: {}", session, e),
+            }
+        }
+    }
+}
+
+fn send(agent: &ureq::Agent, request: HttpRequest) -> Result<HttpResponse, String> {
+    let mut builder = agent.request(&request.method.to_string(), &request.url);
+    if let Some(timeout) = request.timeout {
+        builder = builder.timeout(timeout);
+    }
+    let result = match request.body {
+        Body::Empty => builder.call(),
+        Body::Json(data) => builder
+            .set("Content-Type", "application/json")
+            .send_string(&data),
+        Body::Form(form) => {
+            let (content_type, data) = encode_multipart(&form);
+            builder.set("Content-Type", &content_type).send_bytes(&data)
+        }
+    };
+
+    let response = match result {
+        Ok(response) => response,
+        Err(ureq::Error::Status(_, response)) => response,
+        Err(e) => return Err(e.to_string()),
+    };
+
+    let status = response.status();
+    let headers = response
+        .headers_names()
+        .into_iter()
+        .filter_map(|name| {
+            let value = response.header(&name)?.to_string();
+            Some((name, value))
+        })
+        .collect();
+    let mut body = Vec::new();
+    response
+        .into_reader()
+        .read_to_end(&mut body)
+        .map_err(|e| e.to_string())?;
+    Ok(HttpResponse {
+        status,
+        headers,
+        body,
+    })
+}
+
+fn encode_multipart(form: &Form) -> (String, Vec<u8>) {
+    let boundary = format!(
+        "----challenge-frontend-{}",
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    );
+
+    let mut data = Vec::new();
+    for (name, value) in &form.fields {
+        data.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+        match value {
+            FormValue::Text(text) => {
+                data.extend_from_slice(
+                    format!("Content-Disposition: form-data; name=\"{}\"\r\n\r\n", name).as_bytes(),
+                );
+                data.extend_from_slice(text.as_bytes());
+            }
+            FormValue::File { filename, bytes } => {
+                data.extend_from_slice(
+                    format!(
+                        "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n",
+                        name, filename
+                    )
+                    .as_bytes(),
+                );
+                data.extend_from_slice(b"Content-Type: application/octet-stream\r\n\r\n");
+                data.extend_from_slice(bytes);
+            }
+        }
+        data.extend_from_slice(b"\r\n");
+    }
+    data.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+
+    (format!("multipart/form-data; boundary={}", boundary), data)
+}
@@ -0,0 +1,87 @@
+use super::{Body, Form, FormValue, HttpMethod, HttpRequest, HttpResponse, InFlight};
+use gloo_net::http;
+use poll_promise::Promise;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{AbortController, FormData, RequestCredentials};
+
+/// Uses the browser's `fetch`, cookies are handled by the browser.
+pub struct WebTransport;
+
+impl super::Transport for WebTransport {
+    fn fetch(&self, request: HttpRequest) -> InFlight {
+        let controller = match AbortController::new() {
+            Ok(controller) => controller,
+            Err(e) => return InFlight::new(Promise::from_ready(Err(format!("{:?}", e)))),
+        };
+        let signal = controller.signal();
+        if let Some(timeout) = request.timeout {
+            abort_after(controller.clone(), timeout);
+        }
+
+        let promise = Promise::spawn_local(async move {
+            let builder = match request.method {
+                HttpMethod::Get => http::Request::get(&request.url),
+                HttpMethod::Post => http::Request::post(&request.url),
+            };
+            let builder = match request.with_credentials {
+                true => builder.credentials(RequestCredentials::Include),
+                false => builder,
+            };
+            let builder = builder.abort_signal(Some(&signal));
+            let request = match request.body {
+                Body::Empty => builder.build(),
+                Body::Json(data) => builder
+                    .header("Content-Type", "application/json")
+                    .body(data),
+                Body::Form(form) => builder.body(to_formdata(&form)?),
+            }
+            .map_err(|e| e.to_string())?;
+
+            let response = request.send().await.map_err(|e| e.to_string())?;
+            let body = response.binary().await.map_err(|e| e.to_string())?;
+            Ok(HttpResponse {
+                status: response.status(),
+                headers: response.headers().entries().collect(),
+                body,
+            })
+        });
+        InFlight::new(promise).on_cancel(move || controller.abort())
+    }
+}
+
+/* fetch has no timeout of its own, abort it instead.
+Aborting a request that has already finished does nothing */
+fn abort_after(controller: AbortController, timeout: std::time::Duration) {
+    wasm_bindgen_futures::spawn_local(async move {
+        let delay = js_sys::Promise::new(&mut |resolve, _| {
+            if let Some(window) = web_sys::window() {
+                let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                    &resolve,
+                    timeout.as_millis() as i32,
+                );
+            }
+        });
+        let _ = JsFuture::from(delay).await;
+        controller.abort();
+    });
+}
+
+fn to_formdata(form: &Form) -> Result<FormData, String> {
+    let data = FormData::new().map_err(|e| format!("{:?}", e))?;
+    for (name, value) in &form.fields {
+        match value {
+            FormValue::Text(text) => data.append_with_str(name, text),
+            FormValue::File { filename, bytes } => {
+                let uint8arr =
+                    js_sys::Uint8Array::new(&unsafe { js_sys::Uint8Array::view(bytes) }.into());
+                let array = js_sys::Array::new();
+                array.push(&uint8arr.buffer());
+                let blob = web_sys::Blob::new_with_u8_array_sequence(array.as_ref())
+                    .map_err(|e| format!("{:?}", e))?;
+                data.append_with_blob_and_filename(name, &blob, filename)
+            }
+        }
+        .map_err(|e| format!("{:?}", e))?;
+    }
+    Ok(data)
+}
@@ -0,0 +1,128 @@
+use super::Languages;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+/// Avatars are shown small, bigger uploads are turned away before sending.
+pub const MAX_AVATAR_BYTES: usize = 512 * 1024;
+const AVATAR_SIZE: u32 = 128;
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Notifications {
+    /// Email when a new challenge is published
+    pub new_challenges: bool,
+    /// Email when someone beats one of your scores
+    pub beaten_scores: bool,
+    /// Email the results of every submission
+    pub submission_results: bool,
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self {
+            new_challenges: true,
+            beaten_scores: true,
+            submission_results: false,
+        }
+    }
+}
+
+/// What a player can change about themselves, `api/users/me/profile`.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct UserProfile {
+    /// Shown on the scoreboards
+    pub name: String,
+    /// Picked when opening the code editor or file upload
+    pub language: Languages,
+    pub notifications: Notifications,
+    /// Base64 encoded image, it's uploaded on its own
+    #[serde(skip_serializing)]
+    pub avatar: Option<String>,
+}
+
+impl UserProfile {
+    pub fn validate(&self) -> Result<(), String> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err("Display name is empty".to_string());
+        }
+        if name.chars().count() > 32 {
+            return Err("Display name is longer than 32 characters".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn set_avatar(&mut self, bytes: &[u8]) {
+        self.avatar = Some(STANDARD.encode(bytes));
+    }
+
+    /// The avatar scaled down, ready to upload into a texture.
+    pub fn avatar_image(&self) -> Option<Result<egui::ColorImage, String>> {
+        let bytes = STANDARD.decode(self.avatar.as_ref()?.trim());
+        Some(
+            bytes
+                .map_err(|e| format!("Avatar is not base64: {}", e))
+                .and_then(|bytes| decode_avatar(&bytes)),
+        )
+    }
+}
+
+/// Checks an image is fit to be an avatar before it is uploaded.
+pub fn decode_avatar(bytes: &[u8]) -> Result<egui::ColorImage, String> {
+    if bytes.len() > MAX_AVATAR_BYTES {
+        return Err(format!(
+            "Avatars can be at most {} KiB",
+            MAX_AVATAR_BYTES / 1024
+        ));
+    }
+    let image = image::load_from_memory(bytes)
+        .map_err(|e| format!("Not a PNG or JPEG image: {}", e))?
+        .thumbnail(AVATAR_SIZE, AVATAR_SIZE)
+        .to_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    Ok(egui::ColorImage::from_rgba_unmultiplied(
+        size,
+        image.as_flat_samples().as_slice(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        image::RgbaImage::new(width, height)
+            .write_to(&mut bytes, image::ImageOutputFormat::Png)
+            .unwrap();
+        bytes.into_inner()
+    }
+
+    #[test]
+    fn test_avatar() {
+        let mut profile = UserProfile::default();
+        assert!(profile.avatar_image().is_none());
+
+        profile.set_avatar(&png(512, 256));
+        let avatar = profile.avatar_image().unwrap().unwrap();
+        assert_eq!(avatar.size, [128, 64]);
+
+        assert!(decode_avatar(b"not an image").is_err());
+        profile.avatar = Some("!!".to_string());
+        assert!(profile.avatar_image().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_profile_json() {
+        let profile: UserProfile = serde_json::from_str(
+            r#"{ "name": "Player One", "language": "Rust", "avatar": "AAAA" }"#,
+        )
+        .unwrap();
+        assert_eq!(profile.language, Languages::Rust);
+        assert_eq!(profile.notifications, Notifications::default());
+
+        let json = serde_json::to_value(&profile).unwrap();
+        assert!(json.get("avatar").is_none());
+        assert_eq!(json["notifications"]["new_challenges"], true);
+    }
+}
@@ -1,14 +1,15 @@
 #![warn(clippy::all, rust_2018_idioms)]
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
-mod apps;
-mod components;
-mod helpers;
+use challenge_frontend::helpers::console_log;
 
 // When compiling natively:
 #[cfg(not(target_arch = "wasm32"))]
 fn main() -> eframe::Result<()> {
-    env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
+    // Log to stderr (if you run with `RUST_LOG=debug`) and the console window
+    let logger = env_logger::Builder::from_default_env().build();
+    let level = logger.filter();
+    console_log::init(Box::new(logger), level).ok();
 
     let native_options = eframe::NativeOptions {
         initial_window_size: Some([400.0, 300.0].into()),
@@ -25,8 +26,9 @@ fn main() -> eframe::Result<()> {
 // When compiling to web using trunk:
 #[cfg(target_arch = "wasm32")]
 fn main() {
-    // Redirect `log` message to `console.log` and friends:
-    eframe::WebLogger::init(log::LevelFilter::Debug).ok();
+    // Redirect `log` message to `console.log` and friends, and the console window:
+    let logger = eframe::WebLogger::new(log::LevelFilter::Debug);
+    console_log::init(Box::new(logger), log::LevelFilter::Debug).ok();
 
     let web_options = eframe::WebOptions::default();
     log::debug!("Starting eframe on wasm32");
@@ -1,13 +1,18 @@
 use crate::{
     apps::{self},
-    background_processes::{ChallengeFetcher, LoginFetcher},
+    background_processes::{ChallengeFetcher, LoginFetcher, SubmissionSender},
     code_editor,
-    helpers::AppState,
+    helpers::{
+        config, deep_link::DeepLink, offline_cache, retry, session, AppState, Config, LoginState,
+    },
 };
 #[cfg(target_arch = "wasm32")]
 use core::any::Any;
 use std::sync::{Arc, Mutex};
 
+/// How long changes to the offline cache wait to be written out
+const CACHE_FLUSH_SECS: i64 = 5;
+
 #[derive(serde::Deserialize, serde::Serialize, Default)]
 struct CodeEditorApp {
     pub editor: code_editor::CodeEditor,
@@ -88,6 +93,14 @@ pub struct WrapApp {
     challenge_fetcher: ChallengeFetcher,
     #[serde(skip)]
     login_fetcher: LoginFetcher,
+    #[serde(skip)]
+    submission_sender: SubmissionSender,
+    /// Location hash the last link was followed from
+    #[serde(skip)]
+    last_hash: String,
+    /// When the offline cache was last written out
+    #[serde(skip)]
+    cache_flushed_at: chrono::DateTime<chrono::Utc>,
 }
 
 impl Default for WrapApp {
@@ -98,20 +111,38 @@ impl Default for WrapApp {
             app_state,
             challenge_fetcher: ChallengeFetcher::default(),
             login_fetcher: LoginFetcher::default(),
+            submission_sender: SubmissionSender::default(),
+            last_hash: String::new(),
+            cache_flushed_at: chrono::Utc::now(),
         }
     }
 }
 
 impl WrapApp {
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        let app_state = AppState::default();
+        let mut app_state = AppState::default();
+        app_state.config = Config::load(cc);
+        app_state.session_store = session::default_store();
+        session::restore(&mut app_state);
+        app_state.cache_store = offline_cache::default_store();
+        app_state.queue_store = offline_cache::default_queue_store();
+        app_state.audit_store = offline_cache::default_audit_store();
+        offline_cache::restore(&mut app_state);
         let app_state = Arc::new(Mutex::new(app_state));
 
+        let state = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
+            .unwrap_or_default();
+
         let mut slf = Self {
-            state: State::default(),
+            state,
             app_state: Arc::clone(&app_state),
             challenge_fetcher: ChallengeFetcher::new(app_state.clone()),
             login_fetcher: LoginFetcher::new(app_state.clone()),
+            submission_sender: SubmissionSender::new(app_state.clone()),
+            last_hash: String::new(),
+            cache_flushed_at: chrono::Utc::now(),
             #[cfg(any(feature = "glow", feature = "wgpu"))]
             custom3d: crate::apps::Custom3d::new(cc),
         };
@@ -122,15 +153,24 @@ impl WrapApp {
             .windows
             .set_app_state_ref(Arc::clone(&app_state));
 
-        if let Some(storage) = cc.storage {
-            if let Some(state) = eframe::get_value(storage, eframe::APP_KEY) {
-                slf.state = state;
-            }
-        }
-
         slf
     }
 
+    /* A link is followed once, when the hash changes,
+    so the window it opened can be closed again */
+    #[allow(dead_code)] //inhibit warnings when target =/= WASM
+    fn follow_link(&mut self, hash: &str) {
+        if hash == self.last_hash {
+            return;
+        }
+        self.last_hash = hash.to_string();
+        if let Some(link) = DeepLink::parse(hash) {
+            log::info!("Following {} link", link);
+            self.state.selected_anchor = Anchor::Landing;
+            self.app_state.lock().unwrap().deep_link = Some(link);
+        }
+    }
+
     fn apps_iter_mut(&mut self) -> impl Iterator<Item = (&str, Anchor, &mut dyn eframe::App)> {
         let vec = vec![
             (
@@ -149,9 +189,28 @@ impl WrapApp {
 }
 
 impl eframe::App for WrapApp {
-    #[cfg(feature = "persistence")]
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
         eframe::set_value(storage, eframe::APP_KEY, &self.state);
+        eframe::set_value(
+            storage,
+            config::CONFIG_KEY,
+            &self.app_state.lock().unwrap().config.to_saved(),
+        );
+        /* The refresh token may have been replaced since it was last saved.
+        Logging out saves on its own, and a session that couldn't be checked
+        while offline is left alone */
+        let snapshot = {
+            let app_state = self.app_state.lock().unwrap();
+            matches!(app_state.logged_in, LoginState::LoggedIn)
+                .then(|| session::SessionSnapshot::of(&app_state))
+        };
+        if let Some(snapshot) = snapshot {
+            snapshot.write();
+        }
+        if let Err(e) = offline_cache::flush(&self.app_state) {
+            self.submission_sender.persist_failed(&e);
+        }
+        self.cache_flushed_at = chrono::Utc::now();
     }
 
     fn clear_color(&self, visuals: &egui::Visuals) -> [f32; 4] {
@@ -159,6 +218,8 @@ impl eframe::App for WrapApp {
     }
 
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        #[cfg(target_arch = "wasm32")]
+        self.follow_link(&frame.info().web_info.location.hash);
         #[cfg(target_arch = "wasm32")]
         if let Some(anchor) = frame.info().web_info.location.hash.strip_prefix('#') {
             let anchor = Anchor::all().into_iter().find(|x| x.to_string() == anchor);
@@ -169,6 +230,12 @@ impl eframe::App for WrapApp {
 
         self.challenge_fetcher.tick();
         self.login_fetcher.tick();
+        self.submission_sender.tick(ctx);
+        self.flush_cache(ctx);
+        retry::repaint_while_waiting(ctx, self.challenge_fetcher.next_retry());
+        if let Some(at) = self.challenge_fetcher.next_refresh() {
+            ctx.request_repaint_after((at - chrono::Utc::now()).to_std().unwrap_or_default());
+        }
 
         #[cfg(not(target_arch = "wasm32"))]
         if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::F11)) {
@@ -181,6 +248,7 @@ impl eframe::App for WrapApp {
                 self.bar_contents(ui, frame);
             });
         });
+        self.offline_banner(ctx);
 
         self.show_selected_app(ctx, frame);
 
@@ -204,6 +272,51 @@ impl eframe::App for WrapApp {
 }
 
 impl WrapApp {
+    /* Writing the cache out is left until a few changes have piled up,
+    instead of a whole file on every scoreboard fetched */
+    fn flush_cache(&mut self, ctx: &egui::Context) {
+        if !self.app_state.lock().unwrap().has_unsaved() {
+            return;
+        }
+        let due = self.cache_flushed_at + chrono::Duration::seconds(CACHE_FLUSH_SECS);
+        match due <= chrono::Utc::now() {
+            true => {
+                if let Err(e) = offline_cache::flush(&self.app_state) {
+                    self.submission_sender.persist_failed(&e);
+                }
+                self.cache_flushed_at = chrono::Utc::now();
+            }
+            false => {
+                ctx.request_repaint_after((due - chrono::Utc::now()).to_std().unwrap_or_default())
+            }
+        }
+    }
+
+    /* Everything shown was cached when the backend last answered,
+    it is replaced as soon as it answers again */
+    fn offline_banner(&self, ctx: &egui::Context) {
+        let (offline, fetched_at) = {
+            let app_state = self.app_state.lock().unwrap();
+            (
+                app_state.offline_since.is_some(),
+                app_state.challenges.fetched_at,
+            )
+        };
+        if !offline {
+            return;
+        }
+        let text = match fetched_at {
+            Some(at) => format!(
+                "⚠ Offline, showing the last challenges and scores fetched, {}",
+                offline_cache::stale_since(at)
+            ),
+            None => "⚠ Offline, nothing has been fetched from this backend yet".to_string(),
+        };
+        egui::TopBottomPanel::top("wrap_app_offline_banner").show(ctx, |ui| {
+            ui.colored_label(ui.visuals().warn_fg_color, text);
+        });
+    }
+
     fn bar_contents(&mut self, ui: &mut egui::Ui, frame: &mut eframe::Frame) {
         egui::widgets::global_dark_light_mode_switch(ui);
 
@@ -226,9 +339,44 @@ impl WrapApp {
 
         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
             egui::warn_if_debug_build(ui);
+            self.profile_switcher(ui);
+            if let Some(at) = self.challenge_fetcher.next_retry() {
+                ui.label(format!("⚠ Backend unavailable, {}", retry::countdown(at)));
+            }
         });
     }
 
+    fn profile_switcher(&mut self, ui: &mut egui::Ui) {
+        let (profiles, mut selected) = {
+            let app_state = self.app_state.lock().unwrap();
+            let profiles: Vec<String> = app_state
+                .config
+                .profiles()
+                .iter()
+                .map(|p| p.name.clone())
+                .collect();
+            (profiles, app_state.config.active_profile().to_string())
+        };
+        if profiles.len() < 2 {
+            return;
+        }
+
+        egui::ComboBox::from_id_source("profile_switcher")
+            .selected_text(format!("🖧 {}", selected))
+            .show_ui(ui, |ui| {
+                for profile in profiles {
+                    ui.selectable_value(&mut selected, profile.clone(), profile);
+                }
+            });
+
+        let mut app_state = self.app_state.lock().unwrap();
+        if selected != app_state.config.active_profile() {
+            if let Err(e) = app_state.switch_profile(&selected) {
+                log::error!("Failed to switch profile: {}", e);
+            }
+        }
+    }
+
     fn show_selected_app(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         let selected_anchor = self.state.selected_anchor;
         for (_name, anchor, app) in self.apps_iter_mut() {
@@ -0,0 +1,571 @@
+//! Drives the native client against the mock backend.
+#![cfg(not(target_arch = "wasm32"))]
+
+mod mock_backend;
+
+use challenge_frontend::background_processes::SubmissionSender;
+use challenge_frontend::helpers::{
+    admin::AdminAction,
+    api::{self, ApiError, ApiRequest, LoginResponse},
+    config::Profile,
+    retry::RetryPolicy,
+    session::{self, Identity, MemoryStore, SessionStore},
+    submission::{PendingSubmission, Submission, SubmissionResult},
+    submission_queue,
+    user_profile::UserProfile,
+    AppState, Attachment, ChallengeDraft, Languages, LoginState, Publication,
+};
+use mock_backend::{MockBackend, Reply, ADMIN_EMAIL, EMAIL, PASSWORD, SAMPLE};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+fn app_state(backend: &MockBackend) -> Arc<Mutex<AppState>> {
+    let mut app_state = AppState::default();
+    app_state.config.set_backend_url(backend.url()).unwrap();
+    app_state.config.retry = RetryPolicy {
+        max_retries: 2,
+        base_delay_ms: 10,
+        max_delay_ms: 50,
+        timeout_ms: 2000,
+    };
+    Arc::new(Mutex::new(app_state))
+}
+
+/// Polls the request the way a window would, every frame.
+fn wait<T>(mut request: ApiRequest<T>) -> Result<T, ApiError> {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while Instant::now() < deadline {
+        if let Some(result) = request.check() {
+            return result;
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    panic!("request never completed");
+}
+
+fn login(app_state: &Arc<Mutex<AppState>>) {
+    let response = wait(api::login(app_state, EMAIL, PASSWORD)).unwrap();
+    assert!(matches!(response, LoginResponse::Success { .. }));
+}
+
+fn is_logged_in(app_state: &Arc<Mutex<AppState>>) -> bool {
+    matches!(app_state.lock().unwrap().logged_in, LoginState::LoggedIn)
+}
+
+#[test]
+fn test_login_and_fetch() {
+    let backend = MockBackend::start();
+    let app_state = app_state(&backend);
+
+    let error = wait(api::login(&app_state, EMAIL, "wrong")).unwrap_err();
+    assert_eq!(
+        error,
+        ApiError::Http {
+            status: 400,
+            message: "Invalid email or password".to_string()
+        }
+    );
+
+    login(&app_state);
+    let challenges = wait(api::challenges(&app_state)).unwrap();
+    assert_eq!(challenges.len(), 2);
+    assert_eq!(challenges[0].name, "Find the odd one out");
+
+    let sample = &challenges[0].meta.files[0];
+    let bytes = wait(api::download(&app_state, "2331", sample)).unwrap();
+    assert_eq!(bytes, SAMPLE.as_bytes());
+    assert!(sample.verify(&bytes).is_ok());
+
+    let scores = wait(api::scores(&app_state, &challenges[0].table)).unwrap();
+    assert_eq!(scores[0].name, "Player1");
+    assert_eq!(backend.received("api/game/scores/23_3_1").len(), 1);
+
+    wait(api::logout(&app_state)).unwrap();
+    assert!(matches!(
+        wait(api::challenges(&app_state)),
+        Err(ApiError::Auth(_))
+    ));
+}
+
+#[test]
+fn test_expired_token_is_refreshed() {
+    let backend = MockBackend::start();
+    let app_state = app_state(&backend);
+    login(&app_state);
+
+    backend.script("api/game/challenge", Reply::fail(401, "Token expired"));
+    let challenges = wait(api::challenges(&app_state)).unwrap();
+
+    assert_eq!(challenges.len(), 2);
+    assert_eq!(backend.received("api/auth/refresh").len(), 1);
+    assert_eq!(backend.received("api/game/challenge").len(), 2);
+    assert!(is_logged_in(&app_state));
+    // the refresh shows in the network inspector with the rest
+    let app_state = app_state.lock().unwrap();
+    let logged: Vec<_> = app_state
+        .network_log
+        .entries()
+        .map(|entry| entry.url.clone())
+        .collect();
+    assert!(logged.iter().any(|url| url.ends_with("api/auth/refresh")));
+}
+
+#[test]
+fn test_failed_refresh_logs_out() {
+    let backend = MockBackend::start();
+    let app_state = app_state(&backend);
+    AppState::set_logged_in(&app_state);
+
+    let error = wait(api::challenges(&app_state)).unwrap_err();
+
+    assert!(matches!(error, ApiError::Auth(_)));
+    assert_eq!(backend.received("api/auth/refresh").len(), 1);
+    assert!(!is_logged_in(&app_state));
+}
+
+#[test]
+fn test_profiles_keep_their_own_cookies() {
+    let backend = MockBackend::start();
+    let app_state = app_state(&backend);
+    login(&app_state);
+    let first = {
+        let mut app_state = app_state.lock().unwrap();
+        let first = app_state.config.active_profile().to_string();
+        let practice = Profile::new("Practice", backend.url()).unwrap();
+        app_state.config.add_profile(practice).unwrap();
+        app_state.switch_profile("Practice").unwrap();
+        first
+    };
+
+    // the same backend, but this profile never logged in
+    let error = wait(api::challenges(&app_state)).unwrap_err();
+    assert!(matches!(error, ApiError::Auth(_)));
+
+    app_state.lock().unwrap().switch_profile(&first).unwrap();
+    assert!(wait(api::challenges(&app_state)).is_ok());
+}
+
+#[test]
+fn test_session_survives_restart() {
+    let backend = MockBackend::start();
+    let store = Arc::new(MemoryStore::default());
+
+    let app_state = app_state(&backend);
+    app_state.lock().unwrap().session_store = store.clone();
+    login(&app_state);
+    let identity = wait(api::me(&app_state)).unwrap();
+    assert_eq!(
+        identity,
+        Identity {
+            email: EMAIL.to_string(),
+            name: "Player One".to_string(),
+            roles: vec!["user".to_string()],
+        }
+    );
+    session::set_identity(&app_state, Some(identity.clone()));
+
+    // a fresh transport, as if the app had been restarted
+    let restarted = self::app_state(&backend);
+    {
+        let mut restarted = restarted.lock().unwrap();
+        restarted.session_store = store.clone();
+        session::restore(&mut restarted);
+        assert_eq!(restarted.identity, Some(identity));
+    }
+    assert!(wait(api::refresh(&restarted)).is_ok());
+    assert!(wait(api::challenges(&restarted)).is_ok());
+
+    AppState::set_logged_out(&restarted);
+    let saved = store.load().unwrap();
+    assert!(saved.identities.is_empty());
+}
+
+#[test]
+fn test_change_password() {
+    let backend = MockBackend::start();
+    let app_state = app_state(&backend);
+    login(&app_state);
+
+    let error = wait(api::change_password(
+        &app_state, EMAIL, "wrong", "hunter22", false,
+    ));
+    assert_eq!(
+        error.unwrap_err(),
+        ApiError::Http {
+            status: 400,
+            message: "Current password is incorrect".to_string()
+        }
+    );
+
+    let response = wait(api::change_password(
+        &app_state, EMAIL, PASSWORD, "hunter22", true,
+    ))
+    .unwrap();
+    assert!(response.is_success());
+    let sent = backend.received("api/auth/changepassword");
+    assert!(sent[1].text().contains("\"logout_other_sessions\":true"));
+}
+
+#[test]
+fn test_profile() {
+    let backend = MockBackend::start();
+    let app_state = app_state(&backend);
+    login(&app_state);
+
+    let mut profile = wait(api::profile(&app_state)).unwrap();
+    assert_eq!(profile.name, "Player One");
+    assert_eq!(profile.language, Languages::Rust);
+    assert!(!profile.notifications.beaten_scores);
+    assert!(!profile.notifications.submission_results);
+
+    profile.name = "P1".to_string();
+    profile.set_avatar(b"avatar");
+    assert!(wait(api::update_profile(&app_state, &profile))
+        .unwrap()
+        .is_success());
+    let sent: UserProfile =
+        serde_json::from_slice(&backend.received("api/users/me/profile")[1].body).unwrap();
+    assert_eq!(sent.name, "P1");
+    assert_eq!(sent.avatar, None);
+
+    assert!(wait(api::upload_avatar(&app_state, "me.png", b"png"))
+        .unwrap()
+        .is_success());
+    let sent = &backend.received("api/users/me/avatar")[0];
+    assert!(sent.text().contains("filename=\"me.png\""));
+}
+
+#[test]
+fn test_admin() {
+    let backend = MockBackend::start();
+    let app_state = app_state(&backend);
+    login(&app_state);
+    assert!(matches!(
+        wait(api::admin_users(&app_state)),
+        Err(ApiError::Http { status: 403, .. })
+    ));
+
+    let response = wait(api::login(&app_state, ADMIN_EMAIL, PASSWORD)).unwrap();
+    match response {
+        LoginResponse::Success { roles, .. } => assert!(roles.contains(&"admin".to_string())),
+        other => panic!("expected to log in, got {:?}", other),
+    }
+    let users = wait(api::admin_users(&app_state)).unwrap();
+    assert_eq!(users.len(), 3);
+    assert!(users[2].locked);
+
+    let action = AdminAction::Unlock {
+        email: users[2].email.clone(),
+    };
+    assert!(wait(api::admin(&app_state, &action)).unwrap().is_success());
+    let sent = &backend.received("api/admin/users/unlock")[0];
+    assert!(sent.text().contains("cheater@dummy.com"));
+
+    let mut draft = ChallengeDraft {
+        name: "Sort a million numbers".to_string(),
+        command: "2411".to_string(),
+        table: "24_1_1".to_string(),
+        doc: "# Sort".to_string(),
+        publication: Publication::Published,
+        ..Default::default()
+    };
+    draft.attachments.push(Attachment {
+        filename: "numbers.txt".to_string(),
+        bytes: b"3\n1\n2\n".to_vec(),
+    });
+    assert!(wait(api::save_challenge(&app_state, &draft))
+        .unwrap()
+        .is_success());
+    let sent = backend.received("api/admin/challenges")[0].text();
+    assert!(sent.contains("24_1_1"));
+    assert!(sent.contains("filename=\"numbers.txt\""));
+
+    // drafts are only listed for admins
+    let authored = wait(api::admin_challenges(&app_state)).unwrap();
+    assert_eq!(authored.len(), 3);
+    assert_eq!(authored[2].challenge.command, "2412");
+    assert_eq!(authored[2].publication(), Publication::Draft);
+}
+
+#[test]
+fn test_verify_email() {
+    let backend = MockBackend::start();
+    let app_state = app_state(&backend);
+
+    assert!(wait(api::verify_email(&app_state, "valid")).is_ok());
+    assert!(matches!(
+        wait(api::verify_email(&app_state, "expired")),
+        Err(ApiError::Http { status: 403, .. })
+    ));
+
+    // a token can't reach any other endpoint
+    let _ = wait(api::verify_email(&app_state, "../../users/me"));
+    assert!(backend.received("api/users/me").is_empty());
+    assert_eq!(
+        backend
+            .received("api/auth/verifyemail/..%2F..%2Fusers%2Fme")
+            .len(),
+        1
+    );
+    let _ = wait(api::reset_password(
+        &app_state,
+        "../../admin/users/lock",
+        PASSWORD,
+    ));
+    assert!(backend.received("api/admin/users/lock").is_empty());
+    assert_eq!(
+        backend
+            .received("api/auth/resetpassword/..%2F..%2Fadmin%2Fusers%2Flock")
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_server_errors_are_retried() {
+    let backend = MockBackend::start();
+    let app_state = app_state(&backend);
+    login(&app_state);
+
+    backend.script("api/game/challenge", Reply::fail(500, "Database busy"));
+    backend.script("api/game/challenge", Reply::fail(502, "Bad gateway"));
+    assert!(wait(api::challenges(&app_state)).is_ok());
+    assert_eq!(backend.received("api/game/challenge").len(), 3);
+
+    for _ in 0..3 {
+        backend.script("api/game/scores/23_3_1", Reply::fail(500, "Database busy"));
+    }
+    let error = wait(api::scores(&app_state, "23_3_1")).unwrap_err();
+    assert_eq!(
+        error,
+        ApiError::Http {
+            status: 500,
+            message: "Database busy".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_malformed_json() {
+    let backend = MockBackend::start();
+    let app_state = app_state(&backend);
+    login(&app_state);
+
+    backend.script("api/game/challenge", Reply::malformed());
+    assert!(matches!(
+        wait(api::challenges(&app_state)),
+        Err(ApiError::Decode(_))
+    ));
+}
+
+#[test]
+fn test_slow_responses_time_out() {
+    let backend = MockBackend::start();
+    let app_state = app_state(&backend);
+    login(&app_state);
+    app_state.lock().unwrap().config.retry.timeout_ms = 200;
+
+    let slow = Reply::json(200, serde_json::json!([])).slow(Duration::from_millis(1000));
+    backend.script("api/game/challenge", slow);
+    let challenges = wait(api::challenges(&app_state)).unwrap();
+
+    // the retry got the usual answer
+    assert_eq!(challenges.len(), 2);
+    assert_eq!(backend.received("api/game/challenge").len(), 2);
+}
+
+#[test]
+fn test_submissions() {
+    let backend = MockBackend::start();
+    let app_state = app_state(&backend);
+    login(&app_state);
+
+    let code = Submission {
+        challenge: Some("2331".to_string()),
+        filename: "solution.py".to_string(),
+        language: Languages::Python,
+        test: true,
+        code: Some("print(7)".to_string()),
+        binary: None,
+    };
+    let result = wait(api::submit(&app_state, &code)).unwrap();
+    assert!(matches!(
+        result,
+        SubmissionResult::Success { score: 1500, .. }
+    ));
+    assert!(backend.received("api/game/submit")[0]
+        .text()
+        .contains("print(7)"));
+
+    let binary = Submission {
+        code: None,
+        filename: "solution".to_string(),
+        binary: Some(b"\x7fELF".to_vec()),
+        ..code
+    };
+    backend.script(
+        "api/game/binary",
+        Reply::fail(429, "Slow down").header("Retry-After", "0"),
+    );
+    let result = wait(api::submit(&app_state, &binary)).unwrap();
+    assert!(matches!(result, SubmissionResult::Success { .. }));
+
+    let received = backend.received("api/game/binary");
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[1].method, "POST");
+    assert!(received[1].text().contains("filename=\"solution\""));
+    assert!(received[1].text().contains("ELF"));
+}
+
+#[test]
+fn test_failed_submissions_are_not_repeated() {
+    let backend = MockBackend::start();
+    let app_state = app_state(&backend);
+    login(&app_state);
+
+    backend.script("api/game/submit", Reply::fail(500, "Judge crashed"));
+    let submission = Submission {
+        challenge: Some("2331".to_string()),
+        filename: "solution.py".to_string(),
+        code: Some("print(7)".to_string()),
+        ..Default::default()
+    };
+    assert!(wait(api::submit(&app_state, &submission)).is_err());
+    assert_eq!(backend.received("api/game/submit").len(), 1);
+}
+
+/// Polls a submission the way the code editor does, every frame.
+fn wait_submission(app_state: &Arc<Mutex<AppState>>, submission: &Submission) -> SubmissionResult {
+    let mut sender: Option<PendingSubmission> = submission.sender(app_state);
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while Instant::now() < deadline {
+        match Submission::check_sender(&mut sender, app_state) {
+            SubmissionResult::Busy => std::thread::sleep(Duration::from_millis(5)),
+            result => return result,
+        }
+    }
+    panic!("submission never completed");
+}
+
+#[test]
+fn test_queued_submissions_are_sent_in_order() {
+    let backend = MockBackend::start();
+    let app_state = app_state(&backend);
+    login(&app_state);
+    session::set_identity(
+        &app_state,
+        Some(Identity {
+            email: EMAIL.to_string(),
+            ..Default::default()
+        }),
+    );
+
+    // turned away on every attempt, so it waits in the queue
+    for _ in 0..3 {
+        backend.script("api/game/submit", Reply::fail(503, "Judge busy"));
+    }
+    let code = Submission {
+        challenge: Some("2331".to_string()),
+        filename: "solution.py".to_string(),
+        code: Some("print(7)".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(wait_submission(&app_state, &code), SubmissionResult::Queued);
+    assert_eq!(backend.received("api/game/submit").len(), 3);
+
+    // and anything after it waits its turn
+    let binary = Submission {
+        code: None,
+        filename: "solution".to_string(),
+        binary: Some(b"\x7fELF".to_vec()),
+        ..code.clone()
+    };
+    assert_eq!(
+        wait_submission(&app_state, &binary),
+        SubmissionResult::Queued
+    );
+    assert!(backend.received("api/game/binary").is_empty());
+
+    // too big to keep, so it can't wait either
+    let huge = Submission {
+        binary: Some(vec![0; submission_queue::MAX_QUEUED_BINARY + 1]),
+        ..binary.clone()
+    };
+    assert!(matches!(
+        wait_submission(&app_state, &huge),
+        SubmissionResult::Failure { message } if message.contains("too big to queue")
+    ));
+
+    // someone else's waits for them
+    app_state
+        .lock()
+        .unwrap()
+        .submission_queue
+        .push(code.clone(), "Network error", ADMIN_EMAIL);
+
+    // the login window marks it, the queue only goes out while logged in
+    AppState::set_logged_in(&app_state);
+    let ctx = egui::Context::default();
+    let mut sender = SubmissionSender::new(app_state.clone());
+    let queued = || app_state.lock().unwrap().submission_queue.items().len();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while queued() > 1 && Instant::now() < deadline {
+        let _ = ctx.run(Default::default(), |ctx| sender.tick(ctx));
+        if queued() == 2 {
+            assert_eq!(backend.received("api/game/submit").len(), 4);
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(queued(), 1);
+    for _ in 0..10 {
+        let _ = ctx.run(Default::default(), |ctx| sender.tick(ctx));
+    }
+    assert_eq!(queued(), 1);
+    assert_eq!(backend.received("api/game/submit").len(), 4);
+    let received = backend.received("api/game/binary");
+    assert_eq!(received.len(), 1);
+    assert!(received[0].text().contains("ELF"));
+    assert_eq!(
+        app_state.lock().unwrap().submission_history.records().len(),
+        2
+    );
+}
+
+#[test]
+fn test_submission_history() {
+    let backend = MockBackend::start();
+    let app_state = app_state(&backend);
+    login(&app_state);
+
+    let records = wait(api::submissions(&app_state)).unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].language, Languages::Rust);
+    assert_eq!(records[0].score, None);
+    assert_eq!(records[1].code.as_deref(), Some("print(7)"));
+    app_state
+        .lock()
+        .unwrap()
+        .submission_history
+        .replace(records.clone(), chrono::Utc::now());
+
+    // judged since the list was fetched, so it is kept when it is fetched again
+    let code = Submission {
+        challenge: Some("2333".to_string()),
+        filename: "big.py".to_string(),
+        language: Languages::Python,
+        code: Some("print(1)".to_string()),
+        ..Default::default()
+    };
+    assert!(matches!(
+        wait_submission(&app_state, &code),
+        SubmissionResult::Success { .. }
+    ));
+    let mut app_state = app_state.lock().unwrap();
+    let history = &mut app_state.submission_history;
+    history.replace(records, chrono::Utc::now());
+    let records = history.records();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0].filename, "solution.py");
+    assert_eq!(records[2].filename, "big.py");
+    assert_eq!(records[2].score, Some(1500));
+}
@@ -0,0 +1,419 @@
+//! A stand in for the challenge backend and judge.
+//!
+//! It answers the API the way the real backend does, unless a reply has
+//! been scripted for a path, which is then used once instead.
+
+use challenge_frontend::helpers::downloads;
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+pub const EMAIL: &str = "player@dummy.com";
+pub const PASSWORD: &str = "password123";
+/// Logs in with the same password, but gets an admin token
+pub const ADMIN_EMAIL: &str = "admin@dummy.com";
+/// Offered for download with 2331
+pub const SAMPLE: &str = "[[7], [0], [1, 1, 2]]";
+
+#[derive(Clone, Debug)]
+pub struct Reply {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    pub delay: Duration,
+}
+
+impl Reply {
+    pub fn json(status: u16, body: serde_json::Value) -> Self {
+        Self {
+            status,
+            headers: vec![],
+            body: body.to_string(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    pub fn fail(status: u16, message: &str) -> Self {
+        Self::json(status, json!({ "status": "fail", "message": message }))
+    }
+
+    pub fn malformed() -> Self {
+        Self {
+            body: "{\"status\": \"succ".to_string(),
+            ..Self::json(200, json!({}))
+        }
+    }
+
+    pub fn slow(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Received {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Received {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn has_cookie(&self, name: &str, value: &str) -> bool {
+        self.header("Cookie")
+            .map(|cookies| {
+                cookies
+                    .split(';')
+                    .any(|cookie| cookie.trim() == format!("{}={}", name, value))
+            })
+            .unwrap_or(false)
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).to_string()
+    }
+}
+
+#[derive(Default)]
+struct State {
+    scripts: HashMap<String, VecDeque<Reply>>,
+    received: Vec<Received>,
+}
+
+pub struct MockBackend {
+    url: String,
+    state: Arc<Mutex<State>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl MockBackend {
+    /// Listens on a free port.
+    pub fn start() -> Self {
+        Self::start_on("127.0.0.1:0")
+    }
+
+    pub fn start_on(address: &str) -> Self {
+        let listener = TcpListener::bind(address).expect("bind mock backend");
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(State::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let (thread_state, thread_stop) = (state.clone(), stop.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if thread_stop.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    let state = thread_state.clone();
+                    thread::spawn(move || handle(stream, state));
+                }
+            }
+        });
+
+        Self { url, state, stop }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The next request to `path` gets `reply`, instead of the usual answer.
+    pub fn script(&self, path: &str, reply: Reply) {
+        self.state
+            .lock()
+            .unwrap()
+            .scripts
+            .entry(path.trim_start_matches('/').to_string())
+            .or_default()
+            .push_back(reply);
+    }
+
+    /// Everything sent to `path` so far.
+    pub fn received(&self, path: &str) -> Vec<Received> {
+        let path = path.trim_start_matches('/');
+        self.state
+            .lock()
+            .unwrap()
+            .received
+            .iter()
+            .filter(|r| r.path == path)
+            .cloned()
+            .collect()
+    }
+}
+
+impl Drop for MockBackend {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // wake up the listener so it notices
+        let _ = TcpStream::connect(self.url.trim_start_matches("http://").trim_end_matches('/'));
+    }
+}
+
+fn handle(mut stream: TcpStream, state: Arc<Mutex<State>>) {
+    let request = match read_request(&mut stream) {
+        Some(request) => request,
+        None => return,
+    };
+
+    let reply = {
+        let mut state = state.lock().unwrap();
+        state.received.push(request.clone());
+        state
+            .scripts
+            .get_mut(&request.path)
+            .and_then(|replies| replies.pop_front())
+    };
+    let reply = reply.unwrap_or_else(|| respond(&request));
+
+    thread::sleep(reply.delay);
+    let mut response = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        reply.status,
+        reply.body.len()
+    );
+    for (name, value) in &reply.headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("\r\n");
+    response.push_str(&reply.body);
+    let _ = stream.write_all(response.as_bytes());
+}
+
+fn read_request(stream: &mut TcpStream) -> Option<Received> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.trim_start_matches('/').to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':')?;
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+
+    let length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("Content-Length"))
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(Received {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+/* What the real backend would say.
+The access and refresh tokens are plain cookies, like the backend's */
+fn respond(request: &Received) -> Reply {
+    let is_admin = request.has_cookie("access_token", "admin");
+    let logged_in = is_admin || request.has_cookie("access_token", "valid");
+    let path = request.path.as_str();
+
+    match (request.method.as_str(), path) {
+        ("POST", "api/auth/login") => {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap_or_default();
+            let (token, roles) = match body["email"].as_str() {
+                Some(EMAIL) => ("valid", json!(["user"])),
+                Some(ADMIN_EMAIL) => ("admin", json!(["user", "admin"])),
+                _ => ("", json!([])),
+            };
+            if !token.is_empty() && body["password"] == PASSWORD {
+                Reply::json(
+                    200,
+                    json!({ "Success": { "status": "success", "access_token": token, "roles": roles } }),
+                )
+                .header("Set-Cookie", &format!("access_token={}; Path=/", token))
+                .header("Set-Cookie", &format!("refresh_token={}; Path=/", token))
+            } else {
+                Reply::fail(400, "Invalid email or password")
+            }
+        }
+        ("GET", "api/auth/refresh") => {
+            match ["valid", "admin"]
+                .into_iter()
+                .find(|token| request.has_cookie("refresh_token", token))
+            {
+                Some(token) => Reply::json(
+                    200,
+                    json!({ "status": "success", "message": "Token refreshed" }),
+                )
+                .header("Set-Cookie", &format!("access_token={}; Path=/", token)),
+                None => Reply::fail(401, "Could not refresh access token"),
+            }
+        }
+        ("POST", "api/auth/logout") => Reply::json(200, json!({ "status": "success" }))
+            .header("Set-Cookie", "access_token=; Path=/; Max-Age=0")
+            .header("Set-Cookie", "refresh_token=; Path=/; Max-Age=0"),
+        ("POST", "api/auth/register") | ("POST", "api/auth/forgotpassword") => {
+            Reply::json(200, json!({ "status": "success", "message": "Email sent" }))
+        }
+        ("GET", path) if path.starts_with("api/auth/verifyemail/") => {
+            match path.trim_start_matches("api/auth/verifyemail/") {
+                "valid" => Reply::json(
+                    200,
+                    json!({ "status": "success", "message": "Email verified" }),
+                ),
+                _ => Reply::fail(403, "Invalid verification code or user doesn't exist"),
+            }
+        }
+        ("POST", path) if path.starts_with("api/auth/resetpassword/") => Reply::json(
+            200,
+            json!({ "status": "success", "message": "Password updated" }),
+        ),
+        _ if !logged_in => Reply::fail(401, "You are not logged in"),
+        ("GET", "api/users/me") => Reply::json(
+            200,
+            json!({
+                "status": "success",
+                "data": { "user": { "name": "Player One", "email": EMAIL, "role": "user" } }
+            }),
+        ),
+        (_, path) if path.starts_with("api/admin/") && !is_admin => {
+            Reply::fail(403, "You are not allowed to do that")
+        }
+        ("GET", "api/admin/users") => Reply::json(
+            200,
+            json!([
+                { "name": "Player One", "email": EMAIL, "roles": ["user"], "verified": true },
+                { "name": "Admin", "email": ADMIN_EMAIL, "roles": ["user", "admin"], "verified": true },
+                { "name": "Cheater", "email": "cheater@dummy.com", "roles": ["user"], "locked": true }
+            ]),
+        ),
+        ("GET", "api/admin/challenges") => Reply::json(
+            200,
+            json!([
+                { "name": "Find the odd one out", "command": "2331", "table": "23_3_1", "doc": "# Find Odds", "published": true },
+                { "name": "How big?", "command": "2333", "table": "23_3_3", "doc": "# How Big?!", "published": true },
+                { "name": "Primes", "command": "2412", "table": "24_1_2", "doc": "# Primes", "published": false }
+            ]),
+        ),
+        ("POST", "api/admin/challenges") => match request.text().contains("name=\"command\"") {
+            true => Reply::json(
+                200,
+                json!({ "status": "success", "message": "Challenge saved" }),
+            ),
+            false => Reply::fail(400, "A challenge needs a command"),
+        },
+        ("POST", path) if path.starts_with("api/admin/") => {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap_or_default();
+            let target = match path.starts_with("api/admin/scores/") {
+                true => &body["table"],
+                false => &body["email"],
+            };
+            match target.as_str().unwrap_or_default().is_empty() {
+                false => Reply::json(200, json!({ "status": "success", "message": "Done" })),
+                true => Reply::fail(400, "Nothing to do that to"),
+            }
+        }
+        ("GET", "api/users/me/profile") => Reply::json(
+            200,
+            json!({
+                "name": "Player One",
+                "language": "Rust",
+                "notifications": { "new_challenges": true, "beaten_scores": false }
+            }),
+        ),
+        ("POST", "api/users/me/profile") => {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap_or_default();
+            match body["name"].as_str().unwrap_or_default().trim().is_empty() {
+                false => Reply::json(
+                    200,
+                    json!({ "status": "success", "message": "Profile updated" }),
+                ),
+                true => Reply::fail(400, "Name is required"),
+            }
+        }
+        ("POST", "api/users/me/avatar") => match request.text().contains("name=\"avatar\"") {
+            true => Reply::json(
+                200,
+                json!({ "status": "success", "message": "Avatar updated" }),
+            ),
+            false => Reply::fail(400, "No avatar uploaded"),
+        },
+        ("GET", "api/game/challenge") => Reply::json(
+            200,
+            json!([
+                {
+                    "name": "Find the odd one out", "command": "2331", "table": "23_3_1", "doc": "# Find Odds",
+                    "files": [{
+                        "name": "sample.json", "kind": "Sample", "size": SAMPLE.len(),
+                        "sha1": downloads::sha1_hex(SAMPLE.as_bytes())
+                    }]
+                },
+                { "name": "How big?", "command": "2333", "table": "23_3_3", "doc": "# How Big?!" }
+            ]),
+        ),
+        ("GET", "api/game/challenge/2331/files/sample.json") => Reply {
+            body: SAMPLE.to_string(),
+            ..Reply::json(200, json!({}))
+        },
+        ("POST", "api/auth/changepassword") => {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap_or_default();
+            match body["old_password"] == PASSWORD {
+                true => Reply::json(
+                    200,
+                    json!({ "status": "success", "message": "Password updated" }),
+                ),
+                false => Reply::fail(400, "Current password is incorrect"),
+            }
+        }
+        ("GET", path) if path.starts_with("api/game/scores/") => Reply::json(
+            200,
+            json!([
+                { "name": "Player1", "command": "2331", "time_ns": 1500.0, "language": "rust" },
+                { "name": "Player2", "command": "2331", "time_ns": 2500.0, "language": "python" }
+            ]),
+        ),
+        ("GET", "api/game/submissions") => Reply::json(
+            200,
+            json!([
+                {
+                    "challenge": "2331", "filename": "solution", "language": "Rust", "test": true,
+                    "submitted_at": "2024-01-02T10:00:00Z", "message": "Wrong answer"
+                },
+                {
+                    "challenge": "2331", "filename": "solution.py", "language": "Python", "test": false,
+                    "submitted_at": "2024-01-01T09:30:00Z", "score": 1500,
+                    "message": "Passed all tests", "code": "print(7)"
+                }
+            ]),
+        ),
+        ("POST", "api/game/submit") | ("POST", "api/game/binary") => Reply::json(
+            200,
+            json!({ "Success": { "score": 1500, "message": "Passed all tests" } }),
+        ),
+        _ => Reply::fail(404, "Not found"),
+    }
+}
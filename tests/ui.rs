@@ -0,0 +1,1043 @@
+//! Drives the windows headless against a fake transport.
+#![cfg(not(target_arch = "wasm32"))]
+
+mod ui_harness;
+
+use challenge_frontend::apps::{
+    code_editor::CodeEditor, AdminApp, App, ChallengeAuthorApp, ChallengeInfoApp, LoginApp,
+    PasswordResetApp, ProfileApp, ScoreBoardApp, SettingsApp, SubmissionHistoryApp,
+    SubmissionQueueApp, WhatsNewApp,
+};
+use challenge_frontend::helpers::{
+    deep_link::DeepLink,
+    downloads::{self, ChallengeFile},
+    session::{self, Identity},
+    submission::Submission,
+    transport::{Body, FormValue},
+    user_profile::UserProfile,
+    AppState, Challenge, ChallengeCollection, Languages, LoginState,
+};
+use egui::Key;
+use scoreboard_db::Score;
+use serde_json::json;
+use ui_harness::{FakeTransport, Harness};
+
+const CHALLENGES: &str = r##"[
+    { "name": "Find the odd one out", "command": "2331", "table": "23_3_1", "doc": "# Find Odds" },
+    { "name": "How big?", "command": "2333", "table": "23_3_3", "doc": "# How Big?!",
+      "difficulty": "Hard", "tags": ["maths"], "io_mode": "StdinLoop" }
+]"##;
+
+fn with_challenges<A: App>(harness: &mut Harness<A>) {
+    harness.app_state.lock().unwrap().challenges = ChallengeCollection::from_json(CHALLENGES);
+    harness.run(2);
+}
+
+fn follow<A: App>(harness: &mut Harness<A>, link: &str) {
+    harness.app_state.lock().unwrap().deep_link = DeepLink::parse(link);
+    harness.run(2);
+}
+
+fn json_body(body: &Body) -> serde_json::Value {
+    match body {
+        Body::Json(json) => serde_json::from_str(json).unwrap(),
+        other => panic!("expected a json body, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_login_and_logout() {
+    let transport = FakeTransport::default();
+    transport.route(
+        "api/auth/login",
+        200,
+        json!({ "Success": { "status": "success", "access_token": "token" } }),
+    );
+    transport.route("api/auth/logout", 200, json!({ "status": "success" }));
+    transport.route(
+        "api/users/me",
+        200,
+        json!({ "status": "success", "data": { "user": {
+            "name": "Player One", "email": "player@dummy.com", "roles": ["user", "admin"]
+        } } }),
+    );
+    let mut harness = Harness::new(LoginApp::default(), transport);
+
+    // there was no session to restore
+    assert_eq!(harness.transport.received("api/auth/refresh").len(), 1);
+    assert!(harness.widget("Login").is_some());
+
+    harness.type_into(0, "player@dummy.com");
+    harness.click("Login");
+
+    let sent = harness.transport.received("api/auth/login");
+    assert_eq!(sent.len(), 1);
+    assert_eq!(json_body(&sent[0].body)["email"], "player@dummy.com");
+    assert!(matches!(
+        harness.app_state.lock().unwrap().logged_in,
+        LoginState::LoggedIn
+    ));
+    assert!(harness.shows("Logged in as: Player One"));
+    assert!(harness.shows("Roles: user, admin"));
+    let saved = harness
+        .app_state
+        .lock()
+        .unwrap()
+        .session_store
+        .load()
+        .unwrap();
+    assert_eq!(saved.identities.values().next().unwrap().name, "Player One");
+
+    harness.click("Logout");
+    assert_eq!(harness.transport.received("api/auth/logout").len(), 1);
+    assert!(matches!(
+        harness.app_state.lock().unwrap().logged_in,
+        LoginState::LoggedOut
+    ));
+    assert!(harness.widget("Login").is_some());
+    assert!(harness.app_state.lock().unwrap().identity.is_none());
+}
+
+#[test]
+fn test_failed_login() {
+    let transport = FakeTransport::default();
+    transport.route(
+        "api/auth/login",
+        400,
+        json!({ "status": "fail", "message": "Invalid email or password" }),
+    );
+    let mut harness = Harness::new(LoginApp::default(), transport);
+
+    harness.click("Login");
+
+    assert!(harness.shows("Invalid email or password"));
+    assert!(matches!(
+        harness.app_state.lock().unwrap().logged_in,
+        LoginState::LoggedOut
+    ));
+    assert!(harness.widget("Logout").is_none());
+}
+
+#[test]
+fn test_unreachable_session_check() {
+    let transport = FakeTransport::default();
+    let mut harness = Harness::new(LoginApp::default(), transport);
+
+    // a session restored while the backend is gone stays until it can be checked
+    harness.app_state.lock().unwrap().identity = Some(Identity {
+        name: "Player One".to_string(),
+        email: "player@dummy.com".to_string(),
+        ..Default::default()
+    });
+    harness.transport.set_offline(true);
+    harness.app.set_app_state_ref(harness.app_state.clone());
+    harness.run(2);
+    assert!(harness.shows("Welcome back Player One, the backend couldn't be reached"));
+    {
+        let app_state = harness.app_state.lock().unwrap();
+        assert!(matches!(app_state.logged_in, LoginState::LoggedOut));
+        assert_eq!(app_state.identity.as_ref().unwrap().name, "Player One");
+    }
+
+    harness.transport.set_offline(false);
+    harness.transport.route(
+        "api/auth/refresh",
+        200,
+        json!({ "status": "success", "message": "" }),
+    );
+    harness.click("Check again");
+    harness.run(2);
+    assert_eq!(harness.transport.received("api/auth/refresh").len(), 3);
+    assert!(matches!(
+        harness.app_state.lock().unwrap().logged_in,
+        LoginState::LoggedIn
+    ));
+}
+
+#[test]
+fn test_change_password() {
+    let transport = FakeTransport::default();
+    transport.route(
+        "api/auth/login",
+        200,
+        json!({ "Success": { "status": "success", "access_token": "token" } }),
+    );
+    transport.route(
+        "api/auth/changepassword",
+        400,
+        json!({ "status": "fail", "message": "Current password is incorrect" }),
+    );
+    let mut harness = Harness::new(LoginApp::default(), transport);
+    harness.click("Login");
+    harness.click("Account");
+    assert_eq!(harness.text_fields().len(), 3);
+
+    harness.type_into(0, "password123");
+    harness.type_into(1, "hunter22");
+    harness.type_into(2, "hunter2");
+    harness.click("Change Password");
+    assert!(harness.shows("Passwords do not match"));
+    assert!(harness
+        .transport
+        .received("api/auth/changepassword")
+        .is_empty());
+
+    harness.type_into(2, "hunter22");
+    harness.click("Log out my other sessions");
+    harness.click("Change Password");
+    let sent = harness.transport.received("api/auth/changepassword");
+    assert_eq!(sent.len(), 1);
+    let body = json_body(&sent[0].body);
+    assert_eq!(body["old_password"], "password123");
+    assert_eq!(body["new_password"], "hunter22");
+    assert_eq!(body["logout_other_sessions"], true);
+    assert!(harness.shows("Current password is incorrect"));
+    assert!(matches!(
+        harness.app_state.lock().unwrap().logged_in,
+        LoginState::LoggedIn
+    ));
+
+    harness.transport.route(
+        "api/auth/changepassword",
+        200,
+        json!({ "status": "success", "message": "" }),
+    );
+    harness.click("Change Password");
+    assert!(harness.shows("Password changed"));
+}
+
+#[test]
+fn test_register_validation() {
+    let mut harness = Harness::new(LoginApp::default(), FakeTransport::default());
+
+    harness.click("Register");
+    assert!(harness.widget("Cancel").is_some());
+    assert_eq!(harness.text_fields().len(), 4);
+
+    harness.type_into(1, "not an email");
+    harness.click("Register");
+
+    assert!(harness.shows("Invalid email address"));
+    assert!(harness.transport.received("api/auth/register").is_empty());
+
+    harness.click("Cancel");
+    assert!(harness.widget("Login").is_some());
+}
+
+#[test]
+fn test_register_password_policy() {
+    let mut harness = Harness::new(LoginApp::default(), FakeTransport::default());
+    harness.click("Register");
+    harness.type_into(1, "player@dummy.com");
+    harness.type_into(2, "player1");
+    assert!(harness.shows("Reasonable"));
+    assert!(harness.shows("✖ At least 8 characters"));
+    assert!(harness.shows("✖ Must not contain your email address"));
+
+    harness.type_into(3, "player");
+    assert!(harness.shows("✖ Passwords do not match"));
+
+    harness.type_into(3, "player1");
+    harness.click("Register");
+    assert!(harness.shows("Password too weak: At least 8 characters"));
+    assert!(harness.transport.received("api/auth/register").is_empty());
+
+    harness.click("👁");
+    assert!(harness.shows("player1"));
+}
+
+#[test]
+fn test_scoreboard() {
+    let transport = FakeTransport::default();
+    transport.route(
+        "api/game/scores/23_3_1",
+        200,
+        json!([
+            { "name": "Player1", "command": "2331", "time_ns": 1500.0, "language": "rust" },
+            { "name": "Player2", "command": "2331", "time_ns": 2500.0, "language": "python" }
+        ]),
+    );
+    let mut harness = Harness::new(ScoreBoardApp::default(), transport);
+    with_challenges(&mut harness);
+
+    harness.click("Challenge");
+    harness.click("2331");
+
+    assert_eq!(
+        harness.transport.received("api/game/scores/23_3_1").len(),
+        1
+    );
+    assert!(harness.shows("Player1"));
+    assert!(harness.shows("Player2"));
+    assert!(harness
+        .app_state
+        .lock()
+        .unwrap()
+        .scoreboards
+        .contains_key("23_3_1"));
+
+    harness.click("Refresh");
+    assert_eq!(
+        harness.transport.received("api/game/scores/23_3_1").len(),
+        2
+    );
+}
+
+#[test]
+fn test_offline_scoreboard() {
+    let transport = FakeTransport::default();
+    transport.route(
+        "api/game/scores/23_3_1",
+        200,
+        json!([{ "name": "Player1", "command": "2331", "time_ns": 1500.0, "language": "rust" }]),
+    );
+    let mut harness = Harness::new(ScoreBoardApp::default(), transport);
+    with_challenges(&mut harness);
+    harness.click("Challenge");
+    harness.click("2331");
+    assert!(!harness.shows("Offline"));
+
+    // the cached scores stay up while the backend is gone
+    harness.transport.set_offline(true);
+    harness.click("Refresh");
+    harness.run(2);
+    assert!(harness.app_state.lock().unwrap().offline_since.is_some());
+    assert!(harness.shows("Player1"));
+    assert!(harness.shows("⚠ Offline, these scores are stale since"));
+
+    // and are fetched again once anything gets through
+    harness.transport.set_offline(false);
+    harness.app_state.lock().unwrap().set_reachable(true);
+    harness.run(2);
+    assert_eq!(
+        harness.transport.received("api/game/scores/23_3_1").len(),
+        3
+    );
+    assert!(!harness.shows("Offline"));
+}
+
+#[test]
+fn test_challenge_info() {
+    let mut harness = Harness::new(ChallengeInfoApp::default(), FakeTransport::default());
+    assert!(harness.shows("Unable to load instructions"));
+
+    with_challenges(&mut harness);
+    assert!(harness.shows("-C 2333"));
+    assert!(harness.shows("Hard"));
+    assert!(harness.shows("#maths"));
+    harness.click("How big?");
+
+    assert!(harness.shows("How Big?!"));
+    assert!(!harness.shows("Find Odds"));
+
+    harness.click("Find the odd one out");
+    assert!(harness.shows("Find Odds"));
+}
+
+#[test]
+fn test_corrupt_download() {
+    let transport = FakeTransport::default();
+    transport.route(
+        "api/game/challenge/2333/files/sample.json",
+        200,
+        json!([[7], [0], [1, 1, 3]]),
+    );
+    let mut harness = Harness::new(ChallengeInfoApp::default(), transport);
+    let mut challenges = ChallengeCollection::from_json(CHALLENGES);
+    challenges.items[1].meta.files = vec![ChallengeFile {
+        name: "sample.json".to_string(),
+        size: Some(17),
+        sha1: Some(downloads::sha1_hex(b"[[7],[0],[1,1,2]]")),
+        ..Default::default()
+    }];
+    harness.app_state.lock().unwrap().challenges = challenges;
+    harness.run(2);
+    assert!(!harness.shows("Downloads"));
+
+    harness.click("How big?");
+    assert!(harness.shows("Downloads (1)"));
+    harness.click("Downloads (1)");
+    assert!(harness.shows("Sample data, 17 B"));
+    harness.click("⬇ sample.json");
+    harness.run(2);
+    assert_eq!(
+        harness
+            .transport
+            .received("api/game/challenge/2333/files/sample.json")
+            .len(),
+        1
+    );
+    assert!(harness.shows("sample.json is corrupt"));
+}
+
+#[test]
+fn test_challenge_browser() {
+    let mut harness = Harness::new(ChallengeInfoApp::default(), FakeTransport::default());
+    {
+        let mut app_state = harness.app_state.lock().unwrap();
+        app_state.logged_in = LoginState::LoggedIn;
+        app_state.identity = Some(Identity {
+            email: "player@dummy.com".to_string(),
+            name: "Player One".to_string(),
+            ..Default::default()
+        });
+        app_state.scoreboards.insert(
+            "23_3_1".to_string(),
+            vec![Score {
+                name: "Player One".to_string(),
+                command: "2331".to_string(),
+                time_ns: 1500.0,
+                hash: String::new(),
+                language: "rust".to_string(),
+            }],
+        );
+    }
+    with_challenges(&mut harness);
+    assert!(harness.shows("✔"));
+    // the other table is fetched to see if it's solved
+    assert_eq!(
+        harness.transport.received("api/game/scores/23_3_3").len(),
+        1
+    );
+
+    harness.click("Unsolved");
+    assert!(!harness.shows("Find the odd one out"));
+    assert!(harness.shows("How big?"));
+    harness.click("Unsolved");
+
+    harness.type_into(0, "MATHS");
+    assert!(!harness.shows("Find the odd one out"));
+    harness.press(Key::ArrowDown);
+    assert!(harness.shows("How Big?!"));
+    harness.press(Key::Enter);
+    assert_eq!(
+        harness.app_state.lock().unwrap().deep_link,
+        Some(DeepLink::OpenChallenge {
+            command: "2333".to_string()
+        })
+    );
+
+    let mut editor = Harness::new(CodeEditor::default(), FakeTransport::default());
+    with_challenges(&mut editor);
+    follow(&mut editor, "#challenge?command=2333");
+    assert!(editor.app_state.lock().unwrap().deep_link.is_none());
+    assert!(editor.shows("2333"));
+}
+
+#[test]
+fn test_code_editor_submission() {
+    let transport = FakeTransport::default();
+    transport.route(
+        "api/game/submit",
+        200,
+        json!({ "Success": { "score": 1500, "message": "Passed all tests" } }),
+    );
+    let mut harness = Harness::new(CodeEditor::default(), transport);
+    with_challenges(&mut harness);
+
+    harness.click("Test");
+    assert!(harness.shows("Failure: Challenge not selected"));
+
+    harness.click("Challenge");
+    harness.click("2333");
+    harness.click("Test");
+    assert!(harness.shows("Failure: Filename not specified"));
+    assert!(harness.transport.received("api/game/submit").is_empty());
+
+    harness.type_into(0, "solution.py");
+    harness.click("Test");
+
+    let sent = harness.transport.received("api/game/submit");
+    assert_eq!(sent.len(), 1);
+    let body = json_body(&sent[0].body);
+    assert_eq!(body["challenge"], "2333");
+    assert_eq!(body["filename"], "solution.py");
+    assert_eq!(body["test"], true);
+    assert!(harness.shows("Passed all tests"));
+
+    // the backend going away doesn't lose it
+    harness.transport.set_offline(true);
+    harness.click("Submit");
+    harness.run(2);
+    assert!(harness.shows("Queued, it will be sent once the backend can be reached"));
+    let app_state = harness.app_state.lock().unwrap();
+    let queued = app_state.submission_queue.items();
+    assert_eq!(queued.len(), 1);
+    assert_eq!(queued[0].submission().filename, "solution.py");
+    assert!(!queued[0].submission().test);
+}
+
+#[test]
+fn test_password_reset() {
+    let transport = FakeTransport::default();
+    transport.route(
+        "api/auth/resetpassword/",
+        200,
+        json!({ "status": "success", "message": "Password updated" }),
+    );
+    let mut harness = Harness::new(PasswordResetApp::default(), transport);
+    assert_eq!(harness.text_fields().len(), 4);
+
+    harness.type_into(0, "player@dummy.com");
+    harness.type_into(1, "abc123");
+    harness.type_into(2, "hunter22");
+    harness.type_into(3, "hunter2");
+    harness.click("Submit");
+
+    assert!(harness.shows("Passwords do not match"));
+    assert!(harness
+        .transport
+        .received("api/auth/resetpassword")
+        .is_empty());
+
+    harness.type_into(3, "hunter22");
+    harness.click("Submit");
+
+    let sent = harness.transport.received("api/auth/resetpassword/abc123");
+    assert_eq!(sent.len(), 1);
+    assert_eq!(json_body(&sent[0].body)["password"], "hunter22");
+    assert!(harness.shows("Password reset successfully!"));
+}
+
+#[test]
+fn test_reset_link() {
+    let transport = FakeTransport::default();
+    transport.route(
+        "api/auth/resetpassword/",
+        200,
+        json!({ "status": "success", "message": "Password updated" }),
+    );
+    let mut harness = Harness::new(PasswordResetApp::default(), transport);
+
+    follow(&mut harness, "#reset?token=abc123&email=player%40dummy.com");
+    assert!(harness.app_state.lock().unwrap().deep_link.is_none());
+    let fields = harness.text_fields();
+    assert_eq!(fields[0].value.as_deref(), Some("player@dummy.com"));
+    assert_eq!(fields[1].value.as_deref(), Some("abc123"));
+
+    harness.type_into(2, "hunter22");
+    harness.type_into(3, "hunter22");
+    harness.click("Submit");
+    assert_eq!(
+        harness
+            .transport
+            .received("api/auth/resetpassword/abc123")
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_verify_link() {
+    let transport = FakeTransport::default();
+    transport.route(
+        "api/auth/verifyemail/good",
+        200,
+        json!({ "status": "success", "message": "Email verified" }),
+    );
+    let mut harness = Harness::new(LoginApp::default(), transport);
+
+    follow(&mut harness, "#verify?token=good");
+    assert_eq!(
+        harness
+            .transport
+            .received("api/auth/verifyemail/good")
+            .len(),
+        1
+    );
+    assert!(harness.shows("Email verified, you can log in now"));
+
+    follow(&mut harness, "#verify?token=bad");
+    assert!(harness.shows("Couldn't verify your email: Not found (404)"));
+    assert!(harness.widget("Login").is_some());
+}
+
+#[test]
+fn test_backend_from_a_link() {
+    let mut harness = Harness::new(SettingsApp::default(), FakeTransport::default());
+    let url = harness
+        .app_state
+        .lock()
+        .unwrap()
+        .config
+        .backend_url()
+        .to_string();
+    harness
+        .app_state
+        .lock()
+        .unwrap()
+        .config
+        .override_backend_url("https://evil.example.com")
+        .unwrap();
+    harness.run(2);
+    assert!(harness.shows("A link asked to use the backend https://evil.example.com/"));
+    assert_eq!(harness.app_state.lock().unwrap().config.backend_url(), url);
+
+    harness.click("Ignore");
+    assert!(!harness.shows("A link asked to use the backend"));
+    assert_eq!(harness.app_state.lock().unwrap().config.profiles().len(), 1);
+
+    harness
+        .app_state
+        .lock()
+        .unwrap()
+        .config
+        .override_backend_url("https://kata.example.com")
+        .unwrap();
+    harness.run(2);
+    harness.click("Add and Use");
+    assert!(harness.shows("Added and switched to https://kata.example.com/"));
+    let config = &harness.app_state.lock().unwrap().config;
+    assert_eq!(config.profiles().len(), 2);
+    assert_eq!(config.backend_url(), "https://kata.example.com/");
+}
+
+#[test]
+fn test_profile() {
+    let transport = FakeTransport::default();
+    transport.route(
+        "api/users/me/profile",
+        200,
+        json!({ "name": "Player One", "language": "Rust" }),
+    );
+    let mut harness = Harness::new(ProfileApp::default(), transport);
+    assert!(harness.shows("Log in to see your profile"));
+    assert!(harness
+        .transport
+        .received("api/users/me/profile")
+        .is_empty());
+
+    {
+        let mut app_state = harness.app_state.lock().unwrap();
+        app_state.logged_in = LoginState::LoggedIn;
+        app_state.identity = Some(Identity {
+            email: "player@dummy.com".to_string(),
+            ..Default::default()
+        });
+    }
+    harness.run(3);
+    assert_eq!(harness.transport.received("api/users/me/profile").len(), 1);
+    assert_eq!(
+        harness.text_fields()[0].value.as_deref(),
+        Some("Player One")
+    );
+    assert!(harness.shows("Rust"));
+    let stored = harness.app_state.lock().unwrap().user_profile.clone();
+    assert_eq!(stored.unwrap().language, Languages::Rust);
+
+    harness.transport.route(
+        "api/users/me/profile",
+        200,
+        json!({ "status": "success", "message": "Profile updated" }),
+    );
+    harness.type_into(0, "P1");
+    harness.click("Someone beats my score");
+    harness.click("Save");
+
+    let sent = harness.transport.received("api/users/me/profile");
+    assert_eq!(sent.len(), 2);
+    let body = json_body(&sent[1].body);
+    assert_eq!(body["name"], "P1");
+    assert_eq!(body["notifications"]["beaten_scores"], false);
+    assert!(harness.shows("Profile saved"));
+    let identity = harness.app_state.lock().unwrap().identity.clone();
+    assert_eq!(identity.unwrap().name, "P1");
+
+    // logged in on another backend too, its own profile is shown
+    {
+        let mut app_state = harness.app_state.lock().unwrap();
+        app_state
+            .config
+            .set_backend_url("http://other.dummy.com:4000")
+            .unwrap();
+        app_state.user_profile = Some(UserProfile {
+            name: "Player Two".to_string(),
+            ..Default::default()
+        });
+    }
+    harness.run(2);
+    assert_eq!(
+        harness.text_fields()[0].value.as_deref(),
+        Some("Player Two")
+    );
+
+    harness.app_state.lock().unwrap().logged_in = LoginState::LoggedOut;
+    harness.run(2);
+    assert!(harness.shows("Log in to see your profile"));
+}
+
+#[test]
+fn test_admin_console() {
+    let transport = FakeTransport::default();
+    transport.route(
+        "api/admin/users",
+        200,
+        json!([
+            { "name": "Player One", "email": "player@dummy.com", "roles": ["user"], "verified": true }
+        ]),
+    );
+    transport.route(
+        "api/admin/users/lock",
+        200,
+        json!({ "status": "success", "message": "Locked" }),
+    );
+    let mut harness = Harness::new(AdminApp::default(), transport);
+    assert!(harness.shows("Only admins can use the admin console"));
+
+    {
+        let mut app_state = harness.app_state.lock().unwrap();
+        app_state.logged_in = LoginState::LoggedIn;
+        app_state.identity = Some(Identity {
+            email: "admin@dummy.com".to_string(),
+            roles: vec!["user".to_string()],
+            ..Default::default()
+        });
+    }
+    harness.run(2);
+    assert!(harness.shows("Only admins can use the admin console"));
+    assert!(harness.transport.received("api/admin/users").is_empty());
+
+    harness
+        .app_state
+        .lock()
+        .unwrap()
+        .identity
+        .as_mut()
+        .unwrap()
+        .roles = vec!["admin".to_string()];
+    harness.run(3);
+    assert_eq!(harness.transport.received("api/admin/users").len(), 1);
+    assert!(harness.shows("player@dummy.com"));
+    assert!(harness.shows("Active"));
+
+    // nothing is sent until confirmed
+    harness.click("Lock");
+    assert!(harness.shows("Lock the account of player@dummy.com?"));
+    harness.click("Cancel");
+    assert!(!harness.shows("Lock the account of player@dummy.com?"));
+    harness.click("Lock");
+    assert!(harness
+        .transport
+        .received("api/admin/users/lock")
+        .is_empty());
+
+    harness.click("Confirm");
+    let sent = harness.transport.received("api/admin/users/lock");
+    assert_eq!(sent.len(), 1);
+    assert_eq!(json_body(&sent[0].body)["email"], "player@dummy.com");
+    assert!(harness.shows("Locked"));
+    assert!(harness.widget("Unlock").is_some());
+
+    let app_state = harness.app_state.lock().unwrap();
+    let audit: Vec<_> = app_state.audit_log.entries().collect();
+    assert_eq!(audit.len(), 1);
+    assert_eq!(audit[0].admin, "admin@dummy.com");
+    assert_eq!(audit[0].outcome, Some(Ok("Locked".to_string())));
+    // and is written out with the next flush
+    assert!(app_state.audit_dirty);
+}
+
+#[test]
+fn test_challenge_author() {
+    let transport = FakeTransport::default();
+    transport.route(
+        "api/admin/challenges",
+        200,
+        json!({ "status": "success", "message": "Challenge saved" }),
+    );
+    transport.route_get(
+        "api/admin/challenges",
+        200,
+        json!([
+            { "name": "Find the odd one out", "command": "2331", "table": "23_3_1", "doc": "# Find Odds", "published": true },
+            { "name": "How big?", "command": "2333", "table": "23_3_3", "doc": "# How Big?!", "published": true },
+            { "name": "Primes", "command": "2412", "table": "24_1_2", "doc": "# Primes", "published": false }
+        ]),
+    );
+    let mut harness = Harness::new(ChallengeAuthorApp::default(), transport);
+    assert!(harness.shows("Only admins can author challenges"));
+    assert!(harness
+        .transport
+        .received("api/admin/challenges")
+        .is_empty());
+
+    {
+        let mut app_state = harness.app_state.lock().unwrap();
+        app_state.logged_in = LoginState::LoggedIn;
+        app_state.identity = Some(Identity {
+            email: "admin@dummy.com".to_string(),
+            roles: vec!["admin".to_string()],
+            ..Default::default()
+        });
+    }
+    with_challenges(&mut harness);
+    assert_eq!(harness.transport.received("api/admin/challenges").len(), 1);
+
+    harness.type_into(0, "Sorting");
+    harness.type_into(1, "2411");
+    harness.type_into(2, "23_3_1");
+    harness.type_into(3, "# Sort these");
+    assert!(harness.shows("Sort these"));
+
+    // the table is taken, so nothing is sent
+    harness.click("Publish");
+    assert!(harness.shows("Find the odd one out already uses table 23_3_1"));
+    // drafts count too
+    harness.type_into(2, "24_1_2");
+    harness.click("Publish");
+    assert!(harness.shows("Primes already uses table 24_1_2"));
+    assert!(harness.transport.posted("api/admin/challenges").is_empty());
+
+    // another admin took it since, which only the backend knows
+    harness.type_into(2, "24_1_1");
+    harness.transport.route(
+        "api/admin/challenges",
+        409,
+        json!({ "status": "fail", "message": "table 24_1_1 is used by a draft" }),
+    );
+    harness.click("Publish");
+    harness.run(2);
+    assert!(harness.shows("Not saved, already taken: table 24_1_1 is used by a draft"));
+    assert_eq!(harness.app_state.lock().unwrap().challenges.items.len(), 2);
+
+    harness.transport.route(
+        "api/admin/challenges",
+        200,
+        json!({ "status": "success", "message": "Challenge saved" }),
+    );
+    harness.click("Publish");
+    harness.run(2);
+    let sent = harness.transport.posted("api/admin/challenges");
+    assert_eq!(sent.len(), 2);
+    match &sent[1].body {
+        Body::Form(form) => assert!(form
+            .fields
+            .contains(&("published".to_string(), FormValue::Text("true".to_string())))),
+        other => panic!("expected a form, got {:?}", other),
+    }
+    assert!(harness.shows("Published Sorting"));
+    {
+        let app_state = harness.app_state.lock().unwrap();
+        assert_eq!(app_state.challenges.items.len(), 3);
+        assert_eq!(app_state.challenges.get_table("2411".to_string()), "24_1_1");
+    }
+
+    // taking it back to a draft has to be confirmed
+    harness.click("Save Draft");
+    assert!(harness.shows("Sorting is published, as a draft players won't see it any more"));
+    assert_eq!(harness.transport.posted("api/admin/challenges").len(), 2);
+    harness.click("Unpublish");
+    harness.run(2);
+    assert!(harness.shows("Saved draft of Sorting"));
+    assert_eq!(harness.transport.posted("api/admin/challenges").len(), 3);
+    assert_eq!(harness.app_state.lock().unwrap().challenges.items.len(), 2);
+
+    // the draft can still be found and published again
+    harness.click("New");
+    assert!(!harness.shows("Sort these"));
+    harness.click("Edit");
+    assert!(harness.shows("Primes (draft)"));
+    harness.click("Sorting (draft)");
+    assert!(harness.shows("Sort these"));
+    harness.click("Publish");
+    harness.run(2);
+    assert!(harness.shows("Published Sorting"));
+    assert_eq!(harness.transport.posted("api/admin/challenges").len(), 4);
+    assert_eq!(harness.app_state.lock().unwrap().challenges.items.len(), 3);
+}
+
+#[test]
+fn test_whats_new() {
+    let mut harness = Harness::new(WhatsNewApp::default(), FakeTransport::default());
+    assert!(harness.shows("Nothing has changed yet"));
+    with_challenges(&mut harness);
+
+    {
+        let mut app_state = harness.app_state.lock().unwrap();
+        let mut items = app_state.challenges.items.clone();
+        items[0].doc = "# Find Odds\nThere may be more than one".to_string();
+        items.push(Challenge::new("Sorting", "2411", "24_1_1", "# Sort"));
+        let now = chrono::Utc::now();
+        let changes = app_state.challenges.update(items, now);
+        app_state.challenge_changes.record(changes, now);
+    }
+    harness.run(2);
+    assert!(harness.shows("New challenge: Sorting"));
+    assert!(harness.shows("Find the odd one out was changed"));
+
+    harness.click("Changes");
+    harness.run(2);
+    assert!(harness.shows("+ There may be more than one"));
+
+    harness.click("Clear");
+    harness.run(2);
+    assert!(harness.shows("Nothing has changed yet"));
+}
+
+#[test]
+fn test_submission_queue() {
+    let mut harness = Harness::new(SubmissionQueueApp::default(), FakeTransport::default());
+    assert!(harness.shows("Nothing is waiting to be sent"));
+
+    let submission = Submission {
+        challenge: Some("2331".to_string()),
+        filename: "odds.py".to_string(),
+        code: Some("print(7)".to_string()),
+        ..Default::default()
+    };
+    {
+        let mut app_state = harness.app_state.lock().unwrap();
+        app_state.identity = Some(Identity {
+            email: "player@dummy.com".to_string(),
+            ..Default::default()
+        });
+        let queue = &mut app_state.submission_queue;
+        queue.push(
+            submission.clone(),
+            "Network error: Connection refused",
+            "player@dummy.com",
+        );
+    }
+    harness.run(2);
+    assert!(harness.shows("Submission of odds.py for 2331"));
+    assert!(harness.shows("Network error: Connection refused"));
+
+    harness.click("Edit");
+    harness.type_into(0, "odds and evens.py");
+    harness.click("Save");
+    assert!(harness.shows("Filename contains invalid characters"));
+    harness.type_into(0, "evens.py");
+    harness.click("Save");
+    assert!(harness.shows("Submission of evens.py for 2331"));
+    assert_eq!(
+        harness.app_state.lock().unwrap().submission_queue.items()[0]
+            .submission()
+            .code,
+        Some("print(7)".to_string())
+    );
+
+    harness.click("Discard");
+    assert!(harness.shows("Nothing is waiting to be sent"));
+
+    // only whoever queued it sends it
+    harness.app_state.lock().unwrap().submission_queue.push(
+        submission,
+        "Network error: Connection refused",
+        "admin@dummy.com",
+    );
+    harness.run(2);
+    assert!(harness.shows("Held until admin@dummy.com logs in"));
+    assert!(!harness.shows("Network error: Connection refused"));
+    harness.click("Discard");
+    harness.click("Edit");
+    assert_eq!(
+        harness
+            .app_state
+            .lock()
+            .unwrap()
+            .submission_queue
+            .items()
+            .len(),
+        1
+    );
+    assert!(harness.text_fields().is_empty());
+}
+
+#[test]
+fn test_submission_history() {
+    let transport = FakeTransport::default();
+    transport.route(
+        "api/game/submissions",
+        200,
+        json!([
+            {
+                "challenge": "2331", "filename": "odds.py", "language": "Python", "test": false,
+                "submitted_at": "2024-01-01T09:30:00Z", "score": 1500,
+                "message": "Passed all tests", "code": "print(7)"
+            },
+            {
+                "challenge": "2333", "filename": "big", "language": "Rust", "test": true,
+                "submitted_at": "2024-01-02T10:00:00Z", "message": "Wrong answer"
+            }
+        ]),
+    );
+    transport.route(
+        "api/game/submit",
+        200,
+        json!({ "Success": { "score": 1400, "message": "Faster this time" } }),
+    );
+    let mut harness = Harness::new(SubmissionHistoryApp::default(), transport);
+    assert!(harness.shows("Nothing submitted yet"));
+    assert!(harness
+        .transport
+        .received("api/game/submissions")
+        .is_empty());
+
+    harness.app_state.lock().unwrap().logged_in = LoginState::LoggedIn;
+    harness.run(2);
+    assert_eq!(harness.transport.received("api/game/submissions").len(), 1);
+    assert!(harness.shows("odds.py"));
+    assert!(harness.shows("Wrong answer"));
+    assert_eq!(
+        harness
+            .app_state
+            .lock()
+            .unwrap()
+            .submission_history
+            .records()
+            .len(),
+        2
+    );
+
+    // rows are labelled in local time
+    let when = "2024-01-01T09:30:00Z"
+        .parse::<chrono::DateTime<chrono::Utc>>()
+        .unwrap()
+        .with_timezone(&chrono::Local)
+        .format("%d %b %H:%M")
+        .to_string();
+    harness.click(&when);
+    assert!(harness.shows("print(7)"));
+    harness.click("Open in Editor");
+    let link = harness.app_state.lock().unwrap().deep_link.clone();
+    assert!(matches!(
+        &link,
+        Some(DeepLink::EditSubmission { submission }) if submission.filename == "odds.py"
+    ));
+
+    harness.click("Resubmit");
+    harness.run(2);
+    let sent = harness.transport.received("api/game/submit");
+    assert_eq!(sent.len(), 1);
+    assert_eq!(json_body(&sent[0].body)["code"], "print(7)");
+    assert!(harness.shows("Faster this time"));
+
+    // someone else logging in gets their own
+    session::set_identity(
+        &harness.app_state,
+        Some(Identity {
+            email: "admin@dummy.com".to_string(),
+            ..Default::default()
+        }),
+    );
+    assert!(harness
+        .app_state
+        .lock()
+        .unwrap()
+        .submission_history
+        .is_empty());
+    harness.run(2);
+    assert_eq!(harness.transport.received("api/game/submissions").len(), 2);
+    assert!(harness.shows("Wrong answer"));
+
+    AppState::set_logged_out(&harness.app_state);
+    harness.run(2);
+    assert!(harness.shows("Nothing submitted yet"));
+
+    let mut editor = Harness::new(CodeEditor::default(), FakeTransport::default());
+    with_challenges(&mut editor);
+    editor.app_state.lock().unwrap().deep_link = link;
+    editor.run(2);
+    assert!(editor.shows("odds.py"));
+    assert!(editor.shows("print(7)"));
+}
@@ -0,0 +1,305 @@
+//! Runs a window headless, one frame at a time.
+//!
+//! Widgets are found through the accessibility tree egui builds each frame,
+//! so tests click and type by label the way a user would. Everything that is
+//! painted, toasts included, can be checked with [`Harness::shows`].
+
+use challenge_frontend::apps::App;
+use challenge_frontend::helpers::{
+    retry::RetryPolicy,
+    transport::{HttpMethod, HttpRequest, HttpResponse, InFlight, Transport},
+    AppState,
+};
+use egui::accesskit::Role;
+use egui::{Event, Key, Modifiers, PointerButton, Pos2, RawInput, Rect, Shape, Vec2};
+use poll_promise::Promise;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+const SCREEN: Vec2 = Vec2::new(1280.0, 1024.0);
+const FRAME_TIME: f64 = 1.0 / 60.0;
+
+/// Answers every request straight away with whatever was routed to its path.
+#[derive(Default)]
+pub struct FakeTransport {
+    routes: Mutex<HashMap<String, (u16, String)>>,
+    get_routes: Mutex<HashMap<String, (u16, String)>>,
+    received: Mutex<Vec<HttpRequest>>,
+    offline: Mutex<bool>,
+}
+
+impl FakeTransport {
+    /// Requests to URLs containing `path` get `body` from now on.
+    pub fn route(&self, path: &str, status: u16, body: serde_json::Value) {
+        self.routes
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), (status, body.to_string()));
+    }
+
+    /// Like [`FakeTransport::route`] but only for GETs, which it answers instead.
+    pub fn route_get(&self, path: &str, status: u16, body: serde_json::Value) {
+        self.get_routes
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), (status, body.to_string()));
+    }
+
+    /// While offline every request fails as if the backend couldn't be reached.
+    pub fn set_offline(&self, offline: bool) {
+        *self.offline.lock().unwrap() = offline;
+    }
+
+    /// Everything sent to a URL containing `path` so far.
+    pub fn received(&self, path: &str) -> Vec<HttpRequest> {
+        self.received
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| r.url.contains(path))
+            .cloned()
+            .collect()
+    }
+
+    /// Only what was posted to a URL containing `path`.
+    pub fn posted(&self, path: &str) -> Vec<HttpRequest> {
+        self.received(path)
+            .into_iter()
+            .filter(|r| r.method == HttpMethod::Post)
+            .collect()
+    }
+
+    fn respond(&self, request: &HttpRequest) -> HttpResponse {
+        // the longest matching path is the most specific
+        let find = |routes: &HashMap<String, (u16, String)>| {
+            routes
+                .iter()
+                .filter(|(path, _)| request.url.contains(path.as_str()))
+                .max_by_key(|(path, _)| path.len())
+                .map(|(_, reply)| reply.clone())
+        };
+        let get = match request.method {
+            HttpMethod::Get => find(&self.get_routes.lock().unwrap()),
+            HttpMethod::Post => None,
+        };
+        let (status, body) = get
+            .or_else(|| find(&self.routes.lock().unwrap()))
+            .unwrap_or_else(|| {
+                let body = serde_json::json!({ "status": "fail", "message": "Not found" });
+                (404, body.to_string())
+            });
+        HttpResponse {
+            status,
+            headers: vec![],
+            body: body.into_bytes(),
+        }
+    }
+}
+
+impl Transport for FakeTransport {
+    fn fetch(&self, request: HttpRequest) -> InFlight {
+        let response = match *self.offline.lock().unwrap() {
+            true => Err("Connection refused".to_string()),
+            false => Ok(self.respond(&request)),
+        };
+        self.received.lock().unwrap().push(request);
+        InFlight::new(Promise::from_ready(response))
+    }
+}
+
+/// A widget as it was in the last frame.
+#[derive(Clone, Debug)]
+pub struct Widget {
+    pub role: Role,
+    pub name: Option<String>,
+    pub value: Option<String>,
+    pub rect: Rect,
+}
+
+pub struct Harness<A: App> {
+    pub app: A,
+    pub app_state: Arc<Mutex<AppState>>,
+    pub transport: Arc<FakeTransport>,
+    pub open: bool,
+    ctx: egui::Context,
+    time: f64,
+    widgets: Vec<Widget>,
+    texts: Vec<String>,
+}
+
+impl<A: App> Harness<A> {
+    /// Requests are answered by `transport` and never retried.
+    pub fn new(mut app: A, transport: FakeTransport) -> Self {
+        let transport = Arc::new(transport);
+        let mut app_state = AppState::default();
+        app_state.transport = transport.clone();
+        app_state.config.retry = RetryPolicy {
+            max_retries: 0,
+            ..Default::default()
+        };
+        let app_state = Arc::new(Mutex::new(app_state));
+        app.set_app_state_ref(app_state.clone());
+
+        let ctx = egui::Context::default();
+        ctx.enable_accesskit();
+
+        let mut harness = Self {
+            app,
+            app_state,
+            transport,
+            open: true,
+            ctx,
+            time: 0.0,
+            widgets: vec![],
+            texts: vec![],
+        };
+        // windows are sized on their first frame and shown on the next
+        harness.run(3);
+        harness
+    }
+
+    pub fn run(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.step(vec![]);
+        }
+    }
+
+    /// One frame with `events` as its input.
+    pub fn step(&mut self, events: Vec<Event>) {
+        let input = RawInput {
+            screen_rect: Some(Rect::from_min_size(Pos2::ZERO, SCREEN)),
+            time: Some(self.time),
+            predicted_dt: FRAME_TIME as f32,
+            events,
+            ..Default::default()
+        };
+        self.time += FRAME_TIME;
+
+        let (app, open) = (&mut self.app, &mut self.open);
+        let output = self.ctx.run(input, |ctx| app.show(ctx, open));
+
+        self.widgets = output
+            .platform_output
+            .accesskit_update
+            .map(|update| {
+                update
+                    .nodes
+                    .iter()
+                    .filter_map(|(_, node)| {
+                        let bounds = node.bounds()?;
+                        Some(Widget {
+                            role: node.role(),
+                            name: node.name().map(str::to_string),
+                            value: node.value().map(str::to_string),
+                            rect: Rect::from_min_max(
+                                Pos2::new(bounds.x0 as f32, bounds.y0 as f32),
+                                Pos2::new(bounds.x1 as f32, bounds.y1 as f32),
+                            ),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        self.texts.clear();
+        for clipped in &output.shapes {
+            collect_text(&clipped.shape, &mut self.texts);
+        }
+    }
+
+    pub fn widget(&self, name: &str) -> Option<&Widget> {
+        self.widgets
+            .iter()
+            .find(|w| w.name.as_deref() == Some(name))
+    }
+
+    /// Text fields from top to bottom.
+    pub fn text_fields(&self) -> Vec<&Widget> {
+        let mut fields: Vec<&Widget> = self
+            .widgets
+            .iter()
+            .filter(|w| w.role == Role::TextField)
+            .collect();
+        fields.sort_by(|a, b| {
+            (a.rect.min.y, a.rect.min.x)
+                .partial_cmp(&(b.rect.min.y, b.rect.min.x))
+                .unwrap()
+        });
+        fields
+    }
+
+    /// Whether any painted text contains `text`.
+    pub fn shows(&self, text: &str) -> bool {
+        self.texts.iter().any(|t| t.contains(text))
+    }
+
+    /// Clicks the widget called `name`, skipping any label of the same name.
+    pub fn click(&mut self, name: &str) {
+        let widget = self
+            .widgets
+            .iter()
+            .find(|w| w.name.as_deref() == Some(name) && w.role != Role::StaticText)
+            .unwrap_or_else(|| panic!("no widget named {:?} in {:#?}", name, self.widgets))
+            .clone();
+        self.click_at(widget.rect.center());
+    }
+
+    /// Replaces what is in the `index`th text field from the top with `text`.
+    pub fn type_into(&mut self, index: usize, text: &str) {
+        let field = self
+            .text_fields()
+            .get(index)
+            .map(|w| w.rect.center())
+            .unwrap_or_else(|| panic!("no text field {}", index));
+        self.click_at(field);
+        self.step(vec![
+            Event::Key {
+                key: Key::A,
+                pressed: true,
+                repeat: false,
+                modifiers: Modifiers::COMMAND,
+            },
+            Event::Text(text.to_string()),
+        ]);
+        self.run(1);
+    }
+
+    /// Presses and releases `key`, wherever the focus is.
+    pub fn press(&mut self, key: Key) {
+        let event = |pressed| Event::Key {
+            key,
+            pressed,
+            repeat: false,
+            modifiers: Modifiers::NONE,
+        };
+        self.step(vec![event(true)]);
+        self.step(vec![event(false)]);
+        self.run(1);
+    }
+
+    fn click_at(&mut self, pos: Pos2) {
+        let button = |pressed| Event::PointerButton {
+            pos,
+            button: PointerButton::Primary,
+            pressed,
+            modifiers: Modifiers::NONE,
+        };
+        self.step(vec![Event::PointerMoved(pos)]);
+        self.step(vec![button(true)]);
+        self.step(vec![button(false)]);
+        // let the window react to the click
+        self.run(2);
+    }
+}
+
+fn collect_text(shape: &Shape, texts: &mut Vec<String>) {
+    match shape {
+        Shape::Text(text) => texts.push(text.galley.text().to_string()),
+        Shape::Vec(shapes) => {
+            for shape in shapes {
+                collect_text(shape, texts);
+            }
+        }
+        _ => {}
+    }
+}